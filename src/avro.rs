@@ -0,0 +1,945 @@
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::InvalidArgument;
+use crate::stats::decimal_from_bytes;
+use crate::utils::{open_file, BATCH_SIZE};
+use arrow::array::{
+    as_boolean_array, as_large_list_array, as_list_array, as_primitive_array,
+    as_string_array, as_struct_array, make_array, Array, ArrayData, ArrayRef,
+    BinaryArray, BooleanArray, BooleanBufferBuilder, Date32Array, DecimalArray,
+    DecimalBuilder, FixedSizeBinaryArray, FixedSizeBinaryBuilder, Float32Array,
+    Float64Array, Int32Array, Int64Array, LargeBinaryArray, LargeStringArray,
+    StringArray, StructArray, Time32MillisecondArray, Time64MicrosecondArray,
+    TimestampMicrosecondArray, TimestampMillisecondArray,
+};
+use arrow::buffer::Buffer;
+use arrow::datatypes::*;
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use avro_rs::schema::UnionSchema;
+use avro_rs::types::Value as AvroValue;
+use avro_rs::{Decimal, Duration, Reader, Schema as AvroSchema, Writer};
+use log::debug;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+/// The key of the parquet metadata that holds the avro schema of imported files. The
+/// key used by parquet-avro is also checked when exporting.
+pub static AVRO_SCHEMA_KEY: &str = "avro.schema";
+static PARQUET_AVRO_SCHEMA_KEY: &str = "parquet.avro.schema";
+
+/// Map the avro schema of a file to an arrow schema, the schema must be a record.
+///
+/// Arrays are mapped to lists, maps to lists of key/value structs (the way maps are
+/// stored in parquet), unions of null and a single type to nullable fields and other
+/// unions to structs with one nullable `member<N>` field for every type of the union.
+pub fn to_arrow_schema(schema: &AvroSchema) -> Result<Schema, PQRSError> {
+    match schema {
+        AvroSchema::Record { fields, .. } => Ok(Schema::new(
+            fields
+                .iter()
+                .map(|field| to_arrow_field(&field.name, &field.schema))
+                .collect::<Result<Vec<Field>, PQRSError>>()?,
+        )),
+        _ => Err(InvalidArgument(String::from(
+            "The schema of the avro file must be a record",
+        ))),
+    }
+}
+
+fn to_arrow_field(name: &str, schema: &AvroSchema) -> Result<Field, PQRSError> {
+    let (schema, nullable) = match schema {
+        AvroSchema::Union(union) => match non_null_variants(union).as_slice() {
+            [(_, schema)] => (*schema, true),
+            _ => (schema, union.is_nullable()),
+        },
+        schema => (schema, false),
+    };
+
+    let data_type = match schema {
+        AvroSchema::Boolean => DataType::Boolean,
+        AvroSchema::Int => DataType::Int32,
+        AvroSchema::Long => DataType::Int64,
+        AvroSchema::Float => DataType::Float32,
+        AvroSchema::Double => DataType::Float64,
+        AvroSchema::Bytes => DataType::Binary,
+        AvroSchema::String | AvroSchema::Enum { .. } | AvroSchema::Uuid => DataType::Utf8,
+        AvroSchema::Fixed { size, .. } => DataType::FixedSizeBinary(*size as i32),
+        AvroSchema::Duration => DataType::FixedSizeBinary(12),
+        AvroSchema::Decimal {
+            precision, scale, ..
+        } => DataType::Decimal(*precision, *scale),
+        AvroSchema::Date => DataType::Date32,
+        AvroSchema::TimeMillis => DataType::Time32(TimeUnit::Millisecond),
+        AvroSchema::TimeMicros => DataType::Time64(TimeUnit::Microsecond),
+        AvroSchema::TimestampMillis => DataType::Timestamp(TimeUnit::Millisecond, None),
+        AvroSchema::TimestampMicros => DataType::Timestamp(TimeUnit::Microsecond, None),
+        AvroSchema::Array(items) => {
+            DataType::List(Box::new(to_arrow_field("item", items)?))
+        }
+        AvroSchema::Map(values) => DataType::List(Box::new(Field::new(
+            "key_value",
+            DataType::Struct(vec![
+                Field::new("key", DataType::Utf8, false),
+                to_arrow_field("value", values)?,
+            ]),
+            false,
+        ))),
+        AvroSchema::Union(union) => DataType::Struct(
+            non_null_variants(union)
+                .iter()
+                .map(|(index, schema)| {
+                    let member = to_arrow_field(&format!("member{}", index), schema)?;
+                    Ok(Field::new(member.name(), member.data_type().clone(), true))
+                })
+                .collect::<Result<Vec<Field>, PQRSError>>()?,
+        ),
+        AvroSchema::Record { fields, .. } => DataType::Struct(
+            fields
+                .iter()
+                .map(|field| to_arrow_field(&field.name, &field.schema))
+                .collect::<Result<Vec<Field>, PQRSError>>()?,
+        ),
+        AvroSchema::Null => {
+            return Err(InvalidArgument(format!(
+                "The field {} has the null type, which cannot be stored in parquet",
+                name
+            )))
+        }
+    };
+
+    Ok(Field::new(name, data_type, nullable))
+}
+
+/// The types of the union other than null, along with their position in the union
+fn non_null_variants(union: &UnionSchema) -> Vec<(usize, &AvroSchema)> {
+    union
+        .variants()
+        .iter()
+        .enumerate()
+        .filter(|(_, schema)| **schema != AvroSchema::Null)
+        .collect()
+}
+
+/// Read the records of the avro file one batch at a time, converted to the given arrow
+/// schema as returned by `to_arrow_schema`
+pub fn read_batches<'a, R: Read + 'a>(
+    reader: Reader<'a, R>,
+    schema: &'a AvroSchema,
+    arrow_schema: SchemaRef,
+) -> impl Iterator<Item = ArrowResult<RecordBatch>> + 'a {
+    let mut records = reader.peekable();
+    std::iter::from_fn(move || {
+        records.peek()?;
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for record in records.by_ref().take(BATCH_SIZE) {
+            match record {
+                Ok(record) => batch.push(record),
+                Err(e) => return Some(Err(ArrowError::ExternalError(Box::new(e)))),
+            }
+        }
+        Some(build_batch(&batch, schema, &arrow_schema))
+    })
+}
+
+fn build_batch(
+    records: &[AvroValue],
+    schema: &AvroSchema,
+    arrow_schema: &SchemaRef,
+) -> ArrowResult<RecordBatch> {
+    let fields = match schema {
+        AvroSchema::Record { fields, .. } => fields,
+        _ => return Err(ArrowError::SchemaError(String::from("Expected a record"))),
+    };
+
+    let columns = fields
+        .iter()
+        .zip(arrow_schema.fields())
+        .map(|(field, arrow_field)| {
+            let values: Vec<Option<&AvroValue>> = records
+                .iter()
+                .map(|record| record_field(record, &field.name))
+                .collect();
+            build_array(&values, &field.schema, arrow_field)
+        })
+        .collect::<ArrowResult<Vec<ArrayRef>>>()?;
+
+    RecordBatch::try_new(arrow_schema.clone(), columns)
+}
+
+fn record_field<'a>(record: &'a AvroValue, name: &str) -> Option<&'a AvroValue> {
+    match record {
+        AvroValue::Record(fields) => {
+            fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+        }
+        _ => None,
+    }
+}
+
+/// Build an arrow array from the values of a single field, missing values are nulls
+fn build_array(
+    values: &[Option<&AvroValue>],
+    schema: &AvroSchema,
+    field: &Field,
+) -> ArrowResult<ArrayRef> {
+    if let AvroSchema::Union(union) = schema {
+        let values: Vec<Option<&AvroValue>> = values
+            .iter()
+            .map(|value| match value {
+                Some(AvroValue::Union(inner)) if **inner == AvroValue::Null => None,
+                Some(AvroValue::Union(inner)) => Some(inner.as_ref()),
+                Some(AvroValue::Null) => None,
+                value => *value,
+            })
+            .collect();
+
+        let variants = non_null_variants(union);
+        if let [(_, schema)] = variants.as_slice() {
+            return build_array(&values, schema, field);
+        }
+
+        // every type of the union is stored in its own member of a struct
+        let members = match field.data_type() {
+            DataType::Struct(members) => members,
+            _ => return Err(unexpected_type(field)),
+        };
+        let children = variants
+            .iter()
+            .zip(members)
+            .map(|((index, schema), member)| {
+                let member_values: Vec<Option<&AvroValue>> = values
+                    .iter()
+                    .map(|value| {
+                        value.filter(|v| {
+                            union.find_schema(v).map(|(i, _)| i) == Some(*index)
+                        })
+                    })
+                    .collect();
+                Ok((member.clone(), build_array(&member_values, schema, member)?))
+            })
+            .collect::<ArrowResult<Vec<(Field, ArrayRef)>>>()?;
+        return Ok(build_struct(children, &values));
+    }
+
+    macro_rules! primitive {
+        ($array:ty, $pattern:pat => $value:expr) => {
+            Arc::new(<$array>::from(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        Some($pattern) => Some($value),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+            )) as ArrayRef
+        };
+    }
+
+    let array = match schema {
+        AvroSchema::Boolean => primitive!(BooleanArray, AvroValue::Boolean(v) => *v),
+        AvroSchema::Int => primitive!(Int32Array, AvroValue::Int(v) => *v),
+        AvroSchema::Long => primitive!(Int64Array, AvroValue::Long(v) => *v),
+        AvroSchema::Float => primitive!(Float32Array, AvroValue::Float(v) => *v),
+        AvroSchema::Double => primitive!(Float64Array, AvroValue::Double(v) => *v),
+        AvroSchema::Date => primitive!(Date32Array, AvroValue::Date(v) => *v),
+        AvroSchema::TimeMillis => {
+            primitive!(Time32MillisecondArray, AvroValue::TimeMillis(v) => *v)
+        }
+        AvroSchema::TimeMicros => {
+            primitive!(Time64MicrosecondArray, AvroValue::TimeMicros(v) => *v)
+        }
+        AvroSchema::TimestampMillis => Arc::new(TimestampMillisecondArray::from_opt_vec(
+            values
+                .iter()
+                .map(|value| match value {
+                    Some(AvroValue::TimestampMillis(v)) => Some(*v),
+                    _ => None,
+                })
+                .collect(),
+            None,
+        )),
+        AvroSchema::TimestampMicros => Arc::new(TimestampMicrosecondArray::from_opt_vec(
+            values
+                .iter()
+                .map(|value| match value {
+                    Some(AvroValue::TimestampMicros(v)) => Some(*v),
+                    _ => None,
+                })
+                .collect(),
+            None,
+        )),
+        AvroSchema::Bytes => primitive!(BinaryArray, AvroValue::Bytes(v) => v.as_slice()),
+        AvroSchema::String | AvroSchema::Enum { .. } | AvroSchema::Uuid => {
+            Arc::new(StringArray::from(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        Some(AvroValue::String(s)) | Some(AvroValue::Enum(_, s)) => {
+                            Some(s.clone())
+                        }
+                        Some(AvroValue::Uuid(uuid)) => Some(uuid.to_string()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+            ))
+        }
+        AvroSchema::Fixed { size, .. } => {
+            let mut builder = FixedSizeBinaryBuilder::new(values.len(), *size as i32);
+            for value in values {
+                match value {
+                    Some(AvroValue::Fixed(_, bytes)) => builder.append_value(bytes)?,
+                    _ => builder.append_null()?,
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        AvroSchema::Duration => {
+            let mut builder = FixedSizeBinaryBuilder::new(values.len(), 12);
+            for value in values {
+                match value {
+                    Some(AvroValue::Duration(duration)) => {
+                        builder.append_value(&<[u8; 12]>::from(*duration))?
+                    }
+                    _ => builder.append_null()?,
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        AvroSchema::Decimal {
+            precision, scale, ..
+        } => {
+            let mut builder = DecimalBuilder::new(values.len(), *precision, *scale);
+            for value in values {
+                match value {
+                    Some(AvroValue::Decimal(decimal)) => {
+                        let bytes = Vec::<u8>::try_from(decimal)
+                            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+                        builder.append_value(decimal_from_bytes(&bytes))?
+                    }
+                    _ => builder.append_null()?,
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        AvroSchema::Array(item_schema) => {
+            let item_field = match field.data_type() {
+                DataType::List(item) => item,
+                _ => return Err(unexpected_type(field)),
+            };
+            let lists: Vec<Option<Vec<&AvroValue>>> = values
+                .iter()
+                .map(|value| match value {
+                    Some(AvroValue::Array(items)) => Some(items.iter().collect()),
+                    _ => None,
+                })
+                .collect();
+            let items: Vec<Option<&AvroValue>> = lists
+                .iter()
+                .flatten()
+                .flat_map(|items| items.iter().map(|item| Some(*item)))
+                .collect();
+            let child = build_array(&items, item_schema, item_field)?;
+            build_list(field, &lists, child)
+        }
+        AvroSchema::Map(value_schema) => {
+            let entry_field = match field.data_type() {
+                DataType::List(entry) => entry,
+                _ => return Err(unexpected_type(field)),
+            };
+            let value_field = match entry_field.data_type() {
+                DataType::Struct(fields) => &fields[1],
+                _ => return Err(unexpected_type(entry_field)),
+            };
+            // the entries are sorted by key so that the output does not depend on
+            // the order of the hash map
+            let maps: Vec<Option<Vec<(&String, &AvroValue)>>> = values
+                .iter()
+                .map(|value| match value {
+                    Some(AvroValue::Map(entries)) => {
+                        let mut entries: Vec<_> = entries.iter().collect();
+                        entries.sort_by(|a, b| a.0.cmp(b.0));
+                        Some(entries)
+                    }
+                    _ => None,
+                })
+                .collect();
+            let entries: Vec<&(&String, &AvroValue)> =
+                maps.iter().flatten().flatten().collect();
+            let keys = StringArray::from(
+                entries
+                    .iter()
+                    .map(|(key, _)| key.as_str())
+                    .collect::<Vec<&str>>(),
+            );
+            let entry_values: Vec<Option<&AvroValue>> =
+                entries.iter().map(|(_, value)| Some(*value)).collect();
+            let child = StructArray::from(vec![
+                (
+                    Field::new("key", DataType::Utf8, false),
+                    Arc::new(keys) as ArrayRef,
+                ),
+                (
+                    value_field.clone(),
+                    build_array(&entry_values, value_schema, value_field)?,
+                ),
+            ]);
+            build_list(field, &maps, Arc::new(child))
+        }
+        AvroSchema::Record { fields, .. } => {
+            let children = match field.data_type() {
+                DataType::Struct(children) => children,
+                _ => return Err(unexpected_type(field)),
+            };
+            let columns = fields
+                .iter()
+                .zip(children)
+                .map(|(field, child)| {
+                    let child_values: Vec<Option<&AvroValue>> = values
+                        .iter()
+                        .map(|value| value.and_then(|v| record_field(v, &field.name)))
+                        .collect();
+                    Ok((
+                        child.clone(),
+                        build_array(&child_values, &field.schema, child)?,
+                    ))
+                })
+                .collect::<ArrowResult<Vec<(Field, ArrayRef)>>>()?;
+            build_struct(columns, values)
+        }
+        AvroSchema::Union(_) | AvroSchema::Null => return Err(unexpected_type(field)),
+    };
+
+    Ok(array)
+}
+
+fn unexpected_type(field: &Field) -> ArrowError {
+    ArrowError::SchemaError(format!(
+        "Unexpected type {:?} for the field {}",
+        field.data_type(),
+        field.name()
+    ))
+}
+
+/// Build a struct array, the struct is null wherever the value is missing
+fn build_struct(
+    columns: Vec<(Field, ArrayRef)>,
+    values: &[Option<&AvroValue>],
+) -> ArrayRef {
+    let mut validity = BooleanBufferBuilder::new(values.len());
+    for value in values {
+        validity.append(value.is_some());
+    }
+    Arc::new(StructArray::from((columns, validity.finish())))
+}
+
+/// Build a list array from the number of items in every list and the array of all the
+/// items one after the other
+fn build_list<T>(field: &Field, lists: &[Option<Vec<T>>], items: ArrayRef) -> ArrayRef {
+    let mut offsets: Vec<i32> = Vec::with_capacity(lists.len() + 1);
+    let mut validity = BooleanBufferBuilder::new(lists.len());
+    offsets.push(0);
+    for list in lists {
+        let length = list.as_ref().map_or(0, |items| items.len());
+        offsets.push(offsets[offsets.len() - 1] + length as i32);
+        validity.append(list.is_some());
+    }
+
+    let data = ArrayData::builder(field.data_type().clone())
+        .len(lists.len())
+        .add_buffer(Buffer::from_slice_ref(&offsets))
+        .add_child_data(items.data().clone())
+        .null_bit_buffer(validity.finish())
+        .build();
+    make_array(data)
+}
+
+/// Return the avro schema used to export the parquet file. The schema stored in the
+/// metadata of files imported from avro is used when all the columns are exported,
+/// otherwise the schema is derived from the arrow schema.
+pub fn get_avro_schema(
+    file_name: &str,
+    schema: &Schema,
+    use_metadata: bool,
+) -> Result<AvroSchema, PQRSError> {
+    if use_metadata {
+        let reader = SerializedFileReader::new(open_file(file_name)?)?;
+        let metadata = reader.metadata().file_metadata().key_value_metadata();
+        let stored = metadata
+            .iter()
+            .flat_map(|m| m.iter())
+            .find(|kv| kv.key == AVRO_SCHEMA_KEY || kv.key == PARQUET_AVRO_SCHEMA_KEY);
+        if let Some(value) = stored.and_then(|kv| kv.value.as_ref()) {
+            debug!("Using the avro schema from the metadata: {}", value);
+            return Ok(AvroSchema::parse_str(value)?);
+        }
+    }
+
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| to_avro_field(field, ""))
+        .collect::<Result<Vec<Value>, PQRSError>>()?;
+    let record = json!({"type": "record", "name": "root", "fields": fields});
+    Ok(AvroSchema::parse(&record)?)
+}
+
+fn to_avro_field(field: &Field, prefix: &str) -> Result<Value, PQRSError> {
+    // named types need a unique name, the path of the field is used
+    let path = format!("{}{}", prefix, field.name());
+    let avro_type = match field.data_type() {
+        DataType::Boolean => json!("boolean"),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 => json!("int"),
+        DataType::UInt8 | DataType::UInt16 => json!("int"),
+        DataType::Int64 | DataType::UInt32 => json!("long"),
+        // avro does not have unsigned types, a decimal can hold all the 64 bit values
+        DataType::UInt64 => json!({
+            "type": "bytes", "logicalType": "decimal", "precision": 20, "scale": 0
+        }),
+        DataType::Float16 | DataType::Float32 => json!("float"),
+        DataType::Float64 => json!("double"),
+        DataType::Utf8 | DataType::LargeUtf8 => json!("string"),
+        DataType::Binary | DataType::LargeBinary => json!("bytes"),
+        DataType::FixedSizeBinary(size) => {
+            json!({"type": "fixed", "name": path.replace('.', "_"), "size": size})
+        }
+        DataType::Decimal(precision, scale) => json!({
+            "type": "bytes", "logicalType": "decimal", "precision": precision, "scale": scale
+        }),
+        DataType::Date32 => json!({"type": "int", "logicalType": "date"}),
+        DataType::Date64 => json!({"type": "long", "logicalType": "timestamp-millis"}),
+        DataType::Time32(_) => json!({"type": "int", "logicalType": "time-millis"}),
+        DataType::Time64(_) => json!({"type": "long", "logicalType": "time-micros"}),
+        DataType::Timestamp(TimeUnit::Second, _)
+        | DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            json!({"type": "long", "logicalType": "timestamp-millis"})
+        }
+        DataType::Timestamp(_, _) => {
+            json!({"type": "long", "logicalType": "timestamp-micros"})
+        }
+        DataType::List(item) | DataType::LargeList(item) => {
+            let item = to_avro_field(item, &format!("{}.", path))?;
+            json!({"type": "array", "items": item["type"]})
+        }
+        DataType::Struct(children) => json!({
+            "type": "record",
+            "name": path.replace('.', "_"),
+            "fields": children
+                .iter()
+                .map(|child| to_avro_field(child, &format!("{}.", path)))
+                .collect::<Result<Vec<Value>, PQRSError>>()?,
+        }),
+        data_type => {
+            return Err(InvalidArgument(format!(
+                "The column {} has the type {:?} which cannot be exported to avro",
+                path, data_type
+            )))
+        }
+    };
+
+    let avro_type = if field.is_nullable() {
+        json!(["null", avro_type])
+    } else {
+        avro_type
+    };
+    Ok(json!({"name": field.name(), "type": avro_type}))
+}
+
+/// Write the batches to an avro object container file with the given schema and return
+/// the number of records written
+pub fn write_avro<W, I>(
+    output: W,
+    schema: &AvroSchema,
+    batches: I,
+) -> Result<usize, PQRSError>
+where
+    W: Write,
+    I: IntoIterator<Item = ArrowResult<RecordBatch>>,
+{
+    let mut writer = Writer::new(schema, output);
+    let mut rows = 0;
+    for batch in batches {
+        let batch = batch?;
+        // the columns of the batch are the fields of the top level record
+        let records: ArrayRef = Arc::new(StructArray::from(batch));
+        for index in 0..records.len() {
+            writer.append(to_avro_value(&records, index, schema)?)?;
+        }
+        rows += records.len();
+    }
+    writer.flush()?;
+
+    Ok(rows)
+}
+
+/// Convert a single value of the arrow array to the given avro schema
+fn to_avro_value(
+    array: &ArrayRef,
+    index: usize,
+    schema: &AvroSchema,
+) -> Result<AvroValue, PQRSError> {
+    if let AvroSchema::Union(union) = schema {
+        if array.is_null(index) {
+            return Ok(AvroValue::Union(Box::new(AvroValue::Null)));
+        }
+
+        let value = match non_null_variants(union).as_slice() {
+            [(_, schema)] => to_avro_value(array, index, schema)?,
+            variants => {
+                // the first member that is set holds the value of the union
+                let members = as_struct_array(array);
+                let member = variants
+                    .iter()
+                    .zip(members.columns())
+                    .find(|(_, member)| !member.is_null(index));
+                match member {
+                    Some(((_, schema), member)) => to_avro_value(member, index, schema)?,
+                    None => AvroValue::Null,
+                }
+            }
+        };
+        return Ok(AvroValue::Union(Box::new(value)));
+    }
+
+    if array.is_null(index) {
+        return Ok(AvroValue::Null);
+    }
+
+    let value = match schema {
+        AvroSchema::Null => AvroValue::Null,
+        AvroSchema::Boolean => AvroValue::Boolean(as_boolean_array(array).value(index)),
+        AvroSchema::Int => AvroValue::Int(narrow(integer(array, index)?, schema)?),
+        AvroSchema::Long => AvroValue::Long(integer(array, index)?),
+        AvroSchema::Float => AvroValue::Float(float(array, index)? as f32),
+        AvroSchema::Double => AvroValue::Double(float(array, index)?),
+        AvroSchema::Date => AvroValue::Date(narrow(integer(array, index)?, schema)?),
+        AvroSchema::TimeMillis => AvroValue::TimeMillis(narrow(
+            time(array, index, TimeUnit::Millisecond)?,
+            schema,
+        )?),
+        AvroSchema::TimeMicros => {
+            AvroValue::TimeMicros(time(array, index, TimeUnit::Microsecond)?)
+        }
+        AvroSchema::TimestampMillis => {
+            AvroValue::TimestampMillis(time(array, index, TimeUnit::Millisecond)?)
+        }
+        AvroSchema::TimestampMicros => {
+            AvroValue::TimestampMicros(time(array, index, TimeUnit::Microsecond)?)
+        }
+        AvroSchema::String | AvroSchema::Uuid => AvroValue::String(string(array, index)?),
+        AvroSchema::Enum { symbols, .. } => {
+            let symbol = string(array, index)?;
+            match symbols.iter().position(|s| *s == symbol) {
+                Some(position) => AvroValue::Enum(position as i32, symbol),
+                None => {
+                    return Err(InvalidArgument(format!(
+                        "{} is not a symbol of the enum",
+                        symbol
+                    )))
+                }
+            }
+        }
+        AvroSchema::Bytes => AvroValue::Bytes(bytes(array, index)?),
+        AvroSchema::Fixed { size, .. } => AvroValue::Fixed(*size, bytes(array, index)?),
+        AvroSchema::Duration => {
+            let value =
+                <[u8; 12]>::try_from(bytes(array, index)?.as_slice()).map_err(|_| {
+                    InvalidArgument(String::from("A duration must be 12 bytes"))
+                })?;
+            AvroValue::Duration(Duration::from(value))
+        }
+        AvroSchema::Decimal { inner, .. } => {
+            let unscaled = match array.data_type() {
+                DataType::UInt64 => {
+                    as_primitive_array::<UInt64Type>(array).value(index) as i128
+                }
+                _ => array
+                    .as_any()
+                    .downcast_ref::<DecimalArray>()
+                    .ok_or_else(|| unsupported(array, schema))?
+                    .value(index),
+            };
+            // fixed decimals use all the bytes of the type, sign extended
+            let size = match inner.as_ref() {
+                AvroSchema::Fixed { size, .. } => *size,
+                _ => minimal_length(unscaled),
+            };
+            let bytes = unscaled.to_be_bytes();
+            AvroValue::Decimal(Decimal::from(
+                bytes[bytes.len() - size.min(16)..].to_vec(),
+            ))
+        }
+        AvroSchema::Array(items) => {
+            let list = match array.data_type() {
+                DataType::LargeList(_) => as_large_list_array(array).value(index),
+                _ => as_list_array(array).value(index),
+            };
+            AvroValue::Array(
+                (0..list.len())
+                    .map(|i| to_avro_value(&list, i, items))
+                    .collect::<Result<Vec<AvroValue>, PQRSError>>()?,
+            )
+        }
+        AvroSchema::Map(values) => {
+            let entries = as_list_array(array).value(index);
+            let entries = as_struct_array(&entries);
+            let keys = entries.column(0);
+            let mut map = HashMap::new();
+            for i in 0..entries.len() {
+                map.insert(
+                    string(keys, i)?,
+                    to_avro_value(entries.column(1), i, values)?,
+                );
+            }
+            AvroValue::Map(map)
+        }
+        AvroSchema::Record { fields, .. } => {
+            let record = as_struct_array(array);
+            AvroValue::Record(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let column =
+                            record.column_by_name(&field.name).ok_or_else(|| {
+                                InvalidArgument(format!(
+                                    "The column {} is missing",
+                                    field.name
+                                ))
+                            })?;
+                        Ok((
+                            field.name.clone(),
+                            to_avro_value(column, index, &field.schema)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<(String, AvroValue)>, PQRSError>>()?,
+            )
+        }
+        AvroSchema::Union(_) => unreachable!("unions are handled above"),
+    };
+
+    Ok(value)
+}
+
+fn unsupported(array: &ArrayRef, schema: &AvroSchema) -> PQRSError {
+    InvalidArgument(format!(
+        "Values of type {:?} cannot be exported as {:?}",
+        array.data_type(),
+        schema
+    ))
+}
+
+/// The number of bytes needed to store the value as a two's complement big endian number
+fn minimal_length(value: i128) -> usize {
+    let bits = if value < 0 {
+        128 - value.leading_ones()
+    } else {
+        128 - value.leading_zeros()
+    };
+    (bits as usize + 1 + 7) / 8
+}
+
+/// Convert the value to an avro int, values that do not fit are rejected
+fn narrow(value: i64, schema: &AvroSchema) -> Result<i32, PQRSError> {
+    i32::try_from(value).map_err(|_| {
+        InvalidArgument(format!("The value {} does not fit in {:?}", value, schema))
+    })
+}
+
+fn integer(array: &ArrayRef, index: usize) -> Result<i64, PQRSError> {
+    let value = match array.data_type() {
+        DataType::Int8 => as_primitive_array::<Int8Type>(array).value(index) as i64,
+        DataType::Int16 => as_primitive_array::<Int16Type>(array).value(index) as i64,
+        DataType::Int32 => as_primitive_array::<Int32Type>(array).value(index) as i64,
+        DataType::Int64 => as_primitive_array::<Int64Type>(array).value(index),
+        DataType::UInt8 => as_primitive_array::<UInt8Type>(array).value(index) as i64,
+        DataType::UInt16 => as_primitive_array::<UInt16Type>(array).value(index) as i64,
+        DataType::UInt32 => as_primitive_array::<UInt32Type>(array).value(index) as i64,
+        DataType::UInt64 => {
+            let value = as_primitive_array::<UInt64Type>(array).value(index);
+            i64::try_from(value).map_err(|_| {
+                InvalidArgument(format!("The value {} does not fit in a long", value))
+            })?
+        }
+        DataType::Date32 => as_primitive_array::<Date32Type>(array).value(index) as i64,
+        DataType::Date64 => as_primitive_array::<Date64Type>(array).value(index),
+        _ => return Err(unsupported(array, &AvroSchema::Long)),
+    };
+    Ok(value)
+}
+
+fn float(array: &ArrayRef, index: usize) -> Result<f64, PQRSError> {
+    match array.data_type() {
+        DataType::Float32 => {
+            Ok(as_primitive_array::<Float32Type>(array).value(index) as f64)
+        }
+        DataType::Float64 => Ok(as_primitive_array::<Float64Type>(array).value(index)),
+        _ => Ok(integer(array, index)? as f64),
+    }
+}
+
+/// Return the time or timestamp in the given unit
+fn time(array: &ArrayRef, index: usize, unit: TimeUnit) -> Result<i64, PQRSError> {
+    let (value, from) = match array.data_type() {
+        DataType::Time32(TimeUnit::Second) => (
+            as_primitive_array::<Time32SecondType>(array).value(index) as i64,
+            TimeUnit::Second,
+        ),
+        DataType::Time32(_) => (
+            as_primitive_array::<Time32MillisecondType>(array).value(index) as i64,
+            TimeUnit::Millisecond,
+        ),
+        DataType::Time64(TimeUnit::Microsecond) => (
+            as_primitive_array::<Time64MicrosecondType>(array).value(index),
+            TimeUnit::Microsecond,
+        ),
+        DataType::Time64(_) => (
+            as_primitive_array::<Time64NanosecondType>(array).value(index),
+            TimeUnit::Nanosecond,
+        ),
+        DataType::Timestamp(TimeUnit::Second, _) => (
+            as_primitive_array::<TimestampSecondType>(array).value(index),
+            TimeUnit::Second,
+        ),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => (
+            as_primitive_array::<TimestampMillisecondType>(array).value(index),
+            TimeUnit::Millisecond,
+        ),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => (
+            as_primitive_array::<TimestampMicrosecondType>(array).value(index),
+            TimeUnit::Microsecond,
+        ),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => (
+            as_primitive_array::<TimestampNanosecondType>(array).value(index),
+            TimeUnit::Nanosecond,
+        ),
+        DataType::Date64 => (integer(array, index)?, TimeUnit::Millisecond),
+        _ => (integer(array, index)?, unit.clone()),
+    };
+
+    let exponent = |unit: &TimeUnit| match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond => 6,
+        TimeUnit::Nanosecond => 9,
+    };
+    let difference = exponent(&unit) - exponent(&from);
+    Ok(if difference >= 0 {
+        value * 10i64.pow(difference as u32)
+    } else {
+        value / 10i64.pow(-difference as u32)
+    })
+}
+
+fn string(array: &ArrayRef, index: usize) -> Result<String, PQRSError> {
+    match array.data_type() {
+        DataType::Utf8 => Ok(as_string_array(array).value(index).to_string()),
+        DataType::LargeUtf8 => Ok(array
+            .as_any()
+            .downcast_ref::<LargeStringArray>()
+            .unwrap()
+            .value(index)
+            .to_string()),
+        _ => Ok(array_value_to_string(array, index)?),
+    }
+}
+
+fn bytes(array: &ArrayRef, index: usize) -> Result<Vec<u8>, PQRSError> {
+    let any = array.as_any();
+    if let Some(array) = any.downcast_ref::<BinaryArray>() {
+        Ok(array.value(index).to_vec())
+    } else if let Some(array) = any.downcast_ref::<LargeBinaryArray>() {
+        Ok(array.value(index).to_vec())
+    } else if let Some(array) = any.downcast_ref::<FixedSizeBinaryArray>() {
+        Ok(array.value(index).to_vec())
+    } else {
+        Ok(string(array, index)?.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::UInt64Array;
+
+    #[test]
+    fn it_maps_avro_schemas() {
+        let schema = AvroSchema::parse_str(
+            r#"{"type": "record", "name": "city", "fields": [
+                {"name": "name", "type": "string"},
+                {"name": "population", "type": ["null", "long"]},
+                {"name": "founded", "type": {"type": "int", "logicalType": "date"}},
+                {"name": "districts", "type": {"type": "array", "items": "string"}},
+                {"name": "tags", "type": {"type": "map", "values": "int"}},
+                {"name": "code", "type": ["null", "int", "string"]}
+            ]}"#,
+        )
+        .unwrap();
+        let schema = to_arrow_schema(&schema).unwrap();
+
+        let types: Vec<(&str, &DataType, bool)> = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type(), f.is_nullable()))
+            .collect();
+        assert_eq!(types[0], ("name", &DataType::Utf8, false));
+        assert_eq!(types[1], ("population", &DataType::Int64, true));
+        assert_eq!(types[2], ("founded", &DataType::Date32, false));
+        assert_eq!(
+            types[3].1,
+            &DataType::List(Box::new(Field::new("item", DataType::Utf8, false)))
+        );
+        assert_eq!(
+            types[5].1,
+            &DataType::Struct(vec![
+                Field::new("member1", DataType::Int32, true),
+                Field::new("member2", DataType::Utf8, true),
+            ])
+        );
+        assert!(types[5].2);
+    }
+
+    #[test]
+    fn it_round_trips_unsigned_longs() {
+        let schema = Schema::new(vec![Field::new("id", DataType::UInt64, false)]);
+        let values: ArrayRef = Arc::new(UInt64Array::from(vec![0, u64::MAX]));
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![values]).unwrap();
+
+        let avro_schema = get_avro_schema("", &schema, false).unwrap();
+        let mut output = Vec::new();
+        write_avro(&mut output, &avro_schema, vec![Ok(batch)]).unwrap();
+
+        let reader = Reader::new(output.as_slice()).unwrap();
+        let avro_schema = reader.writer_schema().clone();
+        let arrow_schema = Arc::new(to_arrow_schema(&avro_schema).unwrap());
+        let batches = read_batches(reader, &avro_schema, arrow_schema)
+            .collect::<ArrowResult<Vec<RecordBatch>>>()
+            .unwrap();
+        let ids = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<DecimalArray>()
+            .unwrap();
+        assert_eq!(ids.value(0), 0);
+        assert_eq!(ids.value(1), u64::MAX as i128);
+    }
+
+    #[test]
+    fn it_rejects_values_out_of_range() {
+        let values: ArrayRef = Arc::new(UInt64Array::from(vec![u64::MAX]));
+        assert!(integer(&values, 0).is_err());
+        assert!(narrow(i64::from(i32::MAX) + 1, &AvroSchema::Int).is_err());
+        assert_eq!(narrow(-1, &AvroSchema::Int).unwrap(), -1);
+    }
+
+    #[test]
+    fn it_computes_the_length_of_decimals() {
+        assert_eq!(minimal_length(0), 1);
+        assert_eq!(minimal_length(127), 1);
+        assert_eq!(minimal_length(128), 2);
+        assert_eq!(minimal_length(-128), 1);
+        assert_eq!(minimal_length(-129), 2);
+    }
+}
@@ -1,10 +1,21 @@
 use crate::commands::cat::CatCommand;
+use crate::commands::convert::ConvertCommand;
+use crate::commands::dictionary::DictionaryCommand;
+use crate::commands::footer::FooterCommand;
 use crate::commands::head::HeadCommand;
+use crate::commands::import::ImportCommand;
+use crate::commands::layout::LayoutCommand;
+use crate::commands::levels::LevelsCommand;
 use crate::commands::merge::MergeCommand;
+use crate::commands::pages::PagesCommand;
+use crate::commands::profile::ProfileCommand;
+use crate::commands::query::QueryCommand;
 use crate::commands::rowcount::RowCountCommand;
 use crate::commands::sample::SampleCommand;
 use crate::commands::schema::SchemaCommand;
+use crate::commands::schema_diff::SchemaDiffCommand;
 use crate::commands::size::SizeCommand;
+use crate::commands::stats::StatsCommand;
 use crate::errors::PQRSError;
 use clap::ArgMatches;
 
@@ -25,6 +36,17 @@ pub fn run_command(matches: ArgMatches) -> Result<(), PQRSError> {
         ("size", Some(m)) => SizeCommand::new(m).execute(),
         ("sample", Some(m)) => SampleCommand::new(m).execute(),
         ("merge", Some(m)) => MergeCommand::new(m).execute(),
+        ("query", Some(m)) => QueryCommand::new(m).execute(),
+        ("stats", Some(m)) => StatsCommand::new(m).execute(),
+        ("profile", Some(m)) => ProfileCommand::new(m).execute(),
+        ("convert", Some(m)) => ConvertCommand::new(m).execute(),
+        ("import", Some(m)) => ImportCommand::new(m).execute(),
+        ("schema-diff", Some(m)) => SchemaDiffCommand::new(m).execute(),
+        ("pages", Some(m)) => PagesCommand::new(m).execute(),
+        ("dictionary", Some(m)) => DictionaryCommand::new(m).execute(),
+        ("levels", Some(m)) => LevelsCommand::new(m).execute(),
+        ("layout", Some(m)) => LayoutCommand::new(m).execute(),
+        ("footer", Some(m)) => FooterCommand::new(m).execute(),
         _ => Ok(()),
     }
 }
@@ -1,6 +1,8 @@
 use crate::command::PQRSCommand;
 use crate::errors::PQRSError;
 use crate::errors::PQRSError::FileNotFound;
+use crate::expression::Expr;
+use crate::output::{OutputFormat, RowPrinter};
 use crate::utils::{check_path_present, open_file, print_rows};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
@@ -9,7 +11,9 @@ use std::fmt;
 /// The config params for the "cat" subcommand
 pub struct CatCommand<'a> {
     file_names: Vec<&'a str>,
-    use_json: bool,
+    columns: Option<Vec<&'a str>>,
+    filter: Option<&'a str>,
+    output: OutputFormat,
 }
 
 impl<'a> CatCommand<'a> {
@@ -26,20 +30,34 @@ impl<'a> CatCommand<'a> {
                     .required(true)
                     .help("Parquet files to read"),
             )
+            .args(&OutputFormat::args())
             .arg(
-                Arg::with_name("json")
-                    .long("json")
-                    .short("j")
-                    .takes_value(false)
+                Arg::with_name("columns")
+                    .long("columns")
+                    .short("c")
+                    .takes_value(true)
+                    .use_delimiter(true)
+                    .value_name("COLUMNS")
                     .required(false)
-                    .help("Use JSON lines format for printing"),
+                    .help("Columns to read, use dots to select nested fields"),
+            )
+            .arg(
+                Arg::with_name("where")
+                    .long("where")
+                    .short("w")
+                    .takes_value(true)
+                    .value_name("PREDICATE")
+                    .required(false)
+                    .help("Only print the records matching the predicate, e.g. \"flow1 > 10\""),
             )
     }
 
     pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
         Self {
             file_names: matches.values_of("files").unwrap().collect(),
-            use_json: matches.is_present("json"),
+            columns: matches.values_of("columns").map(|c| c.collect()),
+            filter: matches.value_of("where"),
+            output: OutputFormat::new(matches),
         }
     }
 }
@@ -56,10 +74,22 @@ impl<'a> PQRSCommand for CatCommand<'a> {
             }
         }
 
+        let filter = self.filter.map(Expr::parse).transpose()?;
+
+        // a single printer is shared across files so that the csv header is only
+        // written once, which requires all the files to have the same columns
+        let mut printer = RowPrinter::new(self.output);
         for file_name in &self.file_names {
             let file = open_file(file_name)?;
-            print_rows(file, None, self.use_json)?;
+            print_rows(
+                file,
+                None,
+                self.columns.as_deref(),
+                filter.as_ref(),
+                &mut printer,
+            )?;
         }
+        printer.flush()?;
 
         Ok(())
     }
@@ -72,7 +102,9 @@ impl<'a> fmt::Debug for CatCommand<'a> {
             "The file names to read are: {}",
             &self.file_names.join(", ")
         )?;
-        writeln!(f, "Use JSON Output format: {}", &self.use_json)?;
+        writeln!(f, "Columns to read: {:?}", &self.columns)?;
+        writeln!(f, "Filter: {:?}", &self.filter)?;
+        writeln!(f, "Output format: {:?}", &self.output)?;
 
         Ok(())
     }
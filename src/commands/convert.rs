@@ -0,0 +1,338 @@
+use crate::avro::{get_avro_schema, write_avro};
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileExists, FileNotFound, InvalidArgument};
+use crate::utils::{check_path_present, get_batch_reader, get_projected_batch_reader};
+use arrow::compute::limit;
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::ipc::writer::{FileWriter, StreamWriter};
+use arrow::record_batch::RecordBatch;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use flate2::write::GzEncoder;
+use log::debug;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::sync::Arc;
+
+/// The formats a parquet file can be converted to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConvertFormat {
+    Csv,
+    /// Newline delimited JSON, one record per line
+    Json,
+    /// The Arrow IPC file format, also known as Feather v2
+    Arrow,
+    /// The Arrow IPC streaming format
+    ArrowStream,
+    /// Avro object container files
+    Avro,
+}
+
+/// The codecs that can be used to compress the text formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextCompression {
+    Gzip,
+    Zstd,
+}
+
+pub struct ConvertCommand<'a> {
+    input: &'a str,
+    output: &'a str,
+    format: ConvertFormat,
+    columns: Option<Vec<&'a str>>,
+    limit: Option<usize>,
+    compression: Option<TextCompression>,
+}
+
+impl<'a> ConvertCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("convert")
+            .about("Convert a Parquet file to CSV, JSON, Arrow IPC or Avro")
+            .arg(
+                Arg::with_name("input")
+                    .short("i")
+                    .long("input")
+                    .value_name("INPUT")
+                    .required(true)
+                    .help("Parquet file to read"),
+            )
+            .arg(
+                Arg::with_name("output")
+                    .short("o")
+                    .long("output")
+                    .value_name("OUTPUT")
+                    .required(true)
+                    .help("File to write"),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .short("f")
+                    .long("format")
+                    .takes_value(true)
+                    .required(true)
+                    .possible_values(&["csv", "json", "arrow", "arrow-stream", "avro"])
+                    .help("The format of the output, json is written with one record per line"),
+            )
+            .arg(
+                Arg::with_name("columns")
+                    .long("columns")
+                    .short("c")
+                    .takes_value(true)
+                    .use_delimiter(true)
+                    .value_name("COLUMNS")
+                    .required(false)
+                    .help("Columns to write, use dots to select nested fields"),
+            )
+            .arg(
+                Arg::with_name("limit")
+                    .long("limit")
+                    .short("n")
+                    .takes_value(true)
+                    .value_name("RECORDS")
+                    .required(false)
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The limit must be a number of records"))
+                    })
+                    .help("The maximum number of records to write"),
+            )
+            .arg(
+                Arg::with_name("compression")
+                    .long("compression")
+                    .takes_value(true)
+                    .required(false)
+                    .possible_values(&["gzip", "zstd"])
+                    .help("Compress the output, only supported by the csv and json formats"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            input: matches.value_of("input").unwrap(),
+            output: matches.value_of("output").unwrap(),
+            format: match matches.value_of("format") {
+                Some("json") => ConvertFormat::Json,
+                Some("arrow") => ConvertFormat::Arrow,
+                Some("arrow-stream") => ConvertFormat::ArrowStream,
+                Some("avro") => ConvertFormat::Avro,
+                _ => ConvertFormat::Csv,
+            },
+            columns: matches.values_of("columns").map(|c| c.collect()),
+            limit: matches.value_of("limit").map(|n| n.parse().unwrap()),
+            compression: match matches.value_of("compression") {
+                Some("gzip") => Some(TextCompression::Gzip),
+                Some("zstd") => Some(TextCompression::Zstd),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl<'a> PQRSCommand for ConvertCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        // make sure output does not exist already before any reads
+        if check_path_present(self.output) {
+            return Err(FileExists(self.output.to_string()));
+        }
+
+        if !check_path_present(self.input) {
+            return Err(FileNotFound(String::from(self.input)));
+        }
+
+        let is_text = matches!(self.format, ConvertFormat::Csv | ConvertFormat::Json);
+        if self.compression.is_some() && !is_text {
+            return Err(InvalidArgument(String::from(
+                "Compression is only supported by the csv and json formats",
+            )));
+        }
+
+        // only the selected columns are read from the file
+        let (schema, reader) = match &self.columns {
+            Some(columns) => get_projected_batch_reader(self.input, columns)?,
+            None => get_batch_reader(self.input)?,
+        };
+        let schema: SchemaRef = Arc::new(schema);
+        debug!("This is the output schema: {:#?}", schema);
+
+        // the batches are limited as they are read, reading stops as soon as enough
+        // records have been written
+        let batches =
+            reader.scan(self.limit.unwrap_or(usize::MAX), |remaining, batch| {
+                if *remaining == 0 {
+                    return None;
+                }
+                Some(batch.and_then(|b| limit_batch(&b, *remaining)).map(|b| {
+                    *remaining -= b.num_rows();
+                    b
+                }))
+            });
+
+        match self.write(&schema, batches) {
+            Ok(rows) => {
+                debug!("Wrote {} rows to {}", rows, self.output);
+                Ok(())
+            }
+            Err(e) => {
+                // do not leave a partially written file behind
+                let _ = fs::remove_file(self.output);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'a> ConvertCommand<'a> {
+    /// Write the batches to the output in the requested format and return the number
+    /// of rows written
+    fn write<I>(&self, schema: &SchemaRef, batches: I) -> Result<usize, PQRSError>
+    where
+        I: Iterator<Item = ArrowResult<RecordBatch>>,
+    {
+        let file = File::create(self.output)?;
+        let mut rows = 0;
+
+        match self.format {
+            ConvertFormat::Csv => {
+                let mut output = TextWriter::new(file, self.compression)?;
+                {
+                    // the header is written along with the first batch
+                    let mut writer = arrow::csv::Writer::new(&mut output);
+                    for batch in batches {
+                        let batch = batch?;
+                        rows += batch.num_rows();
+                        writer.write(&batch)?;
+                    }
+                }
+                output.finish()?;
+            }
+            ConvertFormat::Json => {
+                let mut output = TextWriter::new(file, self.compression)?;
+                {
+                    let mut writer = arrow::json::LineDelimitedWriter::new(&mut output);
+                    for batch in batches {
+                        let batch = batch?;
+                        rows += batch.num_rows();
+                        writer.write_batches(&[batch])?;
+                    }
+                    writer.finish()?;
+                }
+                output.finish()?;
+            }
+            ConvertFormat::Arrow => {
+                let mut writer = FileWriter::try_new(BufWriter::new(file), schema)?;
+                for batch in batches {
+                    let batch = batch?;
+                    rows += batch.num_rows();
+                    writer.write(&batch)?;
+                }
+                // finishing the writer writes out the footer of the IPC file
+                writer.finish()?;
+            }
+            ConvertFormat::ArrowStream => {
+                let mut writer = StreamWriter::try_new(BufWriter::new(file), schema)?;
+                for batch in batches {
+                    let batch = batch?;
+                    rows += batch.num_rows();
+                    writer.write(&batch)?;
+                }
+                writer.finish()?;
+            }
+            ConvertFormat::Avro => {
+                // files imported from avro are exported with their original schema,
+                // unless only some of the columns are written
+                let avro_schema =
+                    get_avro_schema(self.input, schema, self.columns.is_none())?;
+                rows = write_avro(BufWriter::new(file), &avro_schema, batches)?;
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Only keep the first records of the batch if it has more than the given number
+fn limit_batch(batch: &RecordBatch, num_records: usize) -> ArrowResult<RecordBatch> {
+    if batch.num_rows() <= num_records {
+        return Ok(batch.clone());
+    }
+
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| limit(column, num_records))
+        .collect();
+    RecordBatch::try_new(batch.schema(), columns)
+}
+
+/// The destination of the text formats, optionally compressed
+enum TextWriter {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl TextWriter {
+    fn new(file: File, compression: Option<TextCompression>) -> io::Result<Self> {
+        let writer = BufWriter::new(file);
+        Ok(match compression {
+            Some(TextCompression::Gzip) => {
+                TextWriter::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+            }
+            Some(TextCompression::Zstd) => {
+                TextWriter::Zstd(zstd::Encoder::new(writer, 0)?)
+            }
+            None => TextWriter::Plain(writer),
+        })
+    }
+
+    /// Write out the end of the compressed stream, the output is not readable without it
+    fn finish(self) -> io::Result<()> {
+        let mut writer = match self {
+            TextWriter::Plain(writer) => writer,
+            TextWriter::Gzip(encoder) => encoder.finish()?,
+            TextWriter::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl Write for TextWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            TextWriter::Plain(writer) => writer.write(buf),
+            TextWriter::Gzip(encoder) => encoder.write(buf),
+            TextWriter::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            TextWriter::Plain(writer) => writer.flush(),
+            TextWriter::Gzip(encoder) => encoder.flush(),
+            TextWriter::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for ConvertCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.input)?;
+        writeln!(f, "The file name to write to: {}", self.output)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+        if let Some(columns) = &self.columns {
+            writeln!(f, "Columns to write: {}", columns.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            writeln!(f, "Maximum number of records: {}", limit)?;
+        }
+        writeln!(f, "Compression: {:?}", self.compression)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use crate::command::PQRSCommand;
+use crate::dictionary::read_dictionary;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileNotFound, InvalidArgument};
+use crate::report::{print_report, print_table, MetadataFormat};
+use crate::utils::{check_path_present, get_column_indices, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use parquet::file::reader::{FileReader, RowGroupReader};
+use parquet::file::serialized_reader::SerializedFileReader;
+use serde_json::{json, Value};
+use std::fmt;
+
+pub struct DictionaryCommand<'a> {
+    file_name: &'a str,
+    column: Option<&'a str>,
+    row_group: Option<usize>,
+    format: MetadataFormat,
+}
+
+impl<'a> DictionaryCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("dictionary")
+            .about("Prints the dictionary values of the columns of a Parquet file")
+            .arg(
+                Arg::with_name("file")
+                    .index(1)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("Parquet file to read"),
+            )
+            .arg(
+                Arg::with_name("column")
+                    .long("column")
+                    .short("c")
+                    .takes_value(true)
+                    .required(false)
+                    .help("Only print the dictionary of this column, nested columns are given as a dotted path"),
+            )
+            .arg(
+                Arg::with_name("row-group")
+                    .long("row-group")
+                    .short("r")
+                    .takes_value(true)
+                    .required(false)
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The row group must be a number"))
+                    })
+                    .help("Only print the dictionaries of this row group, starting from 0"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_name: matches.value_of("file").unwrap(),
+            column: matches.value_of("column"),
+            row_group: matches.value_of("row-group").map(|v| v.parse().unwrap()),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for DictionaryCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        if !check_path_present(self.file_name) {
+            return Err(FileNotFound(String::from(self.file_name)));
+        }
+
+        let reader = SerializedFileReader::new(open_file(self.file_name)?)?;
+        let metadata = reader.metadata();
+
+        let row_groups = metadata.num_row_groups();
+        if let Some(row_group) = self.row_group {
+            if row_group >= row_groups {
+                return Err(InvalidArgument(format!(
+                    "The row group must be less than {}, the number of row groups in {}",
+                    row_groups, self.file_name
+                )));
+            }
+        }
+
+        let schema = metadata.file_metadata().schema_descr();
+        let columns = get_column_indices(schema, self.column)?;
+
+        // only the pages of the selected columns are read, the records are never assembled
+        let mut entries = Vec::new();
+        for row_group in
+            (0..row_groups).filter(|i| self.row_group.map_or(true, |r| r == *i))
+        {
+            let row_group_reader = reader.get_row_group(row_group)?;
+            for &i in &columns {
+                let column = schema.column(i);
+                let path = column.path().string();
+                let mut pages = row_group_reader.get_column_page_reader(i)?;
+                let dictionary = read_dictionary(pages.as_mut(), &column)?;
+
+                if self.format != MetadataFormat::Text {
+                    // every chunk gets an entry, followed by one entry per dictionary index.
+                    // All the entries share the same fields so that they fit in a CSV file,
+                    // the fields that do not apply are null.
+                    entries.push(vec![
+                        ("path", json!(self.file_name)),
+                        ("row_group", json!(row_group)),
+                        ("column", json!(path)),
+                        ("has_dictionary", json!(dictionary.is_some())),
+                        ("is_sorted", json!(dictionary.as_ref().map(|d| d.is_sorted))),
+                        ("nulls", json!(dictionary.as_ref().map(|d| d.nulls))),
+                        (
+                            "fallback_values",
+                            json!(dictionary.as_ref().map(|d| d.fallback_values)),
+                        ),
+                        ("index", Value::Null),
+                        ("value", Value::Null),
+                        ("count", Value::Null),
+                    ]);
+                    for (index, (value, count)) in dictionary
+                        .iter()
+                        .flat_map(|d| d.values.iter().zip(&d.counts))
+                        .enumerate()
+                    {
+                        entries.push(vec![
+                            ("path", json!(self.file_name)),
+                            ("row_group", json!(row_group)),
+                            ("column", json!(path)),
+                            ("has_dictionary", Value::Null),
+                            ("is_sorted", Value::Null),
+                            ("nulls", Value::Null),
+                            ("fallback_values", Value::Null),
+                            ("index", json!(index)),
+                            ("value", json!(value)),
+                            ("count", json!(count)),
+                        ]);
+                    }
+                    continue;
+                }
+
+                let dictionary = match dictionary {
+                    Some(dictionary) => dictionary,
+                    None => {
+                        println!(
+                            "Row group {}, column {}: no dictionary",
+                            row_group, path
+                        );
+                        println!();
+                        continue;
+                    }
+                };
+                println!(
+                    "Row group {}, column {}: {} values, sorted: {}, nulls: {}, not dictionary encoded: {}",
+                    row_group,
+                    path,
+                    dictionary.values.len(),
+                    dictionary.is_sorted,
+                    dictionary.nulls,
+                    dictionary.fallback_values
+                );
+                let rows: Vec<Vec<String>> = dictionary
+                    .values
+                    .iter()
+                    .zip(&dictionary.counts)
+                    .enumerate()
+                    .map(|(index, (value, count))| {
+                        vec![index.to_string(), value.clone(), count.to_string()]
+                    })
+                    .collect();
+                print_table(&["Index", "Value", "Count"], &rows);
+                println!();
+            }
+        }
+
+        if self.format != MetadataFormat::Text {
+            return print_report(self.format, "values", &entries, None);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for DictionaryCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.file_name)?;
+        if let Some(column) = self.column {
+            writeln!(f, "Column: {}", column)?;
+        }
+        if let Some(row_group) = self.row_group {
+            writeln!(f, "Row group: {}", row_group)?;
+        }
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileNotFound, InvalidArgument};
+use crate::footer::{metadata_to_json, print_tree, read_footer};
+use crate::report::MetadataFormat;
+use crate::utils::{check_path_present, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use std::fmt;
+use std::io::{self, BufReader, Cursor, Read};
+
+pub struct FooterCommand<'a> {
+    file_name: &'a str,
+    use_json: bool,
+    format: MetadataFormat,
+}
+
+impl<'a> FooterCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("footer")
+            .about("Prints the raw file metadata stored in the footer of a Parquet file")
+            .arg(
+                Arg::with_name("file")
+                    .index(1)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("Parquet file to read, or only its last bytes, use - to read from stdin"),
+            )
+            .arg(
+                Arg::with_name("json")
+                    .long("json")
+                    .short("j")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Print the file metadata as a JSON document"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_name: matches.value_of("file").unwrap(),
+            use_json: matches.is_present("json"),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for FooterCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        if self.format == MetadataFormat::Csv {
+            return Err(InvalidArgument(String::from(
+                "The footer can only be printed as text or JSON",
+            )));
+        }
+
+        // the footer is read from the end of the input, stdin is read in memory to seek it
+        let metadata = if self.file_name == "-" {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            read_footer(&mut Cursor::new(data))?
+        } else {
+            if !check_path_present(self.file_name) {
+                return Err(FileNotFound(String::from(self.file_name)));
+            }
+            read_footer(&mut BufReader::new(open_file(self.file_name)?))?
+        };
+        let document = metadata_to_json(&metadata)?;
+
+        if self.use_json || self.format == MetadataFormat::Json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&document).map_err(io::Error::from)?
+            );
+        } else {
+            print_tree(&document, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for FooterCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.file_name)?;
+        writeln!(f, "Use JSON: {}", self.use_json)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
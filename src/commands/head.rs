@@ -1,6 +1,8 @@
 use crate::command::PQRSCommand;
 use crate::errors::PQRSError;
 use crate::errors::PQRSError::FileNotFound;
+use crate::expression::Expr;
+use crate::output::{OutputFormat, RowPrinter};
 use crate::utils::{check_path_present, open_file, print_rows};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
@@ -9,7 +11,9 @@ use std::fmt;
 pub struct HeadCommand<'a> {
     file_name: &'a str,
     num_records: i64,
-    use_json: bool,
+    columns: Option<Vec<&'a str>>,
+    filter: Option<&'a str>,
+    output: OutputFormat,
 }
 
 impl<'a> HeadCommand<'a> {
@@ -23,13 +27,25 @@ impl<'a> HeadCommand<'a> {
                     .required(true)
                     .help("Parquet file to read"),
             )
+            .args(&OutputFormat::args())
             .arg(
-                Arg::with_name("json")
-                    .long("json")
-                    .short("j")
-                    .takes_value(false)
+                Arg::with_name("columns")
+                    .long("columns")
+                    .short("c")
+                    .takes_value(true)
+                    .use_delimiter(true)
+                    .value_name("COLUMNS")
+                    .required(false)
+                    .help("Columns to read, use dots to select nested fields"),
+            )
+            .arg(
+                Arg::with_name("where")
+                    .long("where")
+                    .short("w")
+                    .takes_value(true)
+                    .value_name("PREDICATE")
                     .required(false)
-                    .help("Use JSON lines format for printing"),
+                    .help("Only print the records matching the predicate, e.g. \"flow1 > 10\""),
             )
             .arg(
                 Arg::with_name("records")
@@ -46,7 +62,9 @@ impl<'a> HeadCommand<'a> {
         Self {
             file_name: matches.value_of("file").unwrap(),
             num_records: matches.value_of("records").unwrap().parse().unwrap(),
-            use_json: matches.is_present("json"),
+            columns: matches.values_of("columns").map(|c| c.collect()),
+            filter: matches.value_of("where"),
+            output: OutputFormat::new(matches),
         }
     }
 }
@@ -60,8 +78,17 @@ impl<'a> PQRSCommand for HeadCommand<'a> {
             return Err(FileNotFound(String::from(self.file_name)));
         }
 
+        let filter = self.filter.map(Expr::parse).transpose()?;
         let file = open_file(self.file_name)?;
-        print_rows(file, Some(self.num_records), self.use_json)?;
+        let mut printer = RowPrinter::new(self.output);
+        print_rows(
+            file,
+            Some(self.num_records),
+            self.columns.as_deref(),
+            filter.as_ref(),
+            &mut printer,
+        )?;
+        printer.flush()?;
 
         Ok(())
     }
@@ -71,7 +98,9 @@ impl<'a> fmt::Debug for HeadCommand<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "The file name to read is: {}", &self.file_name)?;
         writeln!(f, "Number of records to print: {}", &self.num_records)?;
-        writeln!(f, "Use JSON Output format: {}", &self.use_json)?;
+        writeln!(f, "Columns to read: {:?}", &self.columns)?;
+        writeln!(f, "Filter: {:?}", &self.filter)?;
+        writeln!(f, "Output format: {:?}", &self.output)?;
 
         Ok(())
     }
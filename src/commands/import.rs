@@ -0,0 +1,318 @@
+use crate::avro::{read_batches, to_arrow_schema, AVRO_SCHEMA_KEY};
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileExists, FileNotFound, InvalidArgument};
+use crate::import::{
+    conform_record, infer_schema, read_records, CsvOptions, ImportFormat, Record,
+};
+use crate::utils::{check_path_present, open_file, write_parquet, BATCH_SIZE};
+use crate::writer::WriterOptions;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::json::reader::Decoder;
+use avro_rs::Reader;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::{debug, warn};
+use parquet::arrow::parquet_to_arrow_schema;
+use parquet::basic::Compression;
+use parquet::file::metadata::KeyValue;
+use parquet::schema::parser::parse_message_type;
+use parquet::schema::types::SchemaDescriptor;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader};
+use std::iter;
+use std::sync::Arc;
+
+pub struct ImportCommand<'a> {
+    input: &'a str,
+    output: &'a str,
+    format: ImportFormat,
+    csv: CsvOptions,
+    schema: Option<&'a str>,
+    infer_records: usize,
+    writer_options: WriterOptions<'a>,
+}
+
+impl<'a> ImportCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("import")
+            .about("Import a CSV, JSON or Avro file into a Parquet file")
+            .arg(
+                Arg::with_name("input")
+                    .short("i")
+                    .long("input")
+                    .value_name("INPUT")
+                    .required(true)
+                    .help("CSV, newline delimited JSON or Avro file to read"),
+            )
+            .arg(
+                Arg::with_name("output")
+                    .short("o")
+                    .long("output")
+                    .value_name("OUTPUT")
+                    .required(true)
+                    .help("Parquet file to write"),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .short("f")
+                    .long("format")
+                    .takes_value(true)
+                    .required(true)
+                    .possible_values(&["csv", "json", "avro"])
+                    .help("The format of the input, json is read with one record per line and avro from an object container file"),
+            )
+            .arg(
+                Arg::with_name("delimiter")
+                    .long("delimiter")
+                    .takes_value(true)
+                    .required(false)
+                    .default_value(",")
+                    .validator(validate_ascii_char)
+                    .help("The field delimiter of the csv format"),
+            )
+            .arg(
+                Arg::with_name("quote")
+                    .long("quote")
+                    .takes_value(true)
+                    .required(false)
+                    .default_value("\"")
+                    .validator(validate_ascii_char)
+                    .help("The quote character of the csv format"),
+            )
+            .arg(
+                Arg::with_name("no-header")
+                    .long("no-header")
+                    .takes_value(false)
+                    .required(false)
+                    .help("The csv input has no header, the columns are named column_1, column_2, ..."),
+            )
+            .arg(
+                Arg::with_name("null")
+                    .long("null")
+                    .takes_value(true)
+                    .required(false)
+                    .default_value("")
+                    .help("The csv fields equal to this value are read as nulls"),
+            )
+            .arg(
+                Arg::with_name("schema")
+                    .long("schema")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .required(false)
+                    .help("A file with the Parquet message type to use, instead of inferring the schema"),
+            )
+            .arg(
+                Arg::with_name("infer-records")
+                    .long("infer-records")
+                    .takes_value(true)
+                    .value_name("RECORDS")
+                    .required(false)
+                    .default_value("1000")
+                    .conflicts_with("schema")
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The value must be a number of records"))
+                    })
+                    .help("The number of records used to infer the schema"),
+            )
+            .args(&WriterOptions::args())
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        // the validators make sure that these are single ascii characters
+        let byte = |name| matches.value_of(name).unwrap().as_bytes()[0];
+
+        Self {
+            input: matches.value_of("input").unwrap(),
+            output: matches.value_of("output").unwrap(),
+            format: match matches.value_of("format") {
+                Some("json") => ImportFormat::Json,
+                Some("avro") => ImportFormat::Avro,
+                _ => ImportFormat::Csv,
+            },
+            csv: CsvOptions {
+                delimiter: byte("delimiter"),
+                quote: byte("quote"),
+                has_header: !matches.is_present("no-header"),
+                null_token: matches.value_of("null").unwrap().to_string(),
+            },
+            schema: matches.value_of("schema"),
+            infer_records: matches.value_of("infer-records").unwrap().parse().unwrap(),
+            writer_options: WriterOptions::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for ImportCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        // make sure output does not exist already before any reads
+        if check_path_present(self.output) {
+            return Err(FileExists(self.output.to_string()));
+        }
+
+        for file_name in iter::once(self.input).chain(self.schema) {
+            if !check_path_present(file_name) {
+                return Err(FileNotFound(String::from(file_name)));
+            }
+        }
+
+        if self.format == ImportFormat::Avro {
+            return self.import_avro();
+        }
+
+        let schema = self.schema.map(read_schema).transpose()?;
+        // without a header, the columns of the schema are matched by position
+        let names = schema
+            .as_ref()
+            .map(|s| s.fields().iter().map(|f| f.name().clone()).collect());
+        let mut records =
+            read_records(open_file(self.input)?, self.format, &self.csv, names)?;
+
+        // the records used for inference are kept to be written along with the others
+        let mut buffered: Vec<Record> = Vec::new();
+        let schema = match schema {
+            Some(schema) => schema,
+            None => {
+                let mut sample = Vec::new();
+                while sample.len() < self.infer_records {
+                    match records.next() {
+                        Some((line, Ok(record))) => {
+                            sample.push(record.clone());
+                            buffered.push((line, Ok(record)));
+                        }
+                        Some(rejected) => buffered.push(rejected),
+                        None => break,
+                    }
+                }
+                infer_schema(&sample, self.format)?
+            }
+        };
+        let schema: SchemaRef = Arc::new(schema);
+        debug!("This is the output schema: {:#?}", schema);
+
+        let props = self.writer_options.properties(Compression::SNAPPY);
+
+        // the records that do not fit the schema are reported and skipped, the others
+        // are decoded one batch at a time and written straight to the output
+        let mut rejected = 0;
+        let mut values =
+            buffered
+                .into_iter()
+                .chain(records)
+                .filter_map(|(line, record)| {
+                    match record.and_then(|r| conform_record(&r, &schema)) {
+                        Ok(value) => Some(ArrowResult::Ok(value)),
+                        Err(reason) => {
+                            warn!("Rejected line {}: {}", line, reason);
+                            rejected += 1;
+                            None
+                        }
+                    }
+                });
+        let decoder = Decoder::new(schema.clone(), BATCH_SIZE, None);
+        let batches = iter::from_fn(move || decoder.next_batch(&mut values).transpose());
+
+        match write_parquet(&schema, batches, self.output, props) {
+            Ok(rows) => {
+                println!("Rows written: {}", rows);
+                println!("Lines rejected: {}", rejected);
+                Ok(())
+            }
+            Err(e) => {
+                // do not leave a partially written file behind
+                let _ = fs::remove_file(self.output);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'a> ImportCommand<'a> {
+    /// Avro files carry their own schema, the records are converted one batch at a time
+    /// and the avro schema is kept in the metadata so that the file can be exported back
+    fn import_avro(&self) -> Result<(), PQRSError> {
+        if self.schema.is_some() {
+            return Err(InvalidArgument(String::from(
+                "Avro files are imported with their own schema",
+            )));
+        }
+
+        let reader = Reader::new(BufReader::new(open_file(self.input)?))?;
+        let avro_schema = reader.writer_schema().clone();
+        let schema: SchemaRef = Arc::new(to_arrow_schema(&avro_schema)?);
+        debug!("This is the output schema: {:#?}", schema);
+
+        let metadata = KeyValue::new(
+            AVRO_SCHEMA_KEY.to_string(),
+            serde_json::to_string(&avro_schema).map_err(io::Error::from)?,
+        );
+        let props = self
+            .writer_options
+            .builder(Compression::SNAPPY)
+            .set_key_value_metadata(Some(vec![metadata]))
+            .build();
+
+        let batches = read_batches(reader, &avro_schema, schema.clone());
+        match write_parquet(&schema, batches, self.output, props) {
+            Ok(rows) => {
+                println!("Rows written: {}", rows);
+                Ok(())
+            }
+            Err(e) => {
+                // do not leave a partially written file behind
+                let _ = fs::remove_file(self.output);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Read the arrow schema from a file containing a parquet message type, e.g. the
+/// output of the schema command
+fn read_schema(file_name: &str) -> Result<Schema, PQRSError> {
+    let message_type = fs::read_to_string(file_name)?;
+    let parquet_schema =
+        SchemaDescriptor::new(Arc::new(parse_message_type(&message_type)?));
+    Ok(parquet_to_arrow_schema(&parquet_schema, &None)?)
+}
+
+/// Make sure that the given value is a single ascii character, as needed by the csv reader
+fn validate_ascii_char(value: String) -> Result<(), String> {
+    if value.len() == 1 && value.is_ascii() {
+        Ok(())
+    } else {
+        Err(format!(
+            "The value must be a single ascii character, got: {:?}",
+            value
+        ))
+    }
+}
+
+impl<'a> fmt::Debug for ImportCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.input)?;
+        writeln!(f, "The file name to write to: {}", self.output)?;
+        writeln!(f, "Input format: {:?}", self.format)?;
+        if self.format == ImportFormat::Csv {
+            writeln!(f, "CSV options: {:?}", self.csv)?;
+        }
+        match self.schema {
+            Some(schema) => writeln!(f, "Schema file: {}", schema)?,
+            None => writeln!(
+                f,
+                "Records used to infer the schema: {}",
+                self.infer_records
+            )?,
+        }
+        writeln!(f, "Writer options: {:?}", self.writer_options)?;
+
+        Ok(())
+    }
+}
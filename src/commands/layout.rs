@@ -0,0 +1,144 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileExists, FileNotFound};
+use crate::layout::{read_layout, to_svg, Region, RegionKind};
+use crate::report::{print_report, print_table, MetadataFormat};
+use crate::utils::{check_path_present, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use std::fmt;
+use std::fs;
+
+pub struct LayoutCommand<'a> {
+    file_name: &'a str,
+    svg: Option<&'a str>,
+    format: MetadataFormat,
+}
+
+impl<'a> LayoutCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("layout")
+            .about("Prints the byte ranges of the parts of a Parquet file")
+            .arg(
+                Arg::with_name("file")
+                    .index(1)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("Parquet file to read"),
+            )
+            .arg(
+                Arg::with_name("svg")
+                    .long("svg")
+                    .takes_value(true)
+                    .value_name("OUTPUT")
+                    .required(false)
+                    .help("Also draw the layout as an SVG image in this file"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_name: matches.value_of("file").unwrap(),
+            svg: matches.value_of("svg"),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for LayoutCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        if !check_path_present(self.file_name) {
+            return Err(FileNotFound(String::from(self.file_name)));
+        }
+        if let Some(svg) = self.svg {
+            if check_path_present(svg) {
+                return Err(FileExists(svg.to_string()));
+            }
+        }
+
+        let file = open_file(self.file_name)?;
+        let file_size = file.metadata()?.len();
+        let regions = read_layout(&file)?;
+
+        if let Some(svg) = self.svg {
+            fs::write(svg, to_svg(&regions, file_size))?;
+        }
+
+        if self.format != MetadataFormat::Text {
+            let entries: Vec<_> =
+                regions.iter().map(|r| r.entry(self.file_name)).collect();
+            return print_report(self.format, "regions", &entries, None);
+        }
+
+        let rows: Vec<Vec<String>> = regions.iter().map(text_row).collect();
+        print_table(
+            &[
+                "Start",
+                "End",
+                "Size",
+                "Kind",
+                "Row Group",
+                "Column",
+                "Note",
+            ],
+            &rows,
+        );
+        println!();
+
+        let gaps: Vec<&Region> = regions
+            .iter()
+            .filter(|r| r.kind == RegionKind::Gap)
+            .collect();
+        let overlaps = regions
+            .iter()
+            .filter(|r| r.kind == RegionKind::Overlap)
+            .count();
+        if gaps.is_empty() && overlaps == 0 {
+            println!("No gaps or overlaps in the {} bytes of the file", file_size);
+        } else {
+            println!(
+                "{} gap(s) of {} bytes in total, {} overlap(s)",
+                gaps.len(),
+                gaps.iter().map(|g| g.size()).sum::<u64>(),
+                overlaps
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn text_row(region: &Region) -> Vec<String> {
+    let or_empty = |value: Option<String>| value.unwrap_or_default();
+    // the pages are indented below their column chunk
+    let kind = if region.is_page() {
+        format!("  {}", region.kind)
+    } else {
+        region.kind.to_string()
+    };
+
+    vec![
+        region.start.to_string(),
+        region.end.to_string(),
+        region.size().to_string(),
+        kind,
+        or_empty(region.row_group.map(|r| r.to_string())),
+        or_empty(region.column.clone()),
+        or_empty(region.note.clone()),
+    ]
+}
+
+impl<'a> fmt::Debug for LayoutCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.file_name)?;
+        if let Some(svg) = self.svg {
+            writeln!(f, "SVG output: {}", svg)?;
+        }
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileNotFound, InvalidArgument, UnknownColumn};
+use crate::levels::{assemble_records, get_path_levels, read_triples, Triple};
+use crate::report::{print_report, print_table, MetadataFormat};
+use crate::utils::{check_path_present, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use parquet::file::reader::{FileReader, RowGroupReader};
+use parquet::file::serialized_reader::SerializedFileReader;
+use serde_json::json;
+use std::fmt;
+
+pub struct LevelsCommand<'a> {
+    file_name: &'a str,
+    column: &'a str,
+    row_group: Option<usize>,
+    limit: Option<usize>,
+    format: MetadataFormat,
+}
+
+impl<'a> LevelsCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("levels")
+            .about("Prints the repetition and definition levels of a column of a Parquet file")
+            .arg(
+                Arg::with_name("file")
+                    .index(1)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("Parquet file to read"),
+            )
+            .arg(
+                Arg::with_name("column")
+                    .long("column")
+                    .short("c")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The leaf column to print, nested columns are given as a dotted path"),
+            )
+            .arg(
+                Arg::with_name("row-group")
+                    .long("row-group")
+                    .short("r")
+                    .takes_value(true)
+                    .required(false)
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The row group must be a number"))
+                    })
+                    .help("Only print the levels of this row group, starting from 0"),
+            )
+            .arg(
+                Arg::with_name("limit")
+                    .long("limit")
+                    .short("n")
+                    .takes_value(true)
+                    .value_name("RECORDS")
+                    .required(false)
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The limit must be a number of records"))
+                    })
+                    .help("The maximum number of records to print the levels of"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_name: matches.value_of("file").unwrap(),
+            column: matches.value_of("column").unwrap(),
+            row_group: matches.value_of("row-group").map(|v| v.parse().unwrap()),
+            limit: matches.value_of("limit").map(|v| v.parse().unwrap()),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for LevelsCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        if !check_path_present(self.file_name) {
+            return Err(FileNotFound(String::from(self.file_name)));
+        }
+
+        let reader = SerializedFileReader::new(open_file(self.file_name)?)?;
+        let metadata = reader.metadata();
+
+        let row_groups = metadata.num_row_groups();
+        if let Some(row_group) = self.row_group {
+            if row_group >= row_groups {
+                return Err(InvalidArgument(format!(
+                    "The row group must be less than {}, the number of row groups in {}",
+                    row_groups, self.file_name
+                )));
+            }
+        }
+
+        // the levels are only stored for the leaf columns
+        let schema = metadata.file_metadata().schema_descr();
+        let index = match (0..schema.num_columns())
+            .find(|&i| schema.column(i).path().string() == self.column)
+        {
+            Some(index) => index,
+            None => {
+                let paths: Vec<String> =
+                    schema.columns().iter().map(|c| c.path().string()).collect();
+                return Err(UnknownColumn(self.column.to_string(), paths.join(", ")));
+            }
+        };
+        let column = schema.column(index);
+        let path_levels = get_path_levels(schema.root_schema(), &column);
+
+        if self.format == MetadataFormat::Text {
+            println!("Column: {}", self.column);
+            println!("Max repetition level: {}", column.max_rep_level());
+            println!("Max definition level: {}", column.max_def_level());
+            println!();
+            let rows: Vec<Vec<String>> = path_levels
+                .iter()
+                .map(|level| {
+                    vec![
+                        level.name.clone(),
+                        level.repetition.to_string(),
+                        level.def.to_string(),
+                        level.rep.map_or(String::new(), |r| r.to_string()),
+                    ]
+                })
+                .collect();
+            print_table(&["Field", "Repetition", "Def", "Rep"], &rows);
+            println!();
+        }
+
+        let mut entries = Vec::new();
+        let mut records = 0;
+        for row_group in
+            (0..row_groups).filter(|i| self.row_group.map_or(true, |r| r == *i))
+        {
+            let remaining = self.limit.map(|limit| limit - records);
+            if remaining == Some(0) {
+                break;
+            }
+
+            let column_reader =
+                reader.get_row_group(row_group)?.get_column_reader(index)?;
+            let triples = read_triples(column_reader, &column, remaining)?;
+            let first_record = records;
+            records += triples.iter().filter(|t| t.rep == 0).count();
+
+            if self.format != MetadataFormat::Text {
+                let mut record = first_record;
+                for (i, triple) in triples.iter().enumerate() {
+                    if triple.rep == 0 && i > 0 {
+                        record += 1;
+                    }
+                    entries.push(vec![
+                        ("path", json!(self.file_name)),
+                        ("row_group", json!(row_group)),
+                        ("record", json!(record)),
+                        ("repetition_level", json!(triple.rep)),
+                        ("definition_level", json!(triple.def)),
+                        ("value", json!(triple.value)),
+                    ]);
+                }
+                continue;
+            }
+
+            println!("Row group {}:", row_group);
+            let rows: Vec<Vec<String>> = triples.iter().map(text_row).collect();
+            print_table(&["Rep", "Def", "Value"], &rows);
+            println!();
+
+            // show how the levels assemble into the values of the column in each record
+            println!("Records:");
+            for (i, record) in assemble_records(&triples, &path_levels).iter().enumerate()
+            {
+                println!("{}: {}", first_record + i, record);
+            }
+            println!();
+        }
+
+        if self.format != MetadataFormat::Text {
+            return print_report(self.format, "levels", &entries, None);
+        }
+
+        Ok(())
+    }
+}
+
+fn text_row(triple: &Triple) -> Vec<String> {
+    vec![
+        triple.rep.to_string(),
+        triple.def.to_string(),
+        triple.value.clone().unwrap_or_else(|| String::from("null")),
+    ]
+}
+
+impl<'a> fmt::Debug for LevelsCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.file_name)?;
+        writeln!(f, "Column: {}", self.column)?;
+        if let Some(row_group) = self.row_group {
+            writeln!(f, "Row group: {}", row_group)?;
+        }
+        if let Some(limit) = self.limit {
+            writeln!(f, "Records limit: {}", limit)?;
+        }
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
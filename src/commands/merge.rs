@@ -1,15 +1,25 @@
 use crate::command::PQRSCommand;
 use crate::errors::PQRSError;
 use crate::errors::PQRSError::{FileExists, FileNotFound};
-use crate::utils::{check_path_present, get_row_batches, write_parquet};
+use crate::utils::{
+    adapt_batch, check_path_present, get_arrow_schema, get_batch_reader, get_compression,
+    merge_schemas, write_parquet, SchemaMode,
+};
+use crate::writer::WriterOptions;
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
 use std::fmt;
-use std::ops::Add;
+use std::fs;
+use std::iter;
+use std::sync::Arc;
 
 pub struct MergeCommand<'a> {
     inputs: Vec<&'a str>,
     output: &'a str,
+    schema_mode: SchemaMode,
+    writer_options: WriterOptions<'a>,
 }
 
 impl<'a> MergeCommand<'a> {
@@ -34,12 +44,28 @@ impl<'a> MergeCommand<'a> {
                     .required(true)
                     .help("Parquet file to write"),
             )
+            .arg(
+                Arg::with_name("schema-mode")
+                    .long("schema-mode")
+                    .takes_value(true)
+                    .required(false)
+                    .possible_values(&["strict", "union", "intersect"])
+                    .default_value("strict")
+                    .help("How to combine the schemas of inputs with different columns"),
+            )
+            .args(&WriterOptions::args())
     }
 
     pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
         Self {
             inputs: matches.values_of("input").unwrap().collect(),
             output: matches.value_of("output").unwrap(),
+            schema_mode: match matches.value_of("schema-mode") {
+                Some("union") => SchemaMode::Union,
+                Some("intersect") => SchemaMode::Intersect,
+                _ => SchemaMode::Strict,
+            },
+            writer_options: WriterOptions::new(matches),
         }
     }
 }
@@ -61,16 +87,50 @@ impl<'a> PQRSCommand for MergeCommand<'a> {
             }
         }
 
-        let mut combined = get_row_batches(self.inputs[0])?;
-        for input in &self.inputs[1..] {
-            let local = get_row_batches(input)?;
-            combined = combined.add(local);
+        // the schemas are combined before writing so that mismatching inputs
+        // are reported without creating the output, only the footers are read here
+        let mut schemas = Vec::new();
+        for input in &self.inputs {
+            schemas.push((*input, get_arrow_schema(input)?));
         }
-        // debug!("The combined data looks like this: {:#?}", combined);
-        // debug!("This is the input schema: {:#?}", combined.schema);
-        write_parquet(combined, self.output)?;
+        let schemas: Vec<(&str, &_)> = schemas.iter().map(|(i, s)| (*i, s)).collect();
+        let schema = Arc::new(merge_schemas(&schemas, self.schema_mode)?);
+        debug!("This is the output schema: {:#?}", schema);
 
-        Ok(())
+        // unless another codec is requested, the output is compressed like the first input
+        let props = self
+            .writer_options
+            .properties(get_compression(self.inputs[0])?);
+
+        // the inputs are read one batch at a time and written straight to the output
+        // so that the memory used does not depend on the size of the inputs. Each input
+        // is only opened once the previous one has been written, to keep a single file
+        // open no matter how many inputs are merged.
+        let batches = self
+            .inputs
+            .iter()
+            .flat_map(
+                |input| -> Box<dyn Iterator<Item = ArrowResult<RecordBatch>>> {
+                    match get_batch_reader(input) {
+                        Ok((_, reader)) => Box::new(reader),
+                        Err(e) => Box::new(iter::once(Err(ArrowError::ExternalError(
+                            Box::new(e),
+                        )))),
+                    }
+                },
+            )
+            .map(|batch| batch.and_then(|b| adapt_batch(&b, &schema)));
+        match write_parquet(&schema, batches, self.output, props) {
+            Ok(rows) => {
+                debug!("Wrote {} rows to {}", rows, self.output);
+                Ok(())
+            }
+            Err(e) => {
+                // do not leave a partially written file behind
+                let _ = fs::remove_file(self.output);
+                Err(e)
+            }
+        }
     }
 }
 
@@ -78,6 +138,8 @@ impl<'a> fmt::Debug for MergeCommand<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "The file names to read are: {}", self.inputs.join(", "))?;
         writeln!(f, "The file name to write to: {}", self.output)?;
+        writeln!(f, "Schema mode: {:?}", self.schema_mode)?;
+        writeln!(f, "Writer options: {:?}", self.writer_options)?;
 
         Ok(())
     }
@@ -1,7 +1,18 @@
 pub(crate) mod cat;
+pub(crate) mod convert;
+pub(crate) mod dictionary;
+pub(crate) mod footer;
 pub(crate) mod head;
+pub(crate) mod import;
+pub(crate) mod layout;
+pub(crate) mod levels;
 pub(crate) mod merge;
+pub(crate) mod pages;
+pub(crate) mod profile;
+pub(crate) mod query;
 pub(crate) mod rowcount;
 pub(crate) mod sample;
 pub(crate) mod schema;
+pub(crate) mod schema_diff;
 pub(crate) mod size;
+pub(crate) mod stats;
@@ -0,0 +1,171 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileNotFound, InvalidArgument};
+use crate::pages::{read_page_headers, PageInfo};
+use crate::report::{print_report, print_table, MetadataFormat};
+use crate::utils::{check_path_present, get_column_indices, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use parquet::file::reader::FileReader;
+use parquet::file::serialized_reader::SerializedFileReader;
+use std::fmt;
+
+pub struct PagesCommand<'a> {
+    file_name: &'a str,
+    column: Option<&'a str>,
+    row_group: Option<usize>,
+    format: MetadataFormat,
+}
+
+impl<'a> PagesCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("pages")
+            .about("Prints the dictionary and data pages of a Parquet file")
+            .arg(
+                Arg::with_name("file")
+                    .index(1)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("Parquet file to read"),
+            )
+            .arg(
+                Arg::with_name("column")
+                    .long("column")
+                    .short("c")
+                    .takes_value(true)
+                    .required(false)
+                    .help("Only print the pages of this column, nested columns are given as a dotted path"),
+            )
+            .arg(
+                Arg::with_name("row-group")
+                    .long("row-group")
+                    .short("r")
+                    .takes_value(true)
+                    .required(false)
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The row group must be a number"))
+                    })
+                    .help("Only print the pages of this row group, starting from 0"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_name: matches.value_of("file").unwrap(),
+            column: matches.value_of("column"),
+            row_group: matches.value_of("row-group").map(|v| v.parse().unwrap()),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for PagesCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        if !check_path_present(self.file_name) {
+            return Err(FileNotFound(String::from(self.file_name)));
+        }
+
+        let file = open_file(self.file_name)?;
+        let reader = SerializedFileReader::new(file.try_clone()?)?;
+        let metadata = reader.metadata();
+
+        let row_groups = metadata.num_row_groups();
+        if let Some(row_group) = self.row_group {
+            if row_group >= row_groups {
+                return Err(InvalidArgument(format!(
+                    "The row group must be less than {}, the number of row groups in {}",
+                    row_groups, self.file_name
+                )));
+            }
+        }
+
+        let schema = metadata.file_metadata().schema_descr();
+        let columns = get_column_indices(schema, self.column)?;
+
+        let mut entries = Vec::new();
+        for row_group in
+            (0..row_groups).filter(|i| self.row_group.map_or(true, |r| r == *i))
+        {
+            let row_group_metadata = metadata.row_group(row_group);
+            for &i in &columns {
+                let chunk = row_group_metadata.column(i);
+                let column = chunk.column_path().string();
+                let pages = read_page_headers(&file, chunk)?;
+
+                if self.format == MetadataFormat::Text {
+                    println!("Row group {}, column {}:", row_group, column);
+                    let rows: Vec<Vec<String>> = pages.iter().map(text_row).collect();
+                    print_table(
+                        &[
+                            "Type",
+                            "Offset",
+                            "Compressed",
+                            "Uncompressed",
+                            "Values",
+                            "Encoding",
+                            "Def Levels",
+                            "Rep Levels",
+                            "Min",
+                            "Max",
+                            "Nulls",
+                            "CRC",
+                        ],
+                        &rows,
+                    );
+                    println!();
+                } else {
+                    entries.extend(
+                        pages
+                            .iter()
+                            .map(|p| p.entry(self.file_name, row_group, &column)),
+                    );
+                }
+            }
+        }
+
+        if self.format != MetadataFormat::Text {
+            return print_report(self.format, "pages", &entries, None);
+        }
+
+        Ok(())
+    }
+}
+
+fn text_row(page: &PageInfo) -> Vec<String> {
+    let or_empty = |value: Option<String>| value.unwrap_or_else(|| String::from("-"));
+
+    vec![
+        page.page_type.to_string(),
+        page.offset.to_string(),
+        page.compressed_size.to_string(),
+        page.uncompressed_size.to_string(),
+        or_empty(page.num_values.map(|v| v.to_string())),
+        or_empty(page.encoding.map(|e| e.to_string())),
+        or_empty(page.def_level_encoding.map(|e| e.to_string())),
+        or_empty(page.rep_level_encoding.map(|e| e.to_string())),
+        or_empty(page.min.clone()),
+        or_empty(page.max.clone()),
+        or_empty(page.null_count.map(|n| n.to_string())),
+        String::from(if page.has_crc { "yes" } else { "no" }),
+    ]
+}
+
+impl<'a> fmt::Debug for PagesCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The file name to read is: {}", self.file_name)?;
+        if let Some(column) = self.column {
+            writeln!(f, "Column: {}", column)?;
+        }
+        if let Some(row_group) = self.row_group {
+            writeln!(f, "Row group: {}", row_group)?;
+        }
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
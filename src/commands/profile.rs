@@ -0,0 +1,210 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::FileNotFound;
+use crate::profile::{
+    create_profiles, profile_batch, value_key, ColumnProfile, QUANTILES,
+};
+use crate::report::{print_report, Entry, MetadataFormat};
+use crate::utils::{check_path_present, get_batch_reader};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use serde_json::json;
+use std::fmt;
+
+pub struct ProfileCommand<'a> {
+    file_names: Vec<&'a str>,
+    approximate: bool,
+    top: usize,
+    format: MetadataFormat,
+}
+
+impl<'a> ProfileCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("profile")
+            .about("Prints a summary of the values of every column in Parquet file(s)")
+            .arg(
+                Arg::with_name("files")
+                    .index(1)
+                    .multiple(true)
+                    .value_name("FILES")
+                    .value_delimiter(" ")
+                    .required(true)
+                    .help("Parquet files to read"),
+            )
+            .arg(
+                Arg::with_name("approximate")
+                    .long("approximate")
+                    .short("a")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Estimate distinct counts, quantiles and frequent values using bounded memory"),
+            )
+            .arg(
+                Arg::with_name("top")
+                    .long("top")
+                    .short("k")
+                    .takes_value(true)
+                    .required(false)
+                    .default_value("5")
+                    .validator(|v| {
+                        v.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| String::from("The number of values must be a number"))
+                    })
+                    .help("The number of most frequent values to print"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_names: matches.values_of("files").unwrap().collect(),
+            approximate: matches.is_present("approximate"),
+            // the validator makes sure that the value is a number
+            top: matches.value_of("top").unwrap().parse().unwrap(),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for ProfileCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        // make sure all files are present before printing any data
+        for file_name in &self.file_names {
+            if !check_path_present(*file_name) {
+                return Err(FileNotFound(String::from(*file_name)));
+            }
+        }
+
+        let mut entries = Vec::new();
+        for file_name in &self.file_names {
+            // the records are read one batch at a time, only the summaries are kept
+            let (_, reader) = get_batch_reader(file_name)?;
+            let mut profiles: Vec<ColumnProfile> = Vec::new();
+            for batch in reader {
+                let batch = batch?;
+                if profiles.is_empty() {
+                    profiles = create_profiles(&batch, self.approximate, self.top);
+                }
+                profile_batch(&mut profiles, &batch);
+            }
+
+            if self.format == MetadataFormat::Text {
+                println!("File Name: {}", file_name);
+                for profile in &profiles {
+                    println!();
+                    self.print_profile(profile);
+                }
+                println!();
+            } else {
+                entries.extend(profiles.iter().map(|p| self.entry(file_name, p)));
+            }
+        }
+
+        if self.format != MetadataFormat::Text {
+            return print_report(self.format, "columns", &entries, None);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> ProfileCommand<'a> {
+    fn print_profile(&self, profile: &ColumnProfile) {
+        let estimate = if profile.is_approximate() { "~" } else { "" };
+
+        println!("Column: {} ({:?})", profile.name, profile.data_type);
+        println!("  count: {}", profile.count);
+        println!(
+            "  nulls: {} ({:.2}%)",
+            profile.nulls,
+            profile.null_percentage()
+        );
+        println!("  distinct: {}{}", estimate, profile.distinct());
+        if let (Some(min), Some(max)) = (&profile.min, &profile.max) {
+            println!("  min: {}", value_key(min));
+            println!("  max: {}", value_key(max));
+        }
+        if let Some((mean, stddev)) = profile.mean_stddev() {
+            println!("  mean: {}", mean);
+            println!("  stddev: {}", stddev);
+        }
+        if let Some(values) = profile.quantiles(&QUANTILES) {
+            let quantiles: Vec<String> = QUANTILES
+                .iter()
+                .zip(values)
+                .map(|(q, v)| format!("p{}={}{}", q * 100.0, estimate, v))
+                .collect();
+            println!("  quantiles: {}", quantiles.join(", "));
+        }
+        if let Some((min, max, avg)) = profile.lengths() {
+            println!("  length: min {}, max {}, avg {:.2}", min, max, avg);
+        }
+
+        let top: Vec<String> = profile
+            .top(self.top)
+            .into_iter()
+            .map(|(value, count)| format!("{:?} ({}{})", value, estimate, count))
+            .collect();
+        if !top.is_empty() {
+            println!("  top values: {}", top.join(", "));
+        }
+    }
+
+    fn entry(&self, file_name: &str, profile: &ColumnProfile) -> Entry {
+        let (mean, stddev) = match profile.mean_stddev() {
+            Some((mean, stddev)) => (Some(mean), Some(stddev)),
+            None => (None, None),
+        };
+        let quantiles = profile.quantiles(&QUANTILES).map(|values| {
+            QUANTILES
+                .iter()
+                .zip(values)
+                .map(|(q, v)| (format!("p{}", q * 100.0), json!(v)))
+                .collect::<serde_json::Map<_, _>>()
+        });
+        let lengths = profile
+            .lengths()
+            .map(|(min, max, avg)| json!({"min": min, "max": max, "avg": avg}));
+        let top: Vec<_> = profile
+            .top(self.top)
+            .into_iter()
+            .map(|(value, count)| json!({"value": value, "count": count}))
+            .collect();
+
+        vec![
+            ("path", json!(file_name)),
+            ("column", json!(profile.name)),
+            ("type", json!(format!("{:?}", profile.data_type))),
+            ("count", json!(profile.count)),
+            ("nulls", json!(profile.nulls)),
+            ("null_percentage", json!(profile.null_percentage())),
+            ("distinct", json!(profile.distinct())),
+            ("approximate", json!(profile.is_approximate())),
+            ("min", json!(profile.min.as_ref().map(value_key))),
+            ("max", json!(profile.max.as_ref().map(value_key))),
+            ("mean", json!(mean)),
+            ("stddev", json!(stddev)),
+            ("quantiles", json!(quantiles)),
+            ("length", json!(lengths)),
+            ("top", json!(top)),
+        ]
+    }
+}
+
+impl<'a> fmt::Debug for ProfileCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "The file names to read are: {}",
+            self.file_names.join(", ")
+        )?;
+        writeln!(f, "Approximate: {}", self.approximate)?;
+        writeln!(f, "Number of frequent values: {}", self.top)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileNotFound, InvalidExpression, SchemaMismatch};
+use crate::output::{OutputFormat, RowPrinter};
+use crate::query::Query;
+use crate::utils::{check_path_present, get_arrow_schema, get_row_batches};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use std::fmt;
+
+pub struct QueryCommand<'a> {
+    sql: &'a str,
+    file_names: Vec<&'a str>,
+    table: &'a str,
+    output: OutputFormat,
+}
+
+impl<'a> QueryCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("query")
+            .about("Runs a SQL query against Parquet file(s)")
+            .arg(
+                Arg::with_name("sql")
+                    .index(1)
+                    .value_name("SQL")
+                    .required(true)
+                    .help("The query to run, e.g. \"SELECT COUNT(*) FROM t\""),
+            )
+            .arg(
+                Arg::with_name("files")
+                    .index(2)
+                    .multiple(true)
+                    .value_name("FILES")
+                    .value_delimiter(" ")
+                    .required(true)
+                    .help("Parquet files to query, the files are combined into a single table"),
+            )
+            .arg(
+                Arg::with_name("table")
+                    .long("table")
+                    .short("t")
+                    .takes_value(true)
+                    .default_value("t")
+                    .required(false)
+                    .help("The name of the table used in the query"),
+            )
+            .args(&OutputFormat::args())
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            sql: matches.value_of("sql").unwrap(),
+            file_names: matches.values_of("files").unwrap().collect(),
+            table: matches.value_of("table").unwrap(),
+            output: OutputFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for QueryCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        // make sure all files are present before reading any data
+        for file_name in &self.file_names {
+            if !check_path_present(*file_name) {
+                return Err(FileNotFound(String::from(*file_name)));
+            }
+        }
+
+        // parse the query before reading any data to fail early on invalid queries
+        let query = Query::parse(self.sql)?;
+        debug!("Parsed query: {:#?}", query);
+        if query.table != self.table {
+            return Err(InvalidExpression(format!(
+                "Unknown table {}, the files are available as {}",
+                query.table, self.table
+            )));
+        }
+
+        // the files are combined into a single table, which requires that they all
+        // share the same schema; only the footers are read for this check
+        let schema = get_arrow_schema(self.file_names[0])?;
+        for file_name in &self.file_names[1..] {
+            if get_arrow_schema(file_name)?.fields() != schema.fields() {
+                return Err(SchemaMismatch(format!(
+                    "{} does not have the same columns as {}",
+                    file_name, self.file_names[0]
+                )));
+            }
+        }
+
+        // read the files one at a time so only one of them is open at any time
+        let mut combined = get_row_batches(self.file_names[0])?;
+        for file_name in &self.file_names[1..] {
+            combined = combined + get_row_batches(file_name)?;
+        }
+
+        let mut printer = RowPrinter::new(self.output);
+        query.execute(&combined, &mut printer)?;
+        printer.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for QueryCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The query to run is: {}", self.sql)?;
+        writeln!(
+            f,
+            "The file names to read are: {}",
+            &self.file_names.join(", ")
+        )?;
+        writeln!(f, "The table name is: {}", self.table)?;
+        writeln!(f, "Output format: {:?}", &self.output)?;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,15 @@
 use crate::command::PQRSCommand;
 use crate::errors::PQRSError;
 use crate::errors::PQRSError::FileNotFound;
-use crate::utils::{check_path_present, get_row_count, open_file};
+use crate::report::{print_report, MetadataFormat};
+use crate::utils::{check_path_present, get_file_summary, get_total_entry};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
 use std::fmt;
 
 pub struct RowCountCommand<'a> {
     file_names: Vec<&'a str>,
+    format: MetadataFormat,
 }
 
 impl<'a> RowCountCommand<'a> {
@@ -28,6 +30,7 @@ impl<'a> RowCountCommand<'a> {
     pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
         Self {
             file_names: matches.values_of("files").unwrap().collect(),
+            format: MetadataFormat::new(matches),
         }
     }
 }
@@ -44,10 +47,23 @@ impl<'a> PQRSCommand for RowCountCommand<'a> {
             }
         }
 
+        let mut summaries = Vec::new();
         for file_name in &self.file_names {
-            let file = open_file(file_name)?;
-            let row_count = get_row_count(file)?;
-            println!("File Name: {}: {} rows", &file_name, &row_count);
+            summaries.push(get_file_summary(file_name)?);
+        }
+
+        if self.format != MetadataFormat::Text {
+            let entries: Vec<_> = summaries.iter().map(|s| s.entry()).collect();
+            let total = get_total_entry(&summaries);
+            return print_report(self.format, "files", &entries, Some(total));
+        }
+
+        for summary in &summaries {
+            println!("File Name: {}: {} rows", &summary.path, &summary.rows);
+        }
+        if summaries.len() > 1 {
+            let total: i64 = summaries.iter().map(|s| s.rows).sum();
+            println!("Total: {} rows", total);
         }
 
         Ok(())
@@ -60,6 +76,9 @@ impl<'a> fmt::Debug for RowCountCommand<'a> {
             f,
             "The file names to read are: {}",
             self.file_names.join(", ")
-        )
+        )?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
     }
 }
@@ -1,16 +1,20 @@
 use crate::command::PQRSCommand;
 use crate::errors::PQRSError;
 use crate::errors::PQRSError::FileNotFound;
-use crate::utils::{check_path_present, open_file, print_rows_random};
+use crate::output::{OutputFormat, RowPrinter};
+use crate::utils::{
+    check_path_present, open_file, print_rows_random, SampleMethod, SampleOptions,
+    SampleSize,
+};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
 use std::fmt;
 
 pub struct SampleCommand<'a> {
     file_name: &'a str,
-    num_records: i64,
-    use_json: bool,
-    randomize: bool,
+    sample: SampleOptions,
+    columns: Option<Vec<&'a str>>,
+    output: OutputFormat,
 }
 
 impl<'a> SampleCommand<'a> {
@@ -24,30 +28,83 @@ impl<'a> SampleCommand<'a> {
                     .required(true)
                     .help("Parquet file to read"),
             )
+            .args(&OutputFormat::args())
             .arg(
-                Arg::with_name("json")
-                    .long("json")
-                    .short("j")
-                    .takes_value(false)
+                Arg::with_name("columns")
+                    .long("columns")
+                    .short("c")
+                    .takes_value(true)
+                    .use_delimiter(true)
+                    .value_name("COLUMNS")
                     .required(false)
-                    .help("Use JSON lines format for printing"),
+                    .help("Columns to read, use dots to select nested fields"),
             )
             .arg(
                 Arg::with_name("records")
                     .long("records")
                     .short("n")
                     .takes_value(true)
-                    .required(true)
+                    .required_unless("fraction")
+                    .conflicts_with("fraction")
+                    .validator(validate_records)
                     .help("The number of records to sample"),
             )
+            .arg(
+                Arg::with_name("fraction")
+                    .long("fraction")
+                    .takes_value(true)
+                    .required(false)
+                    .validator(validate_fraction)
+                    .help("Sample every record with the given probability, e.g. 0.01"),
+            )
+            .arg(
+                Arg::with_name("seed")
+                    .long("seed")
+                    .takes_value(true)
+                    .required(false)
+                    .validator(validate_seed)
+                    .help(
+                        "Seed for the random number generator, for reproducible samples",
+                    ),
+            )
+            .arg(
+                Arg::with_name("method")
+                    .long("method")
+                    .takes_value(true)
+                    .required(false)
+                    .possible_values(&["uniform", "rowgroup-cluster"])
+                    .default_value("uniform")
+                    .help("Sample single records, or whole row groups which is faster"),
+            )
+            .arg(
+                Arg::with_name("preserve-order")
+                    .long("preserve-order")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Print the sampled records in the same order as in the file"),
+            )
     }
 
     pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
         Self {
             file_name: matches.value_of("file").unwrap(),
-            num_records: matches.value_of("records").unwrap().parse().unwrap(),
-            use_json: matches.is_present("json"),
-            randomize: true,
+            // the validators make sure that the numbers can be parsed
+            sample: SampleOptions {
+                size: match matches.value_of("fraction") {
+                    Some(fraction) => SampleSize::Fraction(fraction.parse().unwrap()),
+                    None => SampleSize::Records(
+                        matches.value_of("records").unwrap().parse().unwrap(),
+                    ),
+                },
+                method: match matches.value_of("method") {
+                    Some("rowgroup-cluster") => SampleMethod::RowGroupCluster,
+                    _ => SampleMethod::Uniform,
+                },
+                seed: matches.value_of("seed").map(|seed| seed.parse().unwrap()),
+                preserve_order: matches.is_present("preserve-order"),
+            },
+            columns: matches.values_of("columns").map(|c| c.collect()),
+            output: OutputFormat::new(matches),
         }
     }
 }
@@ -62,7 +119,9 @@ impl<'a> PQRSCommand for SampleCommand<'a> {
         }
 
         let file = open_file(self.file_name)?;
-        print_rows_random(file, self.num_records, self.use_json)?;
+        let mut printer = RowPrinter::new(self.output);
+        print_rows_random(file, &self.sample, self.columns.as_deref(), &mut printer)?;
+        printer.flush()?;
 
         Ok(())
     }
@@ -71,10 +130,36 @@ impl<'a> PQRSCommand for SampleCommand<'a> {
 impl<'a> fmt::Debug for SampleCommand<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "The file name to read is: {}", &self.file_name)?;
-        writeln!(f, "Number of records to print: {}", &self.num_records)?;
-        writeln!(f, "Use JSON Output format: {}", &self.use_json)?;
-        writeln!(f, "Randomize output: {}", self.randomize)?;
+        writeln!(f, "Sample size: {:?}", &self.sample.size)?;
+        writeln!(f, "Sample method: {:?}", &self.sample.method)?;
+        writeln!(f, "Random seed: {:?}", &self.sample.seed)?;
+        writeln!(f, "Columns to read: {:?}", &self.columns)?;
+        writeln!(f, "Output format: {:?}", &self.output)?;
+        writeln!(f, "Randomize output: {}", !self.sample.preserve_order)?;
 
         Ok(())
     }
 }
+
+fn validate_records(records: String) -> Result<(), String> {
+    records
+        .parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| format!("Expected a positive number of records, got: {:?}", records))
+}
+
+fn validate_fraction(fraction: String) -> Result<(), String> {
+    match fraction.parse::<f64>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(()),
+        _ => Err(format!(
+            "The fraction must be a number between 0 and 1, got: {:?}",
+            fraction
+        )),
+    }
+}
+
+fn validate_seed(seed: String) -> Result<(), String> {
+    seed.parse::<u64>()
+        .map(|_| ())
+        .map_err(|_| format!("Expected a positive number as the seed, got: {:?}", seed))
+}
@@ -1,17 +1,39 @@
+use crate::avro::get_avro_schema;
 use crate::command::PQRSCommand;
+use crate::ddl::{bigquery_schema, create_table, get_columns, json_schema, DdlFormat};
+use crate::diff::{diff_schemas, Change, Compatibility};
 use crate::errors::PQRSError;
-use crate::errors::PQRSError::FileNotFound;
-use crate::utils::{check_path_present, open_file};
+use crate::errors::PQRSError::{FileNotFound, SchemaMismatch};
+use crate::report::{print_report, print_table, Entry, MetadataFormat};
+use crate::utils::{check_path_present, get_file_summary, open_file};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
+use parquet::arrow::parquet_to_arrow_schema;
 use parquet::file::reader::FileReader;
 use parquet::file::serialized_reader::SerializedFileReader;
-use parquet::schema::printer::{print_file_metadata, print_parquet_metadata};
+use parquet::schema::printer::{
+    print_file_metadata, print_parquet_metadata, print_schema,
+};
+use parquet::schema::types::TypePtr;
+use serde_json::{json, Value};
 use std::fmt;
+use std::io;
+use std::path::Path;
 
 pub struct SchemaCommand<'a> {
     file_names: Vec<&'a str>,
     use_detailed: bool,
+    format: MetadataFormat,
+    export: Option<DdlFormat>,
+    check_consistent: bool,
+}
+
+/// The files sharing the same schema, the fingerprint is a FNV-1a hash of the printed schema
+struct SchemaGroup<'a> {
+    fingerprint: String,
+    message_type: String,
+    schema: TypePtr,
+    file_names: Vec<&'a str>,
 }
 
 impl<'a> SchemaCommand<'a> {
@@ -35,12 +57,50 @@ impl<'a> SchemaCommand<'a> {
                     .required(false)
                     .help("Enable printing full file metadata"),
             )
+            .arg(
+                Arg::with_name("as")
+                    .long("as")
+                    .takes_value(true)
+                    .required(false)
+                    .conflicts_with("detailed")
+                    .possible_values(&[
+                        "spark",
+                        "hive",
+                        "bigquery",
+                        "postgres",
+                        "duckdb",
+                        "avro",
+                        "json-schema",
+                        "arrow",
+                    ])
+                    .help("Print the schema as a CREATE TABLE statement or as the schema of another system"),
+            )
+            .arg(
+                Arg::with_name("check-consistent")
+                    .long("check-consistent")
+                    .takes_value(false)
+                    .required(false)
+                    .conflicts_with_all(&["detailed", "as"])
+                    .help("Group the files by schema and print the differences from the schema of most files, fails when the files do not all have the same schema"),
+            )
     }
 
     pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
         Self {
             file_names: matches.values_of("files").unwrap().collect(),
             use_detailed: matches.is_present("detailed"),
+            format: MetadataFormat::new(matches),
+            export: matches.value_of("as").map(|target| match target {
+                "spark" => DdlFormat::Spark,
+                "hive" => DdlFormat::Hive,
+                "bigquery" => DdlFormat::BigQuery,
+                "postgres" => DdlFormat::Postgres,
+                "duckdb" => DdlFormat::DuckDb,
+                "avro" => DdlFormat::Avro,
+                "json-schema" => DdlFormat::JsonSchema,
+                _ => DdlFormat::Arrow,
+            }),
+            check_consistent: matches.is_present("check-consistent"),
         }
     }
 }
@@ -57,6 +117,28 @@ impl<'a> PQRSCommand for SchemaCommand<'a> {
             }
         }
 
+        if self.check_consistent {
+            return self.print_schema_groups();
+        }
+
+        if let Some(export) = self.export {
+            for (i, file_name) in self.file_names.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!("{}", export_schema(file_name, export)?);
+            }
+            return Ok(());
+        }
+
+        if self.format != MetadataFormat::Text {
+            let mut entries = Vec::new();
+            for file_name in &self.file_names {
+                entries.push(get_file_summary(file_name)?.entry());
+            }
+            return print_report(self.format, "files", &entries, None);
+        }
+
         for file_name in &self.file_names {
             let file = open_file(file_name)?;
             match SerializedFileReader::new(file) {
@@ -81,6 +163,158 @@ impl<'a> PQRSCommand for SchemaCommand<'a> {
     }
 }
 
+impl<'a> SchemaCommand<'a> {
+    /// Print every distinct schema once along with the files that have it, the other
+    /// schemas are compared to the schema shared by most of the files
+    fn print_schema_groups(&self) -> Result<(), PQRSError> {
+        let mut groups: Vec<SchemaGroup> = Vec::new();
+        for file_name in &self.file_names {
+            let reader = SerializedFileReader::new(open_file(file_name)?)?;
+            let schema = reader
+                .metadata()
+                .file_metadata()
+                .schema_descr()
+                .root_schema_ptr();
+
+            let mut message_type = Vec::new();
+            print_schema(&mut message_type, &schema);
+            let message_type = String::from_utf8_lossy(&message_type)
+                .trim_end()
+                .to_string();
+
+            match groups.iter_mut().find(|g| g.message_type == message_type) {
+                Some(group) => group.file_names.push(*file_name),
+                None => groups.push(SchemaGroup {
+                    fingerprint: fingerprint(&message_type),
+                    message_type,
+                    schema,
+                    file_names: vec![*file_name],
+                }),
+            }
+        }
+
+        // the first of the largest groups is the reference, ties go to the first file
+        let mut majority = 0;
+        for (i, group) in groups.iter().enumerate() {
+            if group.file_names.len() > groups[majority].file_names.len() {
+                majority = i;
+            }
+        }
+        let differences: Vec<Vec<Change>> = groups
+            .iter()
+            .map(|group| {
+                diff_schemas(&groups[majority].schema, &group.schema, Compatibility::Full)
+            })
+            .collect();
+
+        if self.format == MetadataFormat::Text {
+            for (i, (group, changes)) in groups.iter().zip(&differences).enumerate() {
+                let role = if i == majority { ", majority" } else { "" };
+                println!(
+                    "Schema {} (fingerprint {}, {} file(s){}):",
+                    i + 1,
+                    group.fingerprint,
+                    group.file_names.len(),
+                    role
+                );
+                println!("{}", group.message_type);
+                if !changes.is_empty() {
+                    println!();
+                    println!("Differences from the majority schema:");
+                    let rows: Vec<Vec<String>> =
+                        changes.iter().map(Change::text_row).collect();
+                    print_table(&["Change", "Path", "Majority", "This schema"], &rows);
+                }
+                println!();
+                println!("Files:");
+                for file_name in &group.file_names {
+                    println!("  {}", file_name);
+                }
+                println!();
+            }
+        } else {
+            let entries: Vec<Entry> = groups
+                .iter()
+                .zip(&differences)
+                .enumerate()
+                .map(|(i, (group, changes))| {
+                    let changes: Vec<Value> = changes
+                        .iter()
+                        .map(|c| {
+                            let fields =
+                                c.entry().into_iter().filter(|(k, _)| *k != "breaking");
+                            Value::Object(
+                                fields.map(|(k, v)| (k.to_string(), v)).collect(),
+                            )
+                        })
+                        .collect();
+                    vec![
+                        ("fingerprint", json!(group.fingerprint)),
+                        ("majority", json!(i == majority)),
+                        ("file_count", json!(group.file_names.len())),
+                        ("files", json!(group.file_names)),
+                        ("schema", json!(group.message_type)),
+                        ("differences", Value::Array(changes)),
+                    ]
+                })
+                .collect();
+            print_report(self.format, "schemas", &entries, None)?;
+        }
+
+        if groups.len() > 1 {
+            return Err(SchemaMismatch(format!(
+                "{} distinct schemas found across {} files",
+                groups.len(),
+                self.file_names.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the 64 bit FNV-1a hash of the printed schema. Unlike the hashers of the standard
+/// library its algorithm is fixed, so the fingerprints can be compared across releases.
+fn fingerprint(message_type: &str) -> String {
+    let hash = message_type
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// Return the schema of the file in the given format, the SQL statements create a table
+/// named after the file
+fn export_schema(file_name: &str, export: DdlFormat) -> Result<String, PQRSError> {
+    let reader = SerializedFileReader::new(open_file(file_name)?)?;
+    let file_metadata = reader.metadata().file_metadata();
+    let columns = get_columns(file_metadata.schema());
+    let arrow_schema = || {
+        parquet_to_arrow_schema(
+            file_metadata.schema_descr(),
+            file_metadata.key_value_metadata(),
+        )
+    };
+
+    let schema: Value = match export {
+        DdlFormat::Spark | DdlFormat::Hive | DdlFormat::Postgres | DdlFormat::DuckDb => {
+            let table = Path::new(file_name)
+                .file_stem()
+                .map_or(file_name.into(), |stem| stem.to_string_lossy());
+            return Ok(create_table(&table, &columns, export));
+        }
+        DdlFormat::BigQuery => bigquery_schema(&columns),
+        DdlFormat::JsonSchema => json_schema(&columns),
+        DdlFormat::Avro => {
+            let avro_schema = get_avro_schema(file_name, &arrow_schema()?, true)?;
+            serde_json::to_value(&avro_schema).map_err(io::Error::from)?
+        }
+        DdlFormat::Arrow => arrow_schema()?.to_json(),
+    };
+    Ok(serde_json::to_string_pretty(&schema).map_err(io::Error::from)?)
+}
+
 impl<'a> fmt::Debug for SchemaCommand<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
@@ -89,6 +323,16 @@ impl<'a> fmt::Debug for SchemaCommand<'a> {
             &self.file_names.join(", ")
         )?;
         writeln!(f, "Print Detailed output: {}", &self.use_detailed)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        if let Some(export) = self.export {
+            writeln!(f, "Export the schema as: {:?}", export)?;
+        }
+        writeln!(
+            f,
+            "Check the schemas are consistent: {}",
+            self.check_consistent
+        )?;
 
         Ok(())
     }
@@ -0,0 +1,129 @@
+use crate::command::PQRSCommand;
+use crate::diff::{diff_schemas, Compatibility};
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{FileNotFound, SchemaMismatch};
+use crate::report::{print_report, print_table, MetadataFormat};
+use crate::utils::{check_path_present, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use parquet::file::reader::FileReader;
+use parquet::file::serialized_reader::SerializedFileReader;
+use std::fmt;
+
+pub struct SchemaDiffCommand<'a> {
+    old: &'a str,
+    new: &'a str,
+    compatibility: Compatibility,
+    format: MetadataFormat,
+}
+
+impl<'a> SchemaDiffCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("schema-diff")
+            .about("Prints the differences between the schemas of two Parquet files")
+            .arg(
+                Arg::with_name("old")
+                    .index(1)
+                    .value_name("OLD")
+                    .required(true)
+                    .help("Parquet file with the old schema"),
+            )
+            .arg(
+                Arg::with_name("new")
+                    .index(2)
+                    .value_name("NEW")
+                    .required(true)
+                    .help("Parquet file with the new schema"),
+            )
+            .arg(
+                Arg::with_name("compatibility")
+                    .long("compatibility")
+                    .short("c")
+                    .takes_value(true)
+                    .required(false)
+                    .possible_values(&["backward", "forward", "full"])
+                    .default_value("backward")
+                    .help("The compatibility policy used to decide which changes are breaking, the command fails when there are any"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            old: matches.value_of("old").unwrap(),
+            new: matches.value_of("new").unwrap(),
+            compatibility: match matches.value_of("compatibility") {
+                Some("forward") => Compatibility::Forward,
+                Some("full") => Compatibility::Full,
+                _ => Compatibility::Backward,
+            },
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for SchemaDiffCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        // make sure all files are present before printing any data
+        for file_name in &[self.old, self.new] {
+            if !check_path_present(*file_name) {
+                return Err(FileNotFound(String::from(*file_name)));
+            }
+        }
+
+        let old = SerializedFileReader::new(open_file(self.old)?)?;
+        let new = SerializedFileReader::new(open_file(self.new)?)?;
+        let changes = diff_schemas(
+            old.metadata().file_metadata().schema(),
+            new.metadata().file_metadata().schema(),
+            self.compatibility,
+        );
+
+        if self.format == MetadataFormat::Text {
+            if changes.is_empty() {
+                println!("The schemas are identical");
+            } else {
+                let rows: Vec<Vec<String>> = changes
+                    .iter()
+                    .map(|change| {
+                        let mut row = change.text_row();
+                        row.push(String::from(if change.breaking {
+                            "yes"
+                        } else {
+                            "no"
+                        }));
+                        row
+                    })
+                    .collect();
+                print_table(&["Change", "Path", "Old", "New", "Breaking"], &rows);
+            }
+        } else {
+            let entries: Vec<_> = changes.iter().map(|c| c.entry()).collect();
+            print_report(self.format, "changes", &entries, None)?;
+        }
+
+        // the exit code is used to gate schema changes, e.g. in continuous integration
+        let breaking = changes.iter().filter(|c| c.breaking).count();
+        if breaking > 0 {
+            return Err(SchemaMismatch(format!(
+                "{} breaking change(s) for the {:?} compatibility policy",
+                breaking, self.compatibility
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for SchemaDiffCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The old file name is: {}", self.old)?;
+        writeln!(f, "The new file name is: {}", self.new)?;
+        writeln!(f, "Compatibility policy: {:?}", self.compatibility)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
@@ -1,15 +1,26 @@
 use crate::command::PQRSCommand;
 use crate::errors::PQRSError;
 use crate::errors::PQRSError::FileNotFound;
-use crate::utils::{check_path_present, get_pretty_size, get_size, open_file};
+use crate::report::Entry;
+use crate::report::{print_report, print_table, MetadataFormat};
+use crate::units::{format_size, UnitSystem};
+use crate::utils::{
+    check_path_present, get_file_summary, get_footer_size, get_size, get_size_breakdown,
+    get_total_entry, open_file, PartSize,
+};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use log::debug;
+use serde_json::json;
 use std::fmt;
 
 pub struct SizeCommand<'a> {
     file_names: Vec<&'a str>,
     compressed: bool,
     pretty: bool,
+    units: UnitSystem,
+    by_column: bool,
+    by_row_group: bool,
+    format: MetadataFormat,
 }
 
 impl<'a> SizeCommand<'a> {
@@ -41,6 +52,27 @@ impl<'a> SizeCommand<'a> {
                     .required(false)
                     .help("Show pretty, human readable size"),
             )
+            .arg(
+                Arg::with_name("si")
+                    .long("si")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Use powers of 1000 (kB, MB) for pretty sizes instead of 1024 (KiB, MiB)"),
+            )
+            .arg(
+                Arg::with_name("by-column")
+                    .long("by-column")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Show the size of every column, biggest first"),
+            )
+            .arg(
+                Arg::with_name("by-row-group")
+                    .long("by-row-group")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Show the size of every row group, biggest first"),
+            )
     }
 
     pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
@@ -48,6 +80,14 @@ impl<'a> SizeCommand<'a> {
             file_names: matches.values_of("files").unwrap().collect(),
             compressed: matches.is_present("compressed"),
             pretty: matches.is_present("pretty"),
+            units: if matches.is_present("si") {
+                UnitSystem::Si
+            } else {
+                UnitSystem::Iec
+            },
+            by_column: matches.is_present("by-column"),
+            by_row_group: matches.is_present("by-row-group"),
+            format: MetadataFormat::new(matches),
         }
     }
 }
@@ -64,39 +104,173 @@ impl<'a> PQRSCommand for SizeCommand<'a> {
             }
         }
 
+        if self.by_column || self.by_row_group {
+            return self.print_breakdown();
+        }
+
+        if self.format != MetadataFormat::Text {
+            let mut summaries = Vec::new();
+            for file_name in &self.file_names {
+                summaries.push(get_file_summary(file_name)?);
+            }
+            let entries: Vec<_> = summaries.iter().map(|s| s.entry()).collect();
+            let total = get_total_entry(&summaries);
+            return print_report(self.format, "files", &entries, Some(total));
+        }
+
         println!("Size in Bytes:");
+        let mut total_size = (0, 0);
         for file_name in &self.file_names {
-            let file = open_file(*file_name)?;
+            let mut file = open_file(*file_name)?;
+            let footer_size = get_footer_size(&mut file)?;
             let size_info = get_size(file)?;
+            total_size = (total_size.0 + size_info.0, total_size.1 + size_info.1);
 
             println!();
             println!("File Name: {}", &file_name);
 
             if !self.compressed {
-                if self.pretty {
-                    println!("Uncompressed Size: {}", get_pretty_size(size_info.0));
-                } else {
-                    println!("Uncompressed Size: {}", size_info.0);
-                }
+                println!("Uncompressed Size: {}", self.size(size_info.0));
             } else {
-                if self.pretty {
-                    println!("Compressed Size: {}", get_pretty_size(size_info.1));
-                } else {
-                    println!("Compressed Size: {}", size_info.1);
-                }
+                println!("Compressed Size: {}", self.size(size_info.1));
             }
+            println!("Footer Size: {}", self.size(footer_size as i64));
+        }
+
+        if self.file_names.len() > 1 {
+            let total = if self.compressed {
+                total_size.1
+            } else {
+                total_size.0
+            };
+            println!();
+            println!("Total Size: {}", self.size(total));
         }
 
         Ok(())
     }
 }
 
+impl<'a> SizeCommand<'a> {
+    /// Print the sizes of the column chunks summed by column and/or row group,
+    /// when both are requested every column chunk is printed on its own
+    fn print_breakdown(&self) -> Result<(), PQRSError> {
+        let mut entries = Vec::new();
+        let mut total = (0, 0);
+        for file_name in &self.file_names {
+            let parts = get_size_breakdown(file_name, self.by_column, self.by_row_group)?;
+            for part in &parts {
+                total = (
+                    total.0 + part.compressed_bytes,
+                    total.1 + part.uncompressed_bytes,
+                );
+            }
+
+            if self.format == MetadataFormat::Text {
+                println!("File Name: {}", &file_name);
+                self.print_table(&parts);
+                println!();
+            } else {
+                entries.extend(parts.iter().map(|part| part_entry(file_name, part)));
+            }
+        }
+
+        if self.format == MetadataFormat::Text {
+            println!("Total Compressed Size: {}", self.size(total.0));
+            println!("Total Uncompressed Size: {}", self.size(total.1));
+            return Ok(());
+        }
+
+        let total: Entry = vec![
+            ("compressed_bytes", json!(total.0)),
+            ("uncompressed_bytes", json!(total.1)),
+        ];
+        print_report(self.format, "parts", &entries, Some(total))
+    }
+
+    fn print_table(&self, parts: &[PartSize]) {
+        let mut header = Vec::new();
+        if self.by_row_group {
+            header.push("Row Group");
+        }
+        if self.by_column {
+            header.push("Column");
+        }
+        header.extend(&["Compressed", "Uncompressed", "Ratio", "Codec", "Encodings"]);
+
+        let rows: Vec<Vec<String>> = parts
+            .iter()
+            .map(|part| {
+                let mut row = Vec::new();
+                if part.footer {
+                    // the footer does not belong to any column or row group
+                    row.push(String::from("footer"));
+                    if self.by_row_group && self.by_column {
+                        row.push(String::new());
+                    }
+                } else {
+                    if let Some(row_group) = part.row_group {
+                        row.push(row_group.to_string());
+                    }
+                    if let Some(column) = &part.column {
+                        row.push(column.clone());
+                    }
+                }
+                row.push(self.size(part.compressed_bytes));
+                row.push(self.size(part.uncompressed_bytes));
+                row.push(format!("{:.2}", part.compression_ratio()));
+                row.push(part.codecs());
+                row.push(part.encodings());
+                row
+            })
+            .collect();
+
+        print_table(&header, &rows);
+    }
+
+    /// Format the size in bytes, using units when pretty printing
+    fn size(&self, bytes: i64) -> String {
+        if self.pretty {
+            format_size(bytes, self.units, 3)
+        } else {
+            bytes.to_string()
+        }
+    }
+}
+
+fn part_entry(file_name: &str, part: &PartSize) -> Entry {
+    vec![
+        ("path", json!(file_name)),
+        (
+            "part",
+            json!(if part.footer {
+                "footer"
+            } else {
+                "column chunks"
+            }),
+        ),
+        ("row_group", json!(part.row_group)),
+        ("column", json!(part.column)),
+        ("compressed_bytes", json!(part.compressed_bytes)),
+        ("uncompressed_bytes", json!(part.uncompressed_bytes)),
+        ("compression_ratio", json!(part.compression_ratio())),
+        ("codec", json!(part.codecs())),
+        ("encodings", json!(part.encodings())),
+    ]
+}
+
 impl<'a> fmt::Debug for SizeCommand<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
             "The file names to read are: {}",
             &self.file_names.join(", ")
-        )
+        )?;
+        writeln!(f, "Units: {:?}", self.units)?;
+        writeln!(f, "Size by column: {}", self.by_column)?;
+        writeln!(f, "Size by row group: {}", self.by_row_group)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
     }
 }
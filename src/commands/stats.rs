@@ -0,0 +1,161 @@
+use crate::command::PQRSCommand;
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::FileNotFound;
+use crate::report::{print_report, print_table, Entry, MetadataFormat};
+use crate::stats::{get_column_stats, ColumnSummary, StatValue};
+use crate::utils::{check_path_present, get_column_type, open_file};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use log::debug;
+use serde_json::json;
+use std::fmt;
+
+pub struct StatsCommand<'a> {
+    file_names: Vec<&'a str>,
+    compute: bool,
+    format: MetadataFormat,
+}
+
+impl<'a> StatsCommand<'a> {
+    pub(crate) fn command() -> App<'static, 'static> {
+        SubCommand::with_name("stats")
+            .about("Prints the column statistics of Parquet file(s)")
+            .arg(
+                Arg::with_name("files")
+                    .index(1)
+                    .multiple(true)
+                    .value_name("FILES")
+                    .value_delimiter(" ")
+                    .required(true)
+                    .help("Parquet files to read"),
+            )
+            .arg(
+                Arg::with_name("compute")
+                    .long("compute")
+                    .takes_value(false)
+                    .required(false)
+                    .help("Read the data to compute the statistics missing from the metadata"),
+            )
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        Self {
+            file_names: matches.values_of("files").unwrap().collect(),
+            compute: matches.is_present("compute"),
+            format: MetadataFormat::new(matches),
+        }
+    }
+}
+
+impl<'a> PQRSCommand for StatsCommand<'a> {
+    fn execute(&self) -> Result<(), PQRSError> {
+        // print debugging information
+        debug!("{:#?}", self);
+
+        // make sure all files are present before printing any data
+        for file_name in &self.file_names {
+            if !check_path_present(*file_name) {
+                return Err(FileNotFound(String::from(*file_name)));
+            }
+        }
+
+        let mut entries = Vec::new();
+        for file_name in &self.file_names {
+            let file = open_file(file_name)?;
+            let summaries = get_column_stats(file, self.compute)?;
+
+            if self.format == MetadataFormat::Text {
+                println!("File Name: {}", file_name);
+                let rows: Vec<Vec<String>> = summaries.iter().map(text_row).collect();
+                print_table(
+                    &[
+                        "Column",
+                        "Type",
+                        "Min",
+                        "Max",
+                        "Nulls",
+                        "Distinct",
+                        "Row Groups",
+                        "Source",
+                    ],
+                    &rows,
+                );
+                println!();
+            } else {
+                entries.extend(summaries.iter().map(|s| entry(file_name, s)));
+            }
+        }
+
+        if self.format != MetadataFormat::Text {
+            return print_report(self.format, "columns", &entries, None);
+        }
+
+        Ok(())
+    }
+}
+
+/// Describe where the statistics of the column come from
+fn source(summary: &ColumnSummary) -> &'static str {
+    if summary.computed {
+        "computed"
+    } else if summary.stats.complete {
+        "metadata"
+    } else {
+        "incomplete"
+    }
+}
+
+fn text_row(summary: &ColumnSummary) -> Vec<String> {
+    let stats = &summary.stats;
+    let display = |value: &Option<StatValue>| match value {
+        Some(value) => value.display(&summary.column),
+        None => String::from("N/A"),
+    };
+    let count = |count: Option<u64>| count.map_or(String::from("N/A"), |c| c.to_string());
+
+    vec![
+        summary.column.path().string(),
+        get_column_type(&summary.column),
+        display(&stats.min),
+        display(&stats.max),
+        count(stats.null_count),
+        count(stats.distinct_count),
+        format!("{}/{}", stats.row_groups_with_statistics, stats.row_groups),
+        String::from(source(summary)),
+    ]
+}
+
+fn entry(file_name: &str, summary: &ColumnSummary) -> Entry {
+    let stats = &summary.stats;
+    let display =
+        |value: &Option<StatValue>| value.as_ref().map(|v| v.display(&summary.column));
+
+    vec![
+        ("path", json!(file_name)),
+        ("column", json!(summary.column.path().string())),
+        ("type", json!(get_column_type(&summary.column))),
+        ("min", json!(display(&stats.min))),
+        ("max", json!(display(&stats.max))),
+        ("null_count", json!(stats.null_count)),
+        ("distinct_count", json!(stats.distinct_count)),
+        ("row_groups", json!(stats.row_groups)),
+        (
+            "row_groups_with_statistics",
+            json!(stats.row_groups_with_statistics),
+        ),
+        ("source", json!(source(summary))),
+    ]
+}
+
+impl<'a> fmt::Debug for StatsCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "The file names to read are: {}",
+            self.file_names.join(", ")
+        )?;
+        writeln!(f, "Compute missing statistics: {}", self.compute)?;
+        writeln!(f, "Output format: {:?}", self.format)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,628 @@
+use log::warn;
+use parquet::basic::{ConvertedType, LogicalType, Repetition, Type as PhysicalType};
+use parquet::schema::types::Type;
+use serde_json::{json, Map, Value};
+
+/// The systems the schema of a parquet file can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DdlFormat {
+    Spark,
+    Hive,
+    BigQuery,
+    Postgres,
+    DuckDb,
+    Avro,
+    JsonSchema,
+    Arrow,
+}
+
+/// The type of a column, independent of the system the schema is exported to
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnType {
+    Boolean,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float,
+    Double,
+    String,
+    Enum,
+    Json,
+    Uuid,
+    Binary,
+    Fixed(i32),
+    Date,
+    /// The time of day, in milliseconds, microseconds or nanoseconds
+    Time,
+    /// Timestamps with `utc` set are instants, the others are local date times
+    Timestamp {
+        utc: bool,
+    },
+    Decimal {
+        precision: i32,
+        scale: i32,
+    },
+    Interval,
+    List(Box<Column>),
+    Map(Box<Column>, Box<Column>),
+    Struct(Vec<Column>),
+}
+
+/// A column of the schema, nested columns are the fields of structs, lists and maps
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+/// Return the columns of the parquet schema, following the conventions used to store
+/// lists and maps, including the legacy two-level lists
+pub fn get_columns(schema: &Type) -> Vec<Column> {
+    schema.get_fields().iter().map(|f| to_column(f)).collect()
+}
+
+fn to_column(field: &Type) -> Column {
+    let info = field.get_basic_info();
+    let repetition = if info.has_repetition() {
+        info.repetition()
+    } else {
+        Repetition::REQUIRED
+    };
+
+    // repeated fields that are not annotated are lists of required elements
+    if repetition == Repetition::REPEATED {
+        let element = Column {
+            nullable: false,
+            ..to_column_type(field, info.name())
+        };
+        return Column {
+            name: info.name().to_string(),
+            column_type: ColumnType::List(Box::new(element)),
+            nullable: false,
+        };
+    }
+
+    Column {
+        nullable: repetition == Repetition::OPTIONAL,
+        ..to_column_type(field, info.name())
+    }
+}
+
+/// Map the type of the field, ignoring its repetition
+fn to_column_type(field: &Type, name: &str) -> Column {
+    let info = field.get_basic_info();
+    let column_type = if field.is_group() {
+        match info.converted_type() {
+            ConvertedType::LIST => {
+                list_element(field).map(|e| ColumnType::List(Box::new(e)))
+            }
+            ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE => map_entry(field),
+            _ => None,
+        }
+        .unwrap_or_else(|| ColumnType::Struct(get_columns(field)))
+    } else {
+        primitive_type(field)
+    };
+
+    Column {
+        name: name.to_string(),
+        column_type,
+        nullable: true,
+    }
+}
+
+/// The element of a list, the repeated group is the element for legacy lists
+fn list_element(list: &Type) -> Option<Column> {
+    let repeated = list.get_fields().first()?;
+    let element = if repeated.is_group()
+        && repeated.get_fields().len() == 1
+        && repeated.name() != "array"
+        && !repeated.name().ends_with("_tuple")
+    {
+        to_column(&repeated.get_fields()[0])
+    } else {
+        Column {
+            nullable: false,
+            ..to_column_type(repeated, repeated.name())
+        }
+    };
+    Some(element)
+}
+
+fn map_entry(map: &Type) -> Option<ColumnType> {
+    let entry = map.get_fields().first()?;
+    match entry.get_fields() {
+        [key, value] => Some(ColumnType::Map(
+            Box::new(to_column(key)),
+            Box::new(to_column(value)),
+        )),
+        _ => None,
+    }
+}
+
+fn primitive_type(field: &Type) -> ColumnType {
+    let info = field.get_basic_info();
+    match info.logical_type() {
+        Some(LogicalType::UUID(_)) => return ColumnType::Uuid,
+        Some(LogicalType::TIMESTAMP(timestamp)) => {
+            return ColumnType::Timestamp {
+                utc: timestamp.is_adjusted_to_u_t_c,
+            }
+        }
+        Some(LogicalType::TIME(_)) => return ColumnType::Time,
+        _ => {}
+    }
+
+    match info.converted_type() {
+        ConvertedType::UTF8 => ColumnType::String,
+        ConvertedType::ENUM => ColumnType::Enum,
+        ConvertedType::JSON => ColumnType::Json,
+        ConvertedType::BSON => ColumnType::Binary,
+        ConvertedType::DECIMAL => ColumnType::Decimal {
+            precision: field.get_precision(),
+            scale: field.get_scale(),
+        },
+        ConvertedType::DATE => ColumnType::Date,
+        ConvertedType::TIME_MILLIS | ConvertedType::TIME_MICROS => ColumnType::Time,
+        // the converted types are only used for timestamps that are adjusted to utc
+        ConvertedType::TIMESTAMP_MILLIS | ConvertedType::TIMESTAMP_MICROS => {
+            ColumnType::Timestamp { utc: true }
+        }
+        ConvertedType::INT_8 => ColumnType::Int8,
+        ConvertedType::INT_16 => ColumnType::Int16,
+        ConvertedType::INT_32 => ColumnType::Int32,
+        ConvertedType::INT_64 => ColumnType::Int64,
+        ConvertedType::UINT_8 => ColumnType::UInt8,
+        ConvertedType::UINT_16 => ColumnType::UInt16,
+        ConvertedType::UINT_32 => ColumnType::UInt32,
+        ConvertedType::UINT_64 => ColumnType::UInt64,
+        ConvertedType::INTERVAL => ColumnType::Interval,
+        _ => match field.get_physical_type() {
+            PhysicalType::BOOLEAN => ColumnType::Boolean,
+            PhysicalType::INT32 => ColumnType::Int32,
+            PhysicalType::INT64 => ColumnType::Int64,
+            // int96 is the legacy representation of timestamps
+            PhysicalType::INT96 => ColumnType::Timestamp { utc: true },
+            PhysicalType::FLOAT => ColumnType::Float,
+            PhysicalType::DOUBLE => ColumnType::Double,
+            PhysicalType::BYTE_ARRAY => ColumnType::Binary,
+            PhysicalType::FIXED_LEN_BYTE_ARRAY => match field {
+                Type::PrimitiveType { type_length, .. } => {
+                    ColumnType::Fixed(*type_length)
+                }
+                _ => ColumnType::Binary,
+            },
+        },
+    }
+}
+
+/// Warn that the column cannot be represented in the target system and return the
+/// type used instead
+fn degrade(
+    path: &str,
+    column_type: &ColumnType,
+    format: DdlFormat,
+    fallback: &str,
+) -> String {
+    warn!(
+        "The column {} of type {} cannot be mapped to {:?}, {} is used instead",
+        path,
+        type_name(column_type),
+        format,
+        fallback
+    );
+    fallback.to_string()
+}
+
+fn type_name(column_type: &ColumnType) -> String {
+    match column_type {
+        ColumnType::List(_) => String::from("LIST"),
+        ColumnType::Map(..) => String::from("MAP"),
+        ColumnType::Struct(_) => String::from("STRUCT"),
+        ColumnType::Decimal { precision, scale } => {
+            format!("DECIMAL({}, {})", precision, scale)
+        }
+        column_type => format!("{:?}", column_type).to_uppercase(),
+    }
+}
+
+/// Return the `CREATE TABLE` statement for the SQL systems
+pub fn create_table(table: &str, columns: &[Column], format: DdlFormat) -> String {
+    let definitions: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            let sql_type = sql_type(&column.column_type, &column.name, format);
+            let not_null = match format {
+                DdlFormat::Postgres | DdlFormat::DuckDb if !column.nullable => {
+                    " NOT NULL"
+                }
+                DdlFormat::Spark if !column.nullable => " NOT NULL",
+                _ => "",
+            };
+            format!("  {} {}{}", quote(&column.name, format), sql_type, not_null)
+        })
+        .collect();
+
+    let suffix = match format {
+        DdlFormat::Spark => " USING PARQUET",
+        DdlFormat::Hive => " STORED AS PARQUET",
+        _ => "",
+    };
+    let create = match format {
+        DdlFormat::Hive => "CREATE EXTERNAL TABLE",
+        _ => "CREATE TABLE",
+    };
+    format!(
+        "{} {} (\n{}\n){};",
+        create,
+        quote(table, format),
+        definitions.join(",\n"),
+        suffix
+    )
+}
+
+fn quote(identifier: &str, format: DdlFormat) -> String {
+    match format {
+        DdlFormat::Spark | DdlFormat::Hive => {
+            format!("`{}`", identifier.replace('`', "``"))
+        }
+        _ => format!("\"{}\"", identifier.replace('"', "\"\"")),
+    }
+}
+
+fn sql_type(column_type: &ColumnType, path: &str, format: DdlFormat) -> String {
+    use ColumnType::*;
+    use DdlFormat::*;
+
+    let name = match (column_type, format) {
+        (Boolean, Postgres) => "boolean",
+        (Boolean, _) => "BOOLEAN",
+        (Int8, Postgres) | (Int16, Postgres) | (UInt8, Postgres) => "smallint",
+        (Int32, Postgres) | (UInt16, Postgres) => "integer",
+        (Int64, Postgres) | (UInt32, Postgres) => "bigint",
+        (UInt64, Postgres) => "numeric(20, 0)",
+        (UInt8, DuckDb) => "UTINYINT",
+        (UInt16, DuckDb) => "USMALLINT",
+        (UInt32, DuckDb) => "UINTEGER",
+        (UInt64, DuckDb) => "UBIGINT",
+        (Int8, _) => "TINYINT",
+        (Int16, _) | (UInt8, _) => "SMALLINT",
+        (Int32, DuckDb) => "INTEGER",
+        (Int32, _) | (UInt16, _) => "INT",
+        (Int64, _) | (UInt32, _) => "BIGINT",
+        (UInt64, _) => "DECIMAL(20, 0)",
+        (Float, Postgres) => "real",
+        (Float, _) => "FLOAT",
+        (Double, Postgres) => "double precision",
+        (Double, _) => "DOUBLE",
+        (String, Postgres) | (Enum, Postgres) => "text",
+        (String, DuckDb) | (Enum, DuckDb) => "VARCHAR",
+        (Json, Postgres) => "jsonb",
+        (Json, DuckDb) => "JSON",
+        (Uuid, Postgres) => "uuid",
+        (Uuid, DuckDb) => "UUID",
+        (String, _) | (Enum, _) | (Json, _) | (Uuid, _) => "STRING",
+        (Binary, Postgres) | (Fixed(_), Postgres) => "bytea",
+        (Binary, DuckDb) | (Fixed(_), DuckDb) => "BLOB",
+        (Binary, _) | (Fixed(_), _) => "BINARY",
+        (Date, Postgres) => "date",
+        (Date, _) => "DATE",
+        (Time, Postgres) => "time",
+        (Time, DuckDb) => "TIME",
+        (Time, _) => return degrade(path, column_type, format, "BIGINT"),
+        (Timestamp { utc: true }, Postgres) => "timestamptz",
+        (Timestamp { utc: false }, Postgres) => "timestamp",
+        (Timestamp { utc: true }, DuckDb) => "TIMESTAMPTZ",
+        (Timestamp { .. }, _) => "TIMESTAMP",
+        (Decimal { precision, scale }, Postgres) => {
+            return format!("numeric({}, {})", precision, scale)
+        }
+        (Decimal { precision, .. }, _) if *precision > 38 => {
+            return degrade(path, column_type, format, "DOUBLE")
+        }
+        (Decimal { precision, scale }, _) => {
+            return format!("DECIMAL({}, {})", precision, scale)
+        }
+        (Interval, Postgres) => "interval",
+        (Interval, DuckDb) => "INTERVAL",
+        (Interval, _) => return degrade(path, column_type, format, "BINARY"),
+        (List(element), Postgres) => {
+            return format!("{}[]", sql_type(&element.column_type, path, format))
+        }
+        (List(element), DuckDb) => {
+            return format!("{}[]", sql_type(&element.column_type, path, format))
+        }
+        (List(element), _) => {
+            return format!("ARRAY<{}>", sql_type(&element.column_type, path, format))
+        }
+        (Map(..), Postgres) | (Struct(_), Postgres) => {
+            return degrade(path, column_type, format, "jsonb")
+        }
+        (Map(key, value), DuckDb) => {
+            return format!(
+                "MAP({}, {})",
+                sql_type(&key.column_type, path, format),
+                sql_type(&value.column_type, path, format)
+            )
+        }
+        (Map(key, value), _) => {
+            return format!(
+                "MAP<{}, {}>",
+                sql_type(&key.column_type, path, format),
+                sql_type(&value.column_type, path, format)
+            )
+        }
+        (Struct(fields), _) => {
+            let fields: Vec<std::string::String> = fields
+                .iter()
+                .map(|field| {
+                    let path = format!("{}.{}", path, field.name);
+                    let field_type = sql_type(&field.column_type, &path, format);
+                    match format {
+                        DuckDb => {
+                            format!("{} {}", quote(&field.name, format), field_type)
+                        }
+                        Hive => format!("{}:{}", field.name, field_type),
+                        _ => format!("{}: {}", quote(&field.name, format), field_type),
+                    }
+                })
+                .collect();
+            return match format {
+                DuckDb => format!("STRUCT({})", fields.join(", ")),
+                _ => format!("STRUCT<{}>", fields.join(", ")),
+            };
+        }
+    };
+
+    name.to_string()
+}
+
+/// Return the BigQuery schema, as used by `bq mk --schema` or the load jobs
+pub fn bigquery_schema(columns: &[Column]) -> Value {
+    Value::Array(
+        columns
+            .iter()
+            .map(|column| bigquery_field(column, &column.name))
+            .collect(),
+    )
+}
+
+fn bigquery_field(column: &Column, path: &str) -> Value {
+    let mode = if column.nullable {
+        "NULLABLE"
+    } else {
+        "REQUIRED"
+    };
+    let (bigquery_type, mode, fields) = match &column.column_type {
+        // lists are repeated fields of their element
+        ColumnType::List(element) => {
+            let (element_type, fields) = bigquery_type(element, path);
+            (element_type, "REPEATED", fields)
+        }
+        ColumnType::Map(key, value) => (
+            "RECORD",
+            "REPEATED",
+            Some(vec![
+                bigquery_field(key, &format!("{}.key", path)),
+                bigquery_field(value, &format!("{}.value", path)),
+            ]),
+        ),
+        _ => {
+            let (column_type, fields) = bigquery_type(column, path);
+            (column_type, mode, fields)
+        }
+    };
+
+    let mut field = Map::new();
+    field.insert(String::from("name"), json!(column.name));
+    field.insert(String::from("type"), json!(bigquery_type));
+    field.insert(String::from("mode"), json!(mode));
+    if let Some(fields) = fields {
+        field.insert(String::from("fields"), Value::Array(fields));
+    }
+    if let ColumnType::Decimal { precision, scale } = &column.column_type {
+        field.insert(String::from("precision"), json!(precision));
+        field.insert(String::from("scale"), json!(scale));
+    }
+    Value::Object(field)
+}
+
+fn bigquery_type(column: &Column, path: &str) -> (&'static str, Option<Vec<Value>>) {
+    let bigquery_type = match &column.column_type {
+        ColumnType::Boolean => "BOOL",
+        ColumnType::Int8
+        | ColumnType::Int16
+        | ColumnType::Int32
+        | ColumnType::Int64
+        | ColumnType::UInt8
+        | ColumnType::UInt16
+        | ColumnType::UInt32 => "INT64",
+        ColumnType::UInt64 => "NUMERIC",
+        ColumnType::Float | ColumnType::Double => "FLOAT64",
+        ColumnType::String | ColumnType::Enum | ColumnType::Uuid => "STRING",
+        ColumnType::Json => "JSON",
+        ColumnType::Binary | ColumnType::Fixed(_) => "BYTES",
+        ColumnType::Date => "DATE",
+        ColumnType::Time => "TIME",
+        ColumnType::Timestamp { utc: true } => "TIMESTAMP",
+        ColumnType::Timestamp { utc: false } => "DATETIME",
+        ColumnType::Decimal { precision, scale } if *precision <= 38 && *scale <= 9 => {
+            "NUMERIC"
+        }
+        ColumnType::Decimal { precision, scale } if *precision <= 76 && *scale <= 38 => {
+            "BIGNUMERIC"
+        }
+        ColumnType::Decimal { .. } => {
+            degrade(path, &column.column_type, DdlFormat::BigQuery, "FLOAT64");
+            "FLOAT64"
+        }
+        ColumnType::Interval => {
+            degrade(path, &column.column_type, DdlFormat::BigQuery, "BYTES");
+            "BYTES"
+        }
+        ColumnType::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| bigquery_field(field, &format!("{}.{}", path, field.name)))
+                .collect();
+            return ("RECORD", Some(fields));
+        }
+        // repeated fields cannot be repeated directly, the lists and maps nested in
+        // lists are wrapped in a record
+        ColumnType::List(_) | ColumnType::Map(..) => {
+            return ("RECORD", Some(vec![bigquery_field(column, path)]));
+        }
+    };
+    (bigquery_type, None)
+}
+
+/// Return a JSON schema describing the records of the file, as printed by `cat --json`
+pub fn json_schema(columns: &[Column]) -> Value {
+    let mut schema = json_object_schema(columns);
+    if let Value::Object(fields) = &mut schema {
+        let mut document = Map::new();
+        document.insert(
+            String::from("$schema"),
+            json!("http://json-schema.org/draft-07/schema#"),
+        );
+        document.append(fields);
+        return Value::Object(document);
+    }
+    schema
+}
+
+fn json_object_schema(columns: &[Column]) -> Value {
+    let properties: Map<String, Value> = columns
+        .iter()
+        .map(|column| (column.name.clone(), json_column_schema(column)))
+        .collect();
+    let required: Vec<&str> = columns
+        .iter()
+        .filter(|column| !column.nullable)
+        .map(|column| column.name.as_str())
+        .collect();
+
+    json!({"type": "object", "properties": properties, "required": required})
+}
+
+fn json_column_schema(column: &Column) -> Value {
+    let mut schema = match &column.column_type {
+        ColumnType::Boolean => json!({"type": "boolean"}),
+        ColumnType::Int8
+        | ColumnType::Int16
+        | ColumnType::Int32
+        | ColumnType::Int64
+        | ColumnType::UInt8
+        | ColumnType::UInt16
+        | ColumnType::UInt32
+        | ColumnType::UInt64 => json!({"type": "integer"}),
+        ColumnType::Float | ColumnType::Double | ColumnType::Decimal { .. } => {
+            json!({"type": "number"})
+        }
+        ColumnType::String | ColumnType::Enum | ColumnType::Json => {
+            json!({"type": "string"})
+        }
+        ColumnType::Uuid => json!({"type": "string", "format": "uuid"}),
+        ColumnType::Date => json!({"type": "string", "format": "date"}),
+        ColumnType::Time => json!({"type": "string", "format": "time"}),
+        ColumnType::Timestamp { .. } => json!({"type": "string", "format": "date-time"}),
+        ColumnType::Binary | ColumnType::Fixed(_) | ColumnType::Interval => {
+            json!({"type": "string", "contentEncoding": "base64"})
+        }
+        ColumnType::List(element) => {
+            json!({"type": "array", "items": json_column_schema(element)})
+        }
+        ColumnType::Map(_, value) => {
+            json!({"type": "object", "additionalProperties": json_column_schema(value)})
+        }
+        ColumnType::Struct(fields) => json_object_schema(fields),
+    };
+
+    if column.nullable {
+        if let Some(Value::String(json_type)) = schema.get("type").cloned() {
+            schema["type"] = json!([json_type, "null"]);
+        }
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::schema::parser::parse_message_type;
+
+    fn columns(message_type: &str) -> Vec<Column> {
+        get_columns(&parse_message_type(message_type).unwrap())
+    }
+
+    #[test]
+    fn it_reads_lists_and_maps() {
+        let columns = columns(
+            "message schema {
+                OPTIONAL group tags (LIST) {
+                    REPEATED group list {
+                        OPTIONAL BYTE_ARRAY element (UTF8);
+                    }
+                }
+                REQUIRED group counts (MAP) {
+                    REPEATED group key_value {
+                        REQUIRED BYTE_ARRAY key (UTF8);
+                        OPTIONAL INT32 value;
+                    }
+                }
+                REPEATED INT64 legacy;
+            }",
+        );
+
+        let element = Column {
+            name: String::from("element"),
+            column_type: ColumnType::String,
+            nullable: true,
+        };
+        assert_eq!(columns[0].column_type, ColumnType::List(Box::new(element)));
+        assert!(columns[0].nullable);
+        match &columns[1].column_type {
+            ColumnType::Map(key, value) => {
+                assert_eq!(key.column_type, ColumnType::String);
+                assert_eq!(value.column_type, ColumnType::Int32);
+            }
+            column_type => panic!("Unexpected type {:?}", column_type),
+        }
+        match &columns[2].column_type {
+            ColumnType::List(element) => {
+                assert_eq!(element.column_type, ColumnType::Int64)
+            }
+            column_type => panic!("Unexpected type {:?}", column_type),
+        }
+    }
+
+    #[test]
+    fn it_creates_tables() {
+        let columns = columns(
+            "message schema {
+                REQUIRED INT32 id;
+                OPTIONAL FIXED_LEN_BYTE_ARRAY (16) price (DECIMAL(30, 2));
+                OPTIONAL group address {
+                    OPTIONAL BYTE_ARRAY city (UTF8);
+                }
+            }",
+        );
+
+        assert_eq!(
+            create_table("t", &columns, DdlFormat::Spark),
+            "CREATE TABLE `t` (\n  `id` INT NOT NULL,\n  `price` DECIMAL(30, 2),\n  \
+             `address` STRUCT<`city`: STRING>\n) USING PARQUET;"
+        );
+        assert_eq!(
+            create_table("t", &columns, DdlFormat::DuckDb),
+            "CREATE TABLE \"t\" (\n  \"id\" INTEGER NOT NULL,\n  \"price\" DECIMAL(30, 2),\n  \
+             \"address\" STRUCT(\"city\" VARCHAR)\n);"
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::errors::PQRSError;
+use crate::stats::{decimal_from_bytes, StatValue};
+use parquet::basic::{ConvertedType, Encoding, Type as PhysicalType};
+use parquet::column::page::{Page, PageReader};
+use parquet::errors::ParquetError;
+use parquet::schema::types::ColumnDescriptor;
+use std::convert::TryInto;
+
+/// The dictionary of a column chunk, along with the number of times each of its
+/// entries is used by the data pages
+#[derive(Debug, Default)]
+pub struct ChunkDictionary {
+    /// The decoded entries of the dictionary, formatted with the logical type
+    pub values: Vec<String>,
+    pub counts: Vec<u64>,
+    pub is_sorted: bool,
+    /// The values of the data pages that are not dictionary encoded, e.g. because the
+    /// writer fell back to plain encoding when the dictionary grew too large
+    pub fallback_values: u64,
+    pub nulls: u64,
+}
+
+/// Read the pages of a column chunk, decoding its dictionary page and counting the
+/// indices used by the dictionary encoded data pages. Returns None when the chunk does
+/// not have a dictionary.
+pub fn read_dictionary(
+    pages: &mut dyn PageReader,
+    column: &ColumnDescriptor,
+) -> Result<Option<ChunkDictionary>, PQRSError> {
+    let truncated = || {
+        ParquetError::EOF(format!(
+            "A data page of column {} is truncated",
+            column.path().string()
+        ))
+    };
+    let mut dictionary: Option<ChunkDictionary> = None;
+
+    while let Some(page) = pages.get_next_page()? {
+        let (data, non_null, encoding) = match &page {
+            Page::DictionaryPage {
+                buf,
+                num_values,
+                is_sorted,
+                ..
+            } => {
+                let values = decode_plain(buf.data(), *num_values as usize, column)?;
+                dictionary = Some(ChunkDictionary {
+                    counts: vec![0; values.len()],
+                    values: values.iter().map(|v| v.display(column)).collect(),
+                    is_sorted: *is_sorted,
+                    ..ChunkDictionary::default()
+                });
+                continue;
+            }
+            Page::DataPage {
+                buf,
+                num_values,
+                encoding,
+                def_level_encoding,
+                rep_level_encoding,
+                ..
+            } => {
+                let num_values = *num_values as usize;
+                let data = buf.data();
+                let rep_levels = read_levels(
+                    data,
+                    *rep_level_encoding,
+                    column.max_rep_level(),
+                    num_values,
+                )?;
+                let def_levels = read_levels(
+                    data.get(rep_levels.size..).ok_or_else(truncated)?,
+                    *def_level_encoding,
+                    column.max_def_level(),
+                    num_values,
+                )?;
+                let non_null = match def_levels.levels {
+                    Some(levels) => levels
+                        .iter()
+                        .filter(|l| **l == column.max_def_level() as u32)
+                        .count(),
+                    None => num_values,
+                };
+                let start = rep_levels.size + def_levels.size;
+                let values = data.get(start..).ok_or_else(truncated)?;
+                (values, non_null, *encoding)
+            }
+            Page::DataPageV2 {
+                buf,
+                num_values,
+                encoding,
+                num_nulls,
+                def_levels_byte_len,
+                rep_levels_byte_len,
+                ..
+            } => {
+                // the levels of v2 pages are stored before the values, with their lengths
+                // in the header
+                let start = (*rep_levels_byte_len + *def_levels_byte_len) as usize;
+                let non_null = (*num_values - *num_nulls) as usize;
+                let values = buf.data().get(start..).ok_or_else(truncated)?;
+                (values, non_null, *encoding)
+            }
+        };
+
+        let dictionary = match dictionary.as_mut() {
+            Some(dictionary) => dictionary,
+            None => return Ok(None),
+        };
+        dictionary.nulls += (page.num_values() as usize - non_null) as u64;
+
+        match encoding {
+            Encoding::PLAIN_DICTIONARY | Encoding::RLE_DICTIONARY if !data.is_empty() => {
+                let indices = decode_hybrid(&data[1..], data[0], non_null)?;
+                for index in indices {
+                    let count =
+                        dictionary.counts.get_mut(index as usize).ok_or_else(|| {
+                            ParquetError::General(format!(
+                                "Dictionary index {} out of range for column {}",
+                                index,
+                                column.path().string()
+                            ))
+                        })?;
+                    *count += 1;
+                }
+            }
+            _ => dictionary.fallback_values += non_null as u64,
+        }
+    }
+
+    Ok(dictionary)
+}
+
+/// Decode the values of a dictionary page, which are always plain encoded
+pub fn decode_plain(
+    data: &[u8],
+    num_values: usize,
+    column: &ColumnDescriptor,
+) -> Result<Vec<StatValue>, PQRSError> {
+    let truncated = || {
+        ParquetError::EOF(format!(
+            "The dictionary page of column {} is truncated",
+            column.path().string()
+        ))
+    };
+    let is_decimal = column.converted_type() == ConvertedType::DECIMAL;
+    let unsigned = matches!(
+        column.converted_type(),
+        ConvertedType::UINT_8
+            | ConvertedType::UINT_16
+            | ConvertedType::UINT_32
+            | ConvertedType::UINT_64
+    );
+    let width = match column.physical_type() {
+        PhysicalType::INT32 | PhysicalType::FLOAT => 4,
+        PhysicalType::INT64 | PhysicalType::DOUBLE => 8,
+        PhysicalType::INT96 => 12,
+        PhysicalType::FIXED_LEN_BYTE_ARRAY => column.type_length() as usize,
+        // booleans are bit packed and byte arrays are prefixed with their length
+        PhysicalType::BOOLEAN | PhysicalType::BYTE_ARRAY => 0,
+    };
+
+    let mut values = Vec::with_capacity(num_values);
+    let mut position = 0;
+    for i in 0..num_values {
+        let value = match column.physical_type() {
+            PhysicalType::BOOLEAN => {
+                let byte = data.get(i / 8).ok_or_else(truncated)?;
+                StatValue::Bool(byte >> (i % 8) & 1 == 1)
+            }
+            PhysicalType::BYTE_ARRAY => {
+                let length = data.get(position..position + 4).ok_or_else(truncated)?;
+                let length = u32::from_le_bytes(length.try_into().unwrap()) as usize;
+                position += 4;
+                let bytes = data
+                    .get(position..position + length)
+                    .ok_or_else(truncated)?;
+                position += length;
+                if is_decimal {
+                    StatValue::Int(decimal_from_bytes(bytes))
+                } else {
+                    StatValue::Bytes(bytes.to_vec())
+                }
+            }
+            physical_type => {
+                let bytes = data.get(position..position + width).ok_or_else(truncated)?;
+                position += width;
+                match physical_type {
+                    PhysicalType::INT32 if unsigned => StatValue::Int(
+                        u32::from_le_bytes(bytes.try_into().unwrap()) as i128,
+                    ),
+                    PhysicalType::INT32 => StatValue::Int(i32::from_le_bytes(
+                        bytes.try_into().unwrap(),
+                    ) as i128),
+                    PhysicalType::INT64 if unsigned => StatValue::Int(
+                        u64::from_le_bytes(bytes.try_into().unwrap()) as i128,
+                    ),
+                    PhysicalType::INT64 => StatValue::Int(i64::from_le_bytes(
+                        bytes.try_into().unwrap(),
+                    ) as i128),
+                    PhysicalType::FLOAT => StatValue::Float(f32::from_le_bytes(
+                        bytes.try_into().unwrap(),
+                    ) as f64),
+                    PhysicalType::DOUBLE => {
+                        StatValue::Float(f64::from_le_bytes(bytes.try_into().unwrap()))
+                    }
+                    _ if is_decimal => StatValue::Int(decimal_from_bytes(bytes)),
+                    _ => StatValue::Bytes(bytes.to_vec()),
+                }
+            }
+        };
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+/// The levels of a data page, along with the number of bytes they take
+struct Levels {
+    size: usize,
+    /// None when the maximum level is 0, in which case no levels are stored
+    levels: Option<Vec<u32>>,
+}
+
+/// Read the repetition or definition levels at the start of the data of a v1 page
+fn read_levels(
+    data: &[u8],
+    encoding: Encoding,
+    max_level: i16,
+    num_values: usize,
+) -> Result<Levels, PQRSError> {
+    if max_level == 0 {
+        return Ok(Levels {
+            size: 0,
+            levels: None,
+        });
+    }
+    let bit_width = bit_width(max_level as u64);
+
+    match encoding {
+        Encoding::RLE => {
+            let length = data.get(0..4).ok_or_else(|| {
+                ParquetError::EOF(String::from("The levels are truncated"))
+            })?;
+            let length = u32::from_le_bytes(length.try_into().unwrap()) as usize;
+            let end = (4 + length).min(data.len());
+            let levels = decode_hybrid(&data[4..end], bit_width, num_values)?;
+            Ok(Levels {
+                size: 4 + length,
+                levels: Some(levels),
+            })
+        }
+        // the deprecated bit packing stores the most significant bits first
+        Encoding::BIT_PACKED => {
+            let size = (num_values * bit_width as usize + 7) / 8;
+            let levels = (0..num_values)
+                .map(|i| {
+                    (0..bit_width as usize).fold(0, |level, bit| {
+                        let position = i * bit_width as usize + bit;
+                        let byte = data.get(position / 8).copied().unwrap_or(0);
+                        (level << 1) | (byte >> (7 - position % 8) & 1) as u32
+                    })
+                })
+                .collect();
+            Ok(Levels {
+                size,
+                levels: Some(levels),
+            })
+        }
+        encoding => Err(ParquetError::General(format!(
+            "Unsupported encoding for the levels: {}",
+            encoding
+        ))
+        .into()),
+    }
+}
+
+/// The number of bits needed to store the values up to the given maximum
+fn bit_width(max: u64) -> u8 {
+    (64 - max.leading_zeros()) as u8
+}
+
+/// Decode the given number of values of the RLE / bit-packing hybrid encoding, used by
+/// the levels and the dictionary indices
+pub fn decode_hybrid(
+    data: &[u8],
+    bit_width: u8,
+    count: usize,
+) -> Result<Vec<u32>, PQRSError> {
+    let truncated =
+        || ParquetError::EOF(String::from("The encoded values are truncated"));
+    let mut values = Vec::with_capacity(count);
+    let mut position = 0;
+
+    while values.len() < count {
+        // the header of each run is an unsigned LEB128 varint
+        let mut header: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = *data.get(position).ok_or_else(truncated)?;
+            position += 1;
+            header |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+
+        if header & 1 == 1 {
+            // bit-packed runs hold groups of 8 values, least significant bits first
+            let length = (header >> 1) as usize * 8;
+            let bytes = (header >> 1) as usize * bit_width as usize;
+            let run = data.get(position..position + bytes).ok_or_else(truncated)?;
+            for i in 0..length {
+                let value = (0..bit_width as usize).fold(0, |value, bit| {
+                    let position = i * bit_width as usize + bit;
+                    value | ((run[position / 8] >> (position % 8) & 1) as u32) << bit
+                });
+                values.push(value);
+            }
+            position += bytes;
+        } else {
+            // repeated runs hold a single value in the fewest bytes that fit the width
+            let length = (header >> 1) as usize;
+            let bytes = (bit_width as usize + 7) / 8;
+            let run = data.get(position..position + bytes).ok_or_else(truncated)?;
+            let value = run
+                .iter()
+                .rev()
+                .fold(0, |value, byte| (value << 8) | *byte as u32);
+            values.extend(std::iter::repeat(value).take(length));
+            position += bytes;
+        }
+    }
+
+    // the last bit-packed run is padded to a multiple of 8 values
+    values.truncate(count);
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decodes_the_hybrid_encoding() {
+        // a repeated run of four 3s, followed by a bit-packed run of 0 to 7
+        let data = [0x08, 0x03, 0x03, 0x88, 0xc6, 0xfa];
+        assert_eq!(
+            decode_hybrid(&data, 3, 12).unwrap(),
+            vec![3, 3, 3, 3, 0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert!(decode_hybrid(&data[..4], 3, 12).is_err());
+    }
+
+    #[test]
+    fn it_computes_bit_widths() {
+        assert_eq!(bit_width(0), 0);
+        assert_eq!(bit_width(1), 1);
+        assert_eq!(bit_width(3), 2);
+        assert_eq!(bit_width(4), 3);
+    }
+}
@@ -0,0 +1,390 @@
+use crate::report::Entry;
+use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+use parquet::schema::types::{Type, TypePtr};
+use serde_json::json;
+use std::fmt;
+
+/// The compatibility policies used to decide which changes are breaking
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compatibility {
+    /// Readers using the new schema can read the files written with the old one
+    Backward,
+    /// Readers using the old schema can read the files written with the new one
+    Forward,
+    /// Both backward and forward compatible
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// The field has the same id but a different name
+    Renamed,
+    TypeChanged,
+    RepetitionChanged,
+    LogicalTypeChanged,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Renamed => "renamed",
+            ChangeKind::TypeChanged => "type changed",
+            ChangeKind::RepetitionChanged => "repetition changed",
+            ChangeKind::LogicalTypeChanged => "logical type changed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A difference between two schemas, the path is the dotted path of the field in the
+/// new schema, or in the old schema for removed fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub path: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub breaking: bool,
+}
+
+impl Change {
+    pub fn entry(&self) -> Entry {
+        vec![
+            ("change", json!(self.kind.to_string())),
+            ("path", json!(self.path)),
+            ("old", json!(self.old)),
+            ("new", json!(self.new)),
+            ("breaking", json!(self.breaking)),
+        ]
+    }
+
+    /// The kind, path, old and new description of the change for the text tables,
+    /// with a dash for the missing descriptions
+    pub fn text_row(&self) -> Vec<String> {
+        let value =
+            |value: &Option<String>| value.clone().unwrap_or_else(|| String::from("-"));
+
+        vec![
+            self.kind.to_string(),
+            self.path.clone(),
+            value(&self.old),
+            value(&self.new),
+        ]
+    }
+}
+
+/// Return the changes between the two schemas. Fields are matched by field id when both
+/// have one and by name otherwise, the fields of matching groups are compared in turn.
+pub fn diff_schemas(old: &Type, new: &Type, policy: Compatibility) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_fields(
+        old.get_fields(),
+        new.get_fields(),
+        "",
+        "",
+        policy,
+        &mut changes,
+    );
+    changes
+}
+
+fn diff_fields(
+    old_fields: &[TypePtr],
+    new_fields: &[TypePtr],
+    old_prefix: &str,
+    new_prefix: &str,
+    policy: Compatibility,
+    changes: &mut Vec<Change>,
+) {
+    let mut matched = vec![false; new_fields.len()];
+
+    for old in old_fields {
+        let old_path = format!("{}{}", old_prefix, old.name());
+        let position = new_fields
+            .iter()
+            .position(|new| field_id(old).is_some() && field_id(old) == field_id(new))
+            .or_else(|| {
+                new_fields
+                    .iter()
+                    .position(|new| new.name() == old.name() && field_id(new).is_none())
+            });
+
+        let new = match position {
+            Some(i) if !matched[i] => {
+                matched[i] = true;
+                &new_fields[i]
+            }
+            _ => {
+                // the readers of the new schema do not need the removed fields
+                let required = repetition(old) == Repetition::REQUIRED;
+                changes.push(Change {
+                    kind: ChangeKind::Removed,
+                    path: old_path,
+                    old: Some(describe(old)),
+                    new: None,
+                    breaking: is_breaking(policy, false, required),
+                });
+                continue;
+            }
+        };
+        let new_path = format!("{}{}", new_prefix, new.name());
+
+        let mut change =
+            |kind, old_value: String, new_value: String, backward, forward| {
+                changes.push(Change {
+                    kind,
+                    path: new_path.clone(),
+                    old: Some(old_value),
+                    new: Some(new_value),
+                    breaking: is_breaking(policy, backward, forward),
+                })
+            };
+
+        // most readers match the columns by name rather than by id
+        if old.name() != new.name() {
+            change(
+                ChangeKind::Renamed,
+                old_path.clone(),
+                new_path.clone(),
+                true,
+                true,
+            );
+        }
+
+        let (old_repetition, new_repetition) = (repetition(old), repetition(new));
+        if old_repetition != new_repetition {
+            change(
+                ChangeKind::RepetitionChanged,
+                old_repetition.to_string(),
+                new_repetition.to_string(),
+                !is_relaxed(old_repetition, new_repetition),
+                !is_relaxed(new_repetition, old_repetition),
+            );
+        }
+
+        let (old_type, new_type) = (physical_type(old), physical_type(new));
+        if old_type != new_type {
+            change(
+                ChangeKind::TypeChanged,
+                old_type.clone(),
+                new_type.clone(),
+                !is_widened(old, new),
+                !is_widened(new, old),
+            );
+            // the fields of a group that became a column are not compared
+            if old.is_group() != new.is_group() {
+                continue;
+            }
+        }
+
+        let (old_logical, new_logical) = (logical_type(old), logical_type(new));
+        if old_logical != new_logical {
+            change(
+                ChangeKind::LogicalTypeChanged,
+                old_logical,
+                new_logical,
+                true,
+                true,
+            );
+        }
+
+        if old.is_group() && new.is_group() {
+            diff_fields(
+                old.get_fields(),
+                new.get_fields(),
+                &format!("{}.", old_path),
+                &format!("{}.", new_path),
+                policy,
+                changes,
+            );
+        }
+    }
+
+    for (new, _) in new_fields.iter().zip(matched).filter(|(_, m)| !m) {
+        // the files written with the old schema do not have the added fields
+        let required = repetition(new) == Repetition::REQUIRED;
+        changes.push(Change {
+            kind: ChangeKind::Added,
+            path: format!("{}{}", new_prefix, new.name()),
+            old: None,
+            new: Some(describe(new)),
+            breaking: is_breaking(policy, required, false),
+        });
+    }
+}
+
+/// Decide whether a change is breaking for the policy, given whether it breaks the
+/// backward and forward compatibility
+fn is_breaking(policy: Compatibility, backward: bool, forward: bool) -> bool {
+    match policy {
+        Compatibility::Backward => backward,
+        Compatibility::Forward => forward,
+        Compatibility::Full => backward || forward,
+    }
+}
+
+fn field_id(field: &Type) -> Option<i32> {
+    let info = field.get_basic_info();
+    if info.has_id() {
+        Some(info.id())
+    } else {
+        None
+    }
+}
+
+fn repetition(field: &Type) -> Repetition {
+    let info = field.get_basic_info();
+    if info.has_repetition() {
+        info.repetition()
+    } else {
+        Repetition::REQUIRED
+    }
+}
+
+/// Whether the values of the first repetition can be read with the second one
+fn is_relaxed(from: Repetition, to: Repetition) -> bool {
+    from == Repetition::REQUIRED && to == Repetition::OPTIONAL
+}
+
+/// Whether the values of the first column can be read as the type of the second one
+fn is_widened(from: &Type, to: &Type) -> bool {
+    if from.is_group() || to.is_group() {
+        return false;
+    }
+    matches!(
+        (from.get_physical_type(), to.get_physical_type()),
+        (PhysicalType::INT32, PhysicalType::INT64)
+            | (PhysicalType::FLOAT, PhysicalType::DOUBLE)
+    )
+}
+
+fn physical_type(field: &Type) -> String {
+    match field {
+        Type::GroupType { .. } => String::from("group"),
+        Type::PrimitiveType {
+            physical_type: PhysicalType::FIXED_LEN_BYTE_ARRAY,
+            type_length,
+            ..
+        } => format!("FIXED_LEN_BYTE_ARRAY ({})", type_length),
+        Type::PrimitiveType { physical_type, .. } => physical_type.to_string(),
+    }
+}
+
+fn logical_type(field: &Type) -> String {
+    match field.get_basic_info().converted_type() {
+        ConvertedType::DECIMAL => {
+            format!("DECIMAL({}, {})", field.get_precision(), field.get_scale())
+        }
+        converted_type => converted_type.to_string(),
+    }
+}
+
+/// Describe the field the way it is printed in the schema, e.g. `OPTIONAL INT32 (DATE)`
+fn describe(field: &Type) -> String {
+    match logical_type(field).as_str() {
+        "NONE" => format!("{} {}", repetition(field), physical_type(field)),
+        logical => format!(
+            "{} {} ({})",
+            repetition(field),
+            physical_type(field),
+            logical
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    fn diff(
+        old: &str,
+        new: &str,
+        policy: Compatibility,
+    ) -> Vec<(ChangeKind, String, bool)> {
+        let old = parse_message_type(old).unwrap();
+        let new = parse_message_type(new).unwrap();
+        diff_schemas(&old, &new, policy)
+            .into_iter()
+            .map(|c| (c.kind, c.path, c.breaking))
+            .collect()
+    }
+
+    #[test]
+    fn it_finds_nested_changes() {
+        let old = "message schema {
+            REQUIRED INT32 id;
+            OPTIONAL group address {
+                OPTIONAL BYTE_ARRAY city (UTF8);
+                OPTIONAL FLOAT lat;
+            }
+        }";
+        let new = "message schema {
+            REQUIRED INT64 id;
+            OPTIONAL group address {
+                REQUIRED BYTE_ARRAY city (UTF8);
+                OPTIONAL DOUBLE lat;
+                REQUIRED BYTE_ARRAY zip;
+            }
+        }";
+
+        assert_eq!(
+            diff(old, new, Compatibility::Backward),
+            vec![
+                (ChangeKind::TypeChanged, String::from("id"), false),
+                (
+                    ChangeKind::RepetitionChanged,
+                    String::from("address.city"),
+                    true
+                ),
+                (ChangeKind::TypeChanged, String::from("address.lat"), false),
+                (ChangeKind::Added, String::from("address.zip"), true),
+            ]
+        );
+        let breaking: Vec<bool> = diff(old, new, Compatibility::Forward)
+            .into_iter()
+            .map(|(_, _, breaking)| breaking)
+            .collect();
+        assert_eq!(breaking, vec![true, false, true, false]);
+    }
+
+    #[test]
+    fn it_matches_fields_by_id() {
+        let field = |name, id, converted_type| {
+            Arc::new(
+                Type::primitive_type_builder(name, PhysicalType::INT32)
+                    .with_repetition(Repetition::OPTIONAL)
+                    .with_converted_type(converted_type)
+                    .with_id(id)
+                    .build()
+                    .unwrap(),
+            )
+        };
+        let schema = |fields| {
+            Type::group_type_builder("schema")
+                .with_fields(&mut vec![fields, field("age", 2, ConvertedType::NONE)])
+                .build()
+                .unwrap()
+        };
+        let old = schema(field("name", 1, ConvertedType::NONE));
+        let new = schema(field("full_name", 1, ConvertedType::DATE));
+
+        let changes: Vec<(ChangeKind, String)> =
+            diff_schemas(&old, &new, Compatibility::Full)
+                .into_iter()
+                .map(|c| (c.kind, c.path))
+                .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (ChangeKind::Renamed, String::from("full_name")),
+                (ChangeKind::LogicalTypeChanged, String::from("full_name")),
+            ]
+        );
+    }
+}
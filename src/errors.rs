@@ -1,8 +1,10 @@
 use arrow::error::ArrowError;
+use avro_rs::Error as AvroError;
 use parquet::errors::ParquetError;
 use std::io;
 use std::num::ParseIntError;
 use thiserror::Error;
+use thrift::Error as ThriftError;
 
 #[allow(dead_code)]
 #[derive(Error, Debug)]
@@ -21,4 +23,16 @@ pub enum PQRSError {
     UnableToProcessFile(#[from] io::Error),
     #[error("Unable to read/write arrow data")]
     ArrowReadWriteError(#[from] ArrowError),
+    #[error("Unknown column {0}, available columns are: {1}")]
+    UnknownColumn(String, String),
+    #[error("Invalid expression: {0}")]
+    InvalidExpression(String),
+    #[error("The schemas of the inputs do not match: {0}")]
+    SchemaMismatch(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Unable to read/write avro data")]
+    AvroReadWriteError(#[from] AvroError),
+    #[error("Unable to read the thrift encoded metadata")]
+    ThriftError(#[from] ThriftError),
 }
@@ -0,0 +1,875 @@
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::InvalidExpression;
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::statistics::Statistics;
+use parquet::record::{Field, Row};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A single value produced while evaluating an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    /// A nested value (a struct or a list), nested values cannot be compared
+    Json(serde_json::Value),
+}
+
+impl Value {
+    /// Convert a parquet field into a value, nested fields (groups, lists and maps)
+    /// cannot be compared and are treated as null
+    pub fn from_field(field: &Field) -> Self {
+        match field {
+            Field::Null => Value::Null,
+            Field::Bool(b) => Value::Bool(*b),
+            Field::Byte(v) => Value::Int(*v as i64),
+            Field::Short(v) => Value::Int(*v as i64),
+            Field::Int(v) => Value::Int(*v as i64),
+            Field::Long(v) => Value::Int(*v),
+            Field::UByte(v) => Value::Int(*v as i64),
+            Field::UShort(v) => Value::Int(*v as i64),
+            Field::UInt(v) => Value::Int(*v as i64),
+            Field::ULong(v) => Value::Float(*v as f64),
+            Field::Float(v) => Value::Float(*v as f64),
+            Field::Double(v) => Value::Float(*v),
+            Field::Str(s) => Value::Str(s.clone()),
+            Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => {
+                Value::Null
+            }
+            // dates, timestamps, decimals and binary values are compared using their
+            // string representation, e.g. '2021-01-17'
+            other => match other.to_json_value() {
+                serde_json::Value::String(s) => Value::Str(s),
+                value => Value::Str(value.to_string()),
+            },
+        }
+    }
+
+    /// Compare two values, returns None if the values are not comparable
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+            (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        *self == Value::Bool(true)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::Str(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            Value::Json(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// A record that expressions can be evaluated against
+pub trait Record {
+    /// Return the value of the column with the given dotted path, or null if it is missing
+    fn get(&self, path: &str) -> Value;
+}
+
+impl Record for Row {
+    fn get(&self, path: &str) -> Value {
+        get_column(self, path)
+            .map(Value::from_field)
+            .unwrap_or(Value::Null)
+    }
+}
+
+/// The comparison operators supported in expressions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CompareOp {
+    /// Return the operator to use when the operands are swapped, i.e. `1 < a` is `a > 1`
+    fn flip(self) -> Self {
+        match self {
+            CompareOp::Lt => CompareOp::Gt,
+            CompareOp::LtEq => CompareOp::GtEq,
+            CompareOp::Gt => CompareOp::Lt,
+            CompareOp::GtEq => CompareOp::LtEq,
+            op => op,
+        }
+    }
+
+    fn matches(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::NotEq => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::LtEq => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::GtEq => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// A boolean or scalar expression that can be evaluated against a record
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A reference to a column, nested fields use a dotted path
+    Column(String),
+    Literal(Value),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// `expr [NOT] IN (values...)`
+    InList(Box<Expr>, Vec<Expr>, bool),
+    /// `expr IS [NOT] NULL`
+    IsNull(Box<Expr>, bool),
+    /// `expr [NOT] LIKE 'pattern'`
+    Like(Box<Expr>, String, bool),
+}
+
+impl Expr {
+    /// Parse the given text into an expression
+    pub fn parse(text: &str) -> Result<Expr, PQRSError> {
+        let mut parser = Parser::new(tokenize(text)?);
+        let expr = parser.parse_expr()?;
+        parser.expect_end()?;
+
+        Ok(expr)
+    }
+
+    /// Return the distinct columns referenced by this expression
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns = Vec::new();
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    fn collect_columns(&self, columns: &mut Vec<String>) {
+        match self {
+            Expr::Column(name) => {
+                if !columns.contains(name) {
+                    columns.push(name.clone());
+                }
+            }
+            Expr::Literal(_) => {}
+            Expr::Compare(left, _, right)
+            | Expr::And(left, right)
+            | Expr::Or(left, right) => {
+                left.collect_columns(columns);
+                right.collect_columns(columns);
+            }
+            Expr::Not(expr) | Expr::IsNull(expr, _) | Expr::Like(expr, _, _) => {
+                expr.collect_columns(columns)
+            }
+            Expr::InList(expr, list, _) => {
+                expr.collect_columns(columns);
+                for item in list {
+                    item.collect_columns(columns);
+                }
+            }
+        }
+    }
+
+    /// Evaluate the expression against the given record using SQL semantics,
+    /// comparisons involving nulls produce null
+    pub fn evaluate<R: Record>(&self, row: &R) -> Value {
+        match self {
+            Expr::Column(name) => row.get(name),
+            Expr::Literal(value) => value.clone(),
+            Expr::Compare(left, op, right) => {
+                match left.evaluate(row).compare(&right.evaluate(row)) {
+                    Some(ordering) => Value::Bool(op.matches(ordering)),
+                    None => Value::Null,
+                }
+            }
+            Expr::And(left, right) => match (left.evaluate(row), right.evaluate(row)) {
+                (Value::Bool(false), _) | (_, Value::Bool(false)) => Value::Bool(false),
+                (Value::Bool(true), Value::Bool(true)) => Value::Bool(true),
+                _ => Value::Null,
+            },
+            Expr::Or(left, right) => match (left.evaluate(row), right.evaluate(row)) {
+                (Value::Bool(true), _) | (_, Value::Bool(true)) => Value::Bool(true),
+                (Value::Bool(false), Value::Bool(false)) => Value::Bool(false),
+                _ => Value::Null,
+            },
+            Expr::Not(expr) => match expr.evaluate(row) {
+                Value::Bool(b) => Value::Bool(!b),
+                _ => Value::Null,
+            },
+            Expr::InList(expr, list, negated) => {
+                let value = expr.evaluate(row);
+                if value == Value::Null {
+                    return Value::Null;
+                }
+                let found = list.iter().any(|item| {
+                    value.compare(&item.evaluate(row)) == Some(Ordering::Equal)
+                });
+                Value::Bool(found != *negated)
+            }
+            Expr::IsNull(expr, negated) => {
+                Value::Bool((expr.evaluate(row) == Value::Null) != *negated)
+            }
+            Expr::Like(expr, pattern, negated) => match expr.evaluate(row) {
+                Value::Str(s) => Value::Bool(like(&s, pattern) != *negated),
+                _ => Value::Null,
+            },
+        }
+    }
+
+    /// Return true if the expression evaluates to true for the given record
+    pub fn matches<R: Record>(&self, row: &R) -> bool {
+        self.evaluate(row).is_true()
+    }
+
+    /// Use the column chunk statistics to check if any record in the given row group
+    /// could match the expression. This errs on the side of caution and only returns
+    /// false if the statistics prove that none of the records can match. The null counts
+    /// of the column chunks are given separately, as written in the file metadata, since
+    /// the parsed statistics report a missing null count as 0.
+    pub fn may_match(
+        &self,
+        row_group: &RowGroupMetaData,
+        null_counts: &[Option<i64>],
+    ) -> bool {
+        match self {
+            Expr::And(left, right) => {
+                left.may_match(row_group, null_counts)
+                    && right.may_match(row_group, null_counts)
+            }
+            Expr::Or(left, right) => {
+                left.may_match(row_group, null_counts)
+                    || right.may_match(row_group, null_counts)
+            }
+            Expr::Compare(left, op, right) => match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(name), Expr::Literal(value)) => {
+                    may_compare(row_group, null_counts, name, *op, value)
+                }
+                (Expr::Literal(value), Expr::Column(name)) => {
+                    may_compare(row_group, null_counts, name, op.flip(), value)
+                }
+                _ => true,
+            },
+            Expr::InList(expr, list, false) => match expr.as_ref() {
+                Expr::Column(name) => list.iter().any(|item| match item {
+                    Expr::Literal(value) => {
+                        may_compare(row_group, null_counts, name, CompareOp::Eq, value)
+                    }
+                    _ => true,
+                }),
+                _ => true,
+            },
+            Expr::IsNull(expr, negated) => {
+                match (expr.as_ref(), get_range(row_group, null_counts, expr)) {
+                    (Expr::Column(_), Some(range)) if !negated => {
+                        range.null_count != Some(0)
+                    }
+                    (Expr::Column(_), Some(range)) => {
+                        range.null_count != Some(range.num_values)
+                    }
+                    _ => true,
+                }
+            }
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not = |negated: &bool| if *negated { "NOT " } else { "" };
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Compare(left, op, right) => {
+                let op = match op {
+                    CompareOp::Eq => "=",
+                    CompareOp::NotEq => "!=",
+                    CompareOp::Lt => "<",
+                    CompareOp::LtEq => "<=",
+                    CompareOp::Gt => ">",
+                    CompareOp::GtEq => ">=",
+                };
+                write!(f, "{} {} {}", left, op, right)
+            }
+            Expr::And(left, right) => write!(f, "({} AND {})", left, right),
+            Expr::Or(left, right) => write!(f, "({} OR {})", left, right),
+            Expr::Not(expr) => write!(f, "NOT {}", expr),
+            Expr::InList(expr, list, negated) => {
+                let items: Vec<String> = list.iter().map(|e| e.to_string()).collect();
+                write!(f, "{} {}IN ({})", expr, not(negated), items.join(", "))
+            }
+            Expr::IsNull(expr, negated) => write!(f, "{} IS {}NULL", expr, not(negated)),
+            Expr::Like(expr, pattern, negated) => {
+                write!(
+                    f,
+                    "{} {}LIKE {}",
+                    expr,
+                    not(negated),
+                    Value::Str(pattern.clone())
+                )
+            }
+        }
+    }
+}
+
+/// Find the field for the given dotted path in the record
+pub fn get_column<'a>(row: &'a Row, path: &str) -> Option<&'a Field> {
+    let mut current = row;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let field = current
+            .get_column_iter()
+            .find(|(name, _)| name.as_str() == part)
+            .map(|(_, field)| field)?;
+        if parts.peek().is_none() {
+            return Some(field);
+        }
+        match field {
+            Field::Group(nested) => current = nested,
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Match the value against a SQL LIKE pattern, `%` matches any number of characters
+/// and `_` matches exactly one character
+fn like(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    // matches[j] is true if the value seen so far matches the first j pattern characters
+    let mut matches = vec![false; pattern.len() + 1];
+    matches[0] = true;
+    for j in 1..=pattern.len() {
+        matches[j] = matches[j - 1] && pattern[j - 1] == '%';
+    }
+
+    for c in value {
+        let mut next = vec![false; pattern.len() + 1];
+        for j in 1..=pattern.len() {
+            next[j] = match pattern[j - 1] {
+                '%' => next[j - 1] || matches[j],
+                '_' => matches[j - 1],
+                p => matches[j - 1] && p == c,
+            };
+        }
+        matches = next;
+    }
+
+    matches[pattern.len()]
+}
+
+/// The min/max range and null count of a column chunk taken from its statistics
+struct ColumnRange {
+    min: Option<Value>,
+    max: Option<Value>,
+    null_count: Option<i64>,
+    num_values: i64,
+}
+
+/// Return the range of values for the column chunk referenced by the expression
+fn get_range(
+    row_group: &RowGroupMetaData,
+    null_counts: &[Option<i64>],
+    expr: &Expr,
+) -> Option<ColumnRange> {
+    let name = match expr {
+        Expr::Column(name) => name,
+        _ => return None,
+    };
+    let (index, column) = row_group
+        .columns()
+        .iter()
+        .enumerate()
+        .find(|(_, c)| c.column_path().string() == *name)?;
+    let statistics = column.statistics()?;
+
+    // only use the min/max values if they are compared the same way the values
+    // read from the records are, i.e. skip unsigned, decimal and temporal types
+    let converted_type = column.column_descr().converted_type();
+    let (min, max) = if !statistics.has_min_max_set() {
+        (None, None)
+    } else {
+        match (statistics, converted_type) {
+            (Statistics::Boolean(s), _) => {
+                (Some(Value::Bool(*s.min())), Some(Value::Bool(*s.max())))
+            }
+            (
+                Statistics::Int32(s),
+                ConvertedType::NONE
+                | ConvertedType::INT_8
+                | ConvertedType::INT_16
+                | ConvertedType::INT_32,
+            ) => (
+                Some(Value::Int(*s.min() as i64)),
+                Some(Value::Int(*s.max() as i64)),
+            ),
+            (Statistics::Int64(s), ConvertedType::NONE | ConvertedType::INT_64) => {
+                (Some(Value::Int(*s.min())), Some(Value::Int(*s.max())))
+            }
+            (Statistics::Float(s), _) => (
+                Some(Value::Float(*s.min() as f64)),
+                Some(Value::Float(*s.max() as f64)),
+            ),
+            (Statistics::Double(s), _) => {
+                (Some(Value::Float(*s.min())), Some(Value::Float(*s.max())))
+            }
+            (
+                Statistics::ByteArray(s),
+                ConvertedType::UTF8 | ConvertedType::ENUM | ConvertedType::JSON,
+            ) if column.column_type() == PhysicalType::BYTE_ARRAY => (
+                Some(Value::Str(
+                    String::from_utf8_lossy(s.min().data()).into_owned(),
+                )),
+                Some(Value::Str(
+                    String::from_utf8_lossy(s.max().data()).into_owned(),
+                )),
+            ),
+            _ => (None, None),
+        }
+    };
+
+    Some(ColumnRange {
+        min,
+        max,
+        // the null count is unknown when the writer did not include it in the statistics
+        null_count: null_counts.get(index).copied().flatten(),
+        num_values: column.num_values(),
+    })
+}
+
+/// Check if the given comparison could be true for any value in the column chunk
+fn may_compare(
+    row_group: &RowGroupMetaData,
+    null_counts: &[Option<i64>],
+    name: &str,
+    op: CompareOp,
+    value: &Value,
+) -> bool {
+    let range = match get_range(row_group, null_counts, &Expr::Column(name.to_string())) {
+        Some(range) => range,
+        None => return true,
+    };
+
+    // comparisons against a column chunk containing only nulls are never true
+    if range.null_count == Some(range.num_values) {
+        return false;
+    }
+
+    let (min, max) = match (&range.min, &range.max) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return true,
+    };
+    let (min_ordering, max_ordering) = match (min.compare(value), max.compare(value)) {
+        (Some(min_ordering), Some(max_ordering)) => (min_ordering, max_ordering),
+        _ => return true,
+    };
+
+    match op {
+        CompareOp::Eq => {
+            min_ordering != Ordering::Greater && max_ordering != Ordering::Less
+        }
+        CompareOp::NotEq => {
+            !(min_ordering == Ordering::Equal && max_ordering == Ordering::Equal)
+        }
+        CompareOp::Lt => min_ordering == Ordering::Less,
+        CompareOp::LtEq => min_ordering != Ordering::Greater,
+        CompareOp::Gt => max_ordering == Ordering::Greater,
+        CompareOp::GtEq => max_ordering != Ordering::Less,
+    }
+}
+
+/// The tokens that make up an expression
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Identifier(String),
+    /// A keyword such as AND or SELECT, stored in upper case
+    Keyword(String),
+    Number(String),
+    Str(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(s) | Token::Keyword(s) | Token::Number(s) => {
+                write!(f, "{}", s)
+            }
+            Token::Str(s) => write!(f, "'{}'", s),
+            Token::Symbol(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+static KEYWORDS: &[&str] = &[
+    "AND", "OR", "NOT", "IN", "IS", "NULL", "LIKE", "TRUE", "FALSE",
+];
+
+static SYMBOLS: &[&str] = &["<=", ">=", "!=", "<>", "=", "<", ">", "(", ")", ",", "*"];
+
+/// Split the given text into tokens. Identifiers can be quoted using double quotes
+/// or backticks, strings use single quotes with '' as the escaped quote.
+pub(crate) fn tokenize(text: &str) -> Result<Vec<Token>, PQRSError> {
+    tokenize_with_keywords(text, &[])
+}
+
+/// Split the given text into tokens, the given words being keywords on top of the ones
+/// of the filter expressions, e.g. the SQL keywords used by queries
+pub(crate) fn tokenize_with_keywords(
+    text: &str,
+    keywords: &[&str],
+) -> Result<Vec<Token>, PQRSError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => {
+                        return Err(InvalidExpression(String::from(
+                            "Unterminated string",
+                        )))
+                    }
+                    Some('\'') if chars.get(i + 1) == Some(&'\'') => {
+                        value.push('\'');
+                        i += 2;
+                    }
+                    Some('\'') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => {
+                        value.push(*c);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token::Str(value));
+        } else if c == '"' || c == '`' {
+            let end = chars[i + 1..].iter().position(|x| *x == c).ok_or_else(|| {
+                InvalidExpression(String::from("Unterminated identifier"))
+            })?;
+            tokens.push(Token::Identifier(
+                chars[i + 1..i + 1 + end].iter().collect(),
+            ));
+            i += end + 2;
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).map_or(false, |n| n.is_ascii_digit()))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == 'e')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let upper = word.to_uppercase();
+            if KEYWORDS.contains(&upper.as_str()) || keywords.contains(&upper.as_str()) {
+                tokens.push(Token::Keyword(word.to_uppercase()));
+            } else {
+                tokens.push(Token::Identifier(word));
+            }
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            match SYMBOLS.iter().find(|s| rest.starts_with(*s)) {
+                Some(symbol) => {
+                    tokens.push(Token::Symbol(*symbol));
+                    i += symbol.len();
+                }
+                None => {
+                    return Err(InvalidExpression(format!("Unexpected character: {}", c)))
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser for expressions. The precedence from lowest to highest
+/// is OR, AND, NOT, comparisons (including IN, IS NULL and LIKE) and literals.
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            position: 0,
+        }
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Return the token the given number of positions after the next one
+    pub(crate) fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset)
+    }
+
+    pub(crate) fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consume the next token if it is the given keyword
+    pub(crate) fn accept_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(&Token::Keyword(keyword.to_string())) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    /// Consume the next token if it is the given symbol
+    pub(crate) fn accept_symbol(&mut self, symbol: &str) -> bool {
+        match self.peek() {
+            Some(Token::Symbol(s)) if *s == symbol => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn expect_keyword(&mut self, keyword: &str) -> Result<(), PQRSError> {
+        if self.accept_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(keyword))
+        }
+    }
+
+    pub(crate) fn expect_symbol(&mut self, symbol: &str) -> Result<(), PQRSError> {
+        if self.accept_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(symbol))
+        }
+    }
+
+    pub(crate) fn expect_end(&self) -> Result<(), PQRSError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("end of expression")),
+        }
+    }
+
+    pub(crate) fn unexpected(&self, expected: &str) -> PQRSError {
+        match self.peek() {
+            Some(token) => {
+                InvalidExpression(format!("Expected {} but found {}", expected, token))
+            }
+            None => {
+                InvalidExpression(format!("Expected {} but reached the end", expected))
+            }
+        }
+    }
+
+    pub(crate) fn parse_expr(&mut self) -> Result<Expr, PQRSError> {
+        let mut expr = self.parse_and()?;
+        while self.accept_keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, PQRSError> {
+        let mut expr = self.parse_not()?;
+        while self.accept_keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, PQRSError> {
+        if self.accept_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expr, PQRSError> {
+        let left = self.parse_primary()?;
+
+        if self.accept_keyword("IS") {
+            let negated = self.accept_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull(Box::new(left), negated));
+        }
+
+        let negated = self.accept_keyword("NOT");
+        if self.accept_keyword("IN") {
+            self.expect_symbol("(")?;
+            let mut list = vec![self.parse_primary()?];
+            while self.accept_symbol(",") {
+                list.push(self.parse_primary()?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(Expr::InList(Box::new(left), list, negated));
+        }
+        if self.accept_keyword("LIKE") {
+            return match self.next_token() {
+                Some(Token::Str(pattern)) => {
+                    Ok(Expr::Like(Box::new(left), pattern, negated))
+                }
+                _ => Err(InvalidExpression(String::from(
+                    "LIKE must be followed by a string pattern",
+                ))),
+            };
+        }
+        if negated {
+            return Err(self.unexpected("IN or LIKE"));
+        }
+
+        let op = match self.peek() {
+            Some(Token::Symbol("=")) => CompareOp::Eq,
+            Some(Token::Symbol("!=")) | Some(Token::Symbol("<>")) => CompareOp::NotEq,
+            Some(Token::Symbol("<")) => CompareOp::Lt,
+            Some(Token::Symbol("<=")) => CompareOp::LtEq,
+            Some(Token::Symbol(">")) => CompareOp::Gt,
+            Some(Token::Symbol(">=")) => CompareOp::GtEq,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.parse_primary()?;
+
+        Ok(Expr::Compare(Box::new(left), op, Box::new(right)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, PQRSError> {
+        match self.next_token() {
+            Some(Token::Identifier(name)) => Ok(Expr::Column(name)),
+            Some(Token::Str(value)) => Ok(Expr::Literal(Value::Str(value))),
+            Some(Token::Number(number)) => match number.parse::<i64>() {
+                Ok(value) => Ok(Expr::Literal(Value::Int(value))),
+                Err(_) => number
+                    .parse::<f64>()
+                    .map(|value| Expr::Literal(Value::Float(value)))
+                    .map_err(|_| {
+                        InvalidExpression(format!("Invalid number: {}", number))
+                    }),
+            },
+            Some(Token::Keyword(keyword)) if keyword == "NULL" => {
+                Ok(Expr::Literal(Value::Null))
+            }
+            Some(Token::Keyword(keyword)) if keyword == "TRUE" => {
+                Ok(Expr::Literal(Value::Bool(true)))
+            }
+            Some(Token::Keyword(keyword)) if keyword == "FALSE" => {
+                Ok(Expr::Literal(Value::Bool(false)))
+            }
+            Some(Token::Symbol("(")) => {
+                let expr = self.parse_expr()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.unexpected("a column or a value"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{like, CompareOp, Expr, Value};
+
+    #[test]
+    fn it_parses_expressions() {
+        let expr =
+            Expr::parse("a = 1 AND NOT (b IN ('x', 'y') OR c IS NOT NULL)").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(a = 1 AND NOT (b IN ('x', 'y') OR c IS NOT NULL))"
+        );
+        assert_eq!(expr.columns(), vec!["a", "b", "c"]);
+
+        let expr = Expr::parse("country.name LIKE 'Fr%'").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Like(
+                Box::new(Expr::Column(String::from("country.name"))),
+                String::from("Fr%"),
+                false
+            )
+        );
+
+        // the SQL keywords of queries are column names in filters
+        let expr = Expr::parse("order > 3 AND by = 'x'").unwrap();
+        assert_eq!(expr.columns(), vec!["order", "by"]);
+
+        let expr = Expr::parse("10 <= speed1").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Compare(
+                Box::new(Expr::Literal(Value::Int(10))),
+                CompareOp::LtEq,
+                Box::new(Expr::Column(String::from("speed1")))
+            )
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_expressions() {
+        assert!(Expr::parse("a =").is_err());
+        assert!(Expr::parse("a = 'unterminated").is_err());
+        assert!(Expr::parse("a NOT = 1").is_err());
+        assert!(Expr::parse("(a = 1").is_err());
+        assert!(Expr::parse("a = 1 b").is_err());
+    }
+
+    #[test]
+    fn it_matches_like_patterns() {
+        assert!(like("France", "Fr%"));
+        assert!(like("France", "%an%"));
+        assert!(like("France", "Fr_nce"));
+        assert!(like("", "%"));
+        assert!(!like("France", "Fr_"));
+        assert!(!like("Greece", "Fr%"));
+    }
+}
@@ -0,0 +1,555 @@
+use crate::errors::PQRSError;
+use parquet::errors::ParquetError;
+use serde_json::{json, Map, Value};
+use std::convert::TryInto;
+use std::io::{Read, Seek, SeekFrom};
+use thrift::protocol::{TCompactInputProtocol, TInputProtocol, TType};
+
+/// The magic number at the start and at the end of Parquet files
+pub const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+/// The magic number at the end of the files with an encrypted footer
+const ENCRYPTED_MAGIC: &[u8] = b"PARE";
+
+/// Read the thrift encoded file metadata at the end of the input, which is either a whole
+/// Parquet file or only its last bytes, e.g. the tail of a truncated file
+pub fn read_footer<R: Read + Seek>(reader: &mut R) -> Result<Vec<u8>, PQRSError> {
+    let size = reader.seek(SeekFrom::End(0))?;
+    if size < 8 {
+        return Err(ParquetError::General(format!(
+            "The input is too small to contain a Parquet footer: {} bytes",
+            size
+        ))
+        .into());
+    }
+
+    let mut footer = [0; 8];
+    reader.seek(SeekFrom::End(-8))?;
+    reader.read_exact(&mut footer)?;
+    if &footer[4..] == ENCRYPTED_MAGIC {
+        return Err(ParquetError::General(String::from(
+            "The footer is encrypted and cannot be read without its key",
+        ))
+        .into());
+    }
+    if &footer[4..] != PARQUET_MAGIC {
+        return Err(ParquetError::General(String::from(
+            "The input does not end with the Parquet magic number",
+        ))
+        .into());
+    }
+
+    let length = u32::from_le_bytes(footer[..4].try_into().unwrap()) as u64;
+    if length + 8 > size {
+        return Err(ParquetError::General(format!(
+            "The input is missing the start of the file metadata: {} bytes are needed but only {} are available",
+            length + 8,
+            size
+        ))
+        .into());
+    }
+
+    let mut metadata = vec![0; length as usize];
+    reader.seek(SeekFrom::Start(size - 8 - length))?;
+    reader.read_exact(&mut metadata)?;
+    Ok(metadata)
+}
+
+/// How the value of a thrift field is printed, the wire type being known from the data
+#[derive(Debug, Clone, Copy)]
+enum FieldType {
+    /// Numbers and booleans
+    Plain,
+    Enum(&'static [&'static str]),
+    String,
+    /// Bytes that are printed in hexadecimal, e.g. the statistics and the key metadata
+    Binary,
+    Struct(&'static [Field]),
+    List(&'static FieldType),
+}
+
+type Field = (i16, &'static str, FieldType);
+
+const TYPE: FieldType = FieldType::Enum(&[
+    "BOOLEAN",
+    "INT32",
+    "INT64",
+    "INT96",
+    "FLOAT",
+    "DOUBLE",
+    "BYTE_ARRAY",
+    "FIXED_LEN_BYTE_ARRAY",
+]);
+
+const CONVERTED_TYPE: FieldType = FieldType::Enum(&[
+    "UTF8",
+    "MAP",
+    "MAP_KEY_VALUE",
+    "LIST",
+    "ENUM",
+    "DECIMAL",
+    "DATE",
+    "TIME_MILLIS",
+    "TIME_MICROS",
+    "TIMESTAMP_MILLIS",
+    "TIMESTAMP_MICROS",
+    "UINT_8",
+    "UINT_16",
+    "UINT_32",
+    "UINT_64",
+    "INT_8",
+    "INT_16",
+    "INT_32",
+    "INT_64",
+    "JSON",
+    "BSON",
+    "INTERVAL",
+]);
+
+const REPETITION: FieldType = FieldType::Enum(&["REQUIRED", "OPTIONAL", "REPEATED"]);
+
+const ENCODING: FieldType = FieldType::Enum(&[
+    "PLAIN",
+    "GROUP_VAR_INT",
+    "PLAIN_DICTIONARY",
+    "RLE",
+    "BIT_PACKED",
+    "DELTA_BINARY_PACKED",
+    "DELTA_LENGTH_BYTE_ARRAY",
+    "DELTA_BYTE_ARRAY",
+    "RLE_DICTIONARY",
+    "BYTE_STREAM_SPLIT",
+]);
+
+const CODEC: FieldType = FieldType::Enum(&[
+    "UNCOMPRESSED",
+    "SNAPPY",
+    "GZIP",
+    "LZO",
+    "BROTLI",
+    "LZ4",
+    "ZSTD",
+    "LZ4_RAW",
+]);
+
+const PAGE_TYPE: FieldType =
+    FieldType::Enum(&["DATA_PAGE", "INDEX_PAGE", "DICTIONARY_PAGE", "DATA_PAGE_V2"]);
+
+const EDGE_INTERPOLATION: FieldType =
+    FieldType::Enum(&["SPHERICAL", "VINCENTY", "THOMAS", "ANDOYER", "KARNEY"]);
+
+/// The structs without fields, used as the members of unions
+const EMPTY: FieldType = FieldType::Struct(&[]);
+
+const TIME_UNIT: FieldType = FieldType::Struct(&[
+    (1, "MILLIS", EMPTY),
+    (2, "MICROS", EMPTY),
+    (3, "NANOS", EMPTY),
+]);
+
+const LOGICAL_TYPE: FieldType = FieldType::Struct(&[
+    (1, "STRING", EMPTY),
+    (2, "MAP", EMPTY),
+    (3, "LIST", EMPTY),
+    (4, "ENUM", EMPTY),
+    (
+        5,
+        "DECIMAL",
+        FieldType::Struct(&[
+            (1, "scale", FieldType::Plain),
+            (2, "precision", FieldType::Plain),
+        ]),
+    ),
+    (6, "DATE", EMPTY),
+    (
+        7,
+        "TIME",
+        FieldType::Struct(&[
+            (1, "isAdjustedToUTC", FieldType::Plain),
+            (2, "unit", TIME_UNIT),
+        ]),
+    ),
+    (
+        8,
+        "TIMESTAMP",
+        FieldType::Struct(&[
+            (1, "isAdjustedToUTC", FieldType::Plain),
+            (2, "unit", TIME_UNIT),
+        ]),
+    ),
+    (
+        10,
+        "INTEGER",
+        FieldType::Struct(&[
+            (1, "bitWidth", FieldType::Plain),
+            (2, "isSigned", FieldType::Plain),
+        ]),
+    ),
+    (11, "UNKNOWN", EMPTY),
+    (12, "JSON", EMPTY),
+    (13, "BSON", EMPTY),
+    (14, "UUID", EMPTY),
+    (15, "FLOAT16", EMPTY),
+    (
+        16,
+        "VARIANT",
+        FieldType::Struct(&[(1, "specification_version", FieldType::Plain)]),
+    ),
+    (
+        17,
+        "GEOMETRY",
+        FieldType::Struct(&[(1, "crs", FieldType::String)]),
+    ),
+    (
+        18,
+        "GEOGRAPHY",
+        FieldType::Struct(&[
+            (1, "crs", FieldType::String),
+            (2, "algorithm", EDGE_INTERPOLATION),
+        ]),
+    ),
+]);
+
+const SCHEMA_ELEMENT: FieldType = FieldType::Struct(&[
+    (1, "type", TYPE),
+    (2, "type_length", FieldType::Plain),
+    (3, "repetition_type", REPETITION),
+    (4, "name", FieldType::String),
+    (5, "num_children", FieldType::Plain),
+    (6, "converted_type", CONVERTED_TYPE),
+    (7, "scale", FieldType::Plain),
+    (8, "precision", FieldType::Plain),
+    (9, "field_id", FieldType::Plain),
+    (10, "logicalType", LOGICAL_TYPE),
+]);
+
+const KEY_VALUE: FieldType = FieldType::Struct(&[
+    (1, "key", FieldType::String),
+    (2, "value", FieldType::String),
+]);
+
+const STATISTICS: FieldType = FieldType::Struct(&[
+    (1, "max", FieldType::Binary),
+    (2, "min", FieldType::Binary),
+    (3, "null_count", FieldType::Plain),
+    (4, "distinct_count", FieldType::Plain),
+    (5, "max_value", FieldType::Binary),
+    (6, "min_value", FieldType::Binary),
+    (7, "is_max_value_exact", FieldType::Plain),
+    (8, "is_min_value_exact", FieldType::Plain),
+]);
+
+const PAGE_ENCODING_STATS: FieldType = FieldType::Struct(&[
+    (1, "page_type", PAGE_TYPE),
+    (2, "encoding", ENCODING),
+    (3, "count", FieldType::Plain),
+]);
+
+const SIZE_STATISTICS: FieldType = FieldType::Struct(&[
+    (1, "unencoded_byte_array_data_bytes", FieldType::Plain),
+    (
+        2,
+        "repetition_level_histogram",
+        FieldType::List(&FieldType::Plain),
+    ),
+    (
+        3,
+        "definition_level_histogram",
+        FieldType::List(&FieldType::Plain),
+    ),
+]);
+
+const GEOSPATIAL_STATISTICS: FieldType = FieldType::Struct(&[
+    (
+        1,
+        "bbox",
+        FieldType::Struct(&[
+            (1, "xmin", FieldType::Plain),
+            (2, "xmax", FieldType::Plain),
+            (3, "ymin", FieldType::Plain),
+            (4, "ymax", FieldType::Plain),
+            (5, "zmin", FieldType::Plain),
+            (6, "zmax", FieldType::Plain),
+            (7, "mmin", FieldType::Plain),
+            (8, "mmax", FieldType::Plain),
+        ]),
+    ),
+    (2, "geospatial_types", FieldType::List(&FieldType::Plain)),
+]);
+
+const COLUMN_METADATA: FieldType = FieldType::Struct(&[
+    (1, "type", TYPE),
+    (2, "encodings", FieldType::List(&ENCODING)),
+    (3, "path_in_schema", FieldType::List(&FieldType::String)),
+    (4, "codec", CODEC),
+    (5, "num_values", FieldType::Plain),
+    (6, "total_uncompressed_size", FieldType::Plain),
+    (7, "total_compressed_size", FieldType::Plain),
+    (8, "key_value_metadata", FieldType::List(&KEY_VALUE)),
+    (9, "data_page_offset", FieldType::Plain),
+    (10, "index_page_offset", FieldType::Plain),
+    (11, "dictionary_page_offset", FieldType::Plain),
+    (12, "statistics", STATISTICS),
+    (13, "encoding_stats", FieldType::List(&PAGE_ENCODING_STATS)),
+    (14, "bloom_filter_offset", FieldType::Plain),
+    (15, "bloom_filter_length", FieldType::Plain),
+    (16, "size_statistics", SIZE_STATISTICS),
+    (17, "geospatial_statistics", GEOSPATIAL_STATISTICS),
+]);
+
+const COLUMN_CRYPTO_METADATA: FieldType = FieldType::Struct(&[
+    (1, "ENCRYPTION_WITH_FOOTER_KEY", EMPTY),
+    (
+        2,
+        "ENCRYPTION_WITH_COLUMN_KEY",
+        FieldType::Struct(&[
+            (1, "path_in_schema", FieldType::List(&FieldType::String)),
+            (2, "key_metadata", FieldType::Binary),
+        ]),
+    ),
+]);
+
+const COLUMN_CHUNK: FieldType = FieldType::Struct(&[
+    (1, "file_path", FieldType::String),
+    (2, "file_offset", FieldType::Plain),
+    (3, "meta_data", COLUMN_METADATA),
+    (4, "offset_index_offset", FieldType::Plain),
+    (5, "offset_index_length", FieldType::Plain),
+    (6, "column_index_offset", FieldType::Plain),
+    (7, "column_index_length", FieldType::Plain),
+    (8, "crypto_metadata", COLUMN_CRYPTO_METADATA),
+    (9, "encrypted_column_metadata", FieldType::Binary),
+]);
+
+const SORTING_COLUMN: FieldType = FieldType::Struct(&[
+    (1, "column_idx", FieldType::Plain),
+    (2, "descending", FieldType::Plain),
+    (3, "nulls_first", FieldType::Plain),
+]);
+
+const ROW_GROUP: FieldType = FieldType::Struct(&[
+    (1, "columns", FieldType::List(&COLUMN_CHUNK)),
+    (2, "total_byte_size", FieldType::Plain),
+    (3, "num_rows", FieldType::Plain),
+    (4, "sorting_columns", FieldType::List(&SORTING_COLUMN)),
+    (5, "file_offset", FieldType::Plain),
+    (6, "total_compressed_size", FieldType::Plain),
+    (7, "ordinal", FieldType::Plain),
+]);
+
+const COLUMN_ORDER: FieldType = FieldType::Struct(&[(1, "TYPE_ORDER", EMPTY)]);
+
+const AES_GCM: FieldType = FieldType::Struct(&[
+    (1, "aad_prefix", FieldType::Binary),
+    (2, "aad_file_unique", FieldType::Binary),
+    (3, "supply_aad_prefix", FieldType::Plain),
+]);
+
+const ENCRYPTION_ALGORITHM: FieldType =
+    FieldType::Struct(&[(1, "AES_GCM_V1", AES_GCM), (2, "AES_GCM_CTR_V1", AES_GCM)]);
+
+/// The FileMetaData struct of the Parquet format, with the fields added up to format 2.11
+const FILE_METADATA: FieldType = FieldType::Struct(&[
+    (1, "version", FieldType::Plain),
+    (2, "schema", FieldType::List(&SCHEMA_ELEMENT)),
+    (3, "num_rows", FieldType::Plain),
+    (4, "row_groups", FieldType::List(&ROW_GROUP)),
+    (5, "key_value_metadata", FieldType::List(&KEY_VALUE)),
+    (6, "created_by", FieldType::String),
+    (7, "column_orders", FieldType::List(&COLUMN_ORDER)),
+    (8, "encryption_algorithm", ENCRYPTION_ALGORITHM),
+    (9, "footer_signing_key_metadata", FieldType::Binary),
+]);
+
+/// Decode the thrift encoded file metadata into JSON, keeping every field that is set.
+/// The metadata is decoded from the wire types rather than with the generated structs,
+/// so that the fields added by newer versions of the format are kept as well: the fields
+/// that are not known are named after their id, e.g. `field_20`.
+pub fn metadata_to_json(metadata: &[u8]) -> Result<Value, PQRSError> {
+    let mut protocol = TCompactInputProtocol::new(metadata);
+    read_value(&mut protocol, TType::Struct, FILE_METADATA).map_err(PQRSError::from)
+}
+
+fn read_value(
+    protocol: &mut dyn TInputProtocol,
+    wire_type: TType,
+    field_type: FieldType,
+) -> thrift::Result<Value> {
+    let value = match wire_type {
+        TType::Bool => json!(protocol.read_bool()?),
+        TType::I08 => json!(protocol.read_i8()?),
+        TType::I16 => json!(protocol.read_i16()?),
+        TType::I32 => {
+            let value = protocol.read_i32()?;
+            match field_type {
+                FieldType::Enum(names)
+                    if value >= 0 && (value as usize) < names.len() =>
+                {
+                    json!(names[value as usize])
+                }
+                _ => json!(value),
+            }
+        }
+        TType::I64 => json!(protocol.read_i64()?),
+        TType::Double => json!(protocol.read_double()?),
+        TType::String => {
+            let bytes = protocol.read_bytes()?;
+            match (field_type, String::from_utf8(bytes)) {
+                (FieldType::String, Ok(string)) => json!(string),
+                (_, Ok(string)) => json!(to_hex(string.as_bytes())),
+                (_, Err(e)) => json!(to_hex(e.as_bytes())),
+            }
+        }
+        TType::Struct => {
+            let fields: &[Field] = match field_type {
+                FieldType::Struct(fields) => fields,
+                _ => &[],
+            };
+            read_struct(protocol, fields)?
+        }
+        TType::List | TType::Set => {
+            let (element_type, size) = if wire_type == TType::List {
+                let list = protocol.read_list_begin()?;
+                (list.element_type, list.size)
+            } else {
+                let set = protocol.read_set_begin()?;
+                (set.element_type, set.size)
+            };
+            let element = match field_type {
+                FieldType::List(element) => *element,
+                _ => FieldType::Plain,
+            };
+            let values = (0..size)
+                .map(|_| read_value(protocol, element_type, element))
+                .collect::<thrift::Result<Vec<Value>>>()?;
+            if wire_type == TType::List {
+                protocol.read_list_end()?;
+            } else {
+                protocol.read_set_end()?;
+            }
+            Value::Array(values)
+        }
+        TType::Map => {
+            let map = protocol.read_map_begin()?;
+            let mut entries = Map::new();
+            for _ in 0..map.size {
+                let key = match map.key_type {
+                    Some(key_type) => read_value(protocol, key_type, FieldType::String)?,
+                    None => Value::Null,
+                };
+                let value = match map.value_type {
+                    Some(value_type) => {
+                        read_value(protocol, value_type, FieldType::Plain)?
+                    }
+                    None => Value::Null,
+                };
+                let key = match key {
+                    Value::String(key) => key,
+                    key => key.to_string(),
+                };
+                entries.insert(key, value);
+            }
+            protocol.read_map_end()?;
+            Value::Object(entries)
+        }
+        wire_type => {
+            return Err(thrift::Error::Protocol(thrift::ProtocolError::new(
+                thrift::ProtocolErrorKind::InvalidData,
+                format!("Unexpected thrift type {:?}", wire_type),
+            )))
+        }
+    };
+
+    Ok(value)
+}
+
+fn read_struct(
+    protocol: &mut dyn TInputProtocol,
+    fields: &[Field],
+) -> thrift::Result<Value> {
+    let mut object = Map::new();
+
+    protocol.read_struct_begin()?;
+    loop {
+        let field = protocol.read_field_begin()?;
+        if field.field_type == TType::Stop {
+            break;
+        }
+        let id = field.id.unwrap_or_default();
+        let (name, field_type) = match fields.iter().find(|(i, _, _)| *i == id) {
+            Some((_, name, field_type)) => (name.to_string(), *field_type),
+            None => (format!("field_{}", id), FieldType::Plain),
+        };
+        let value = read_value(protocol, field.field_type, field_type)?;
+        object.insert(name, value);
+        protocol.read_field_end()?;
+    }
+    protocol.read_struct_end()?;
+
+    Ok(Value::Object(object))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Print the JSON value as an indented tree of `name: value` lines
+pub fn print_tree(value: &Value, indent: usize) {
+    let padding = "  ".repeat(indent);
+    let print_entry = |name: &str, value: &Value| match value {
+        Value::Object(_) | Value::Array(_) => {
+            println!("{}{}:", padding, name);
+            print_tree(value, indent + 1);
+        }
+        Value::String(string) => println!("{}{}: {}", padding, name, string),
+        value => println!("{}{}: {}", padding, name, value),
+    };
+
+    match value {
+        Value::Object(object) => {
+            for (name, value) in object {
+                print_entry(name, value);
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                print_entry(&format!("[{}]", i), value);
+            }
+        }
+        value => println!("{}{}", padding, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn it_reads_the_footer_of_a_truncated_file() {
+        // a file metadata with the version, an empty schema and the number of rows
+        let metadata = vec![0x15, 0x02, 0x19, 0x0c, 0x16, 0x06, 0x00];
+        let mut tail = vec![0x42; 3];
+        tail.extend(&metadata);
+        tail.extend(&(metadata.len() as u32).to_le_bytes());
+        tail.extend(PARQUET_MAGIC);
+
+        let bytes = read_footer(&mut Cursor::new(&tail)).unwrap();
+        assert_eq!(bytes, metadata);
+        assert_eq!(
+            metadata_to_json(&bytes).unwrap(),
+            json!({"version": 1, "schema": [], "num_rows": 3})
+        );
+        assert!(read_footer(&mut Cursor::new(&tail[5..])).is_err());
+    }
+
+    #[test]
+    fn it_keeps_the_unknown_fields() {
+        // the created_by string followed by an unknown i32 field with id 42
+        let metadata = vec![0x68, 0x01, 0x61, 0x05, 0x54, 0x08, 0x00];
+        assert_eq!(
+            metadata_to_json(&metadata).unwrap(),
+            json!({"created_by": "a", "field_42": 4})
+        );
+    }
+}
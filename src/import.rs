@@ -0,0 +1,355 @@
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::InvalidArgument;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::json::reader::infer_json_schema_from_iterator;
+use serde_json::{Map, Number, Value};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// The formats that can be imported into a parquet file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    /// Delimiter separated values, every field is read as a string before conversion
+    Csv,
+    /// Newline delimited JSON, one object per line
+    Json,
+    /// Avro object container files, which are read with their own schema
+    Avro,
+}
+
+/// The settings used to read CSV inputs
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub quote: u8,
+    /// Use the first line as the column names, instead of `column_1`, `column_2`, ...
+    pub has_header: bool,
+    /// The fields equal to this value are read as nulls
+    pub null_token: String,
+}
+
+/// A record read from the input along with the line it starts on, or the reason why the
+/// line could not be read
+pub type Record = (u64, Result<Value, String>);
+
+/// Return an iterator over the records of the input, every record is a JSON object.
+/// Lines that cannot be parsed are returned as errors so that they can be reported.
+/// The names are used for the columns of CSV inputs without a header, if given.
+pub fn read_records(
+    file: File,
+    format: ImportFormat,
+    options: &CsvOptions,
+    names: Option<Vec<String>>,
+) -> Result<Box<dyn Iterator<Item = Record>>, PQRSError> {
+    match format {
+        ImportFormat::Csv => read_csv_records(file, options, names),
+        ImportFormat::Json => Ok(Box::new(read_json_records(file))),
+        ImportFormat::Avro => Err(InvalidArgument(String::from(
+            "Avro files are read in batches, not record by record",
+        ))),
+    }
+}
+
+fn read_csv_records(
+    file: File,
+    options: &CsvOptions,
+    names: Option<Vec<String>>,
+) -> Result<Box<dyn Iterator<Item = Record>>, PQRSError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .has_headers(options.has_header)
+        // records with the wrong number of fields are rejected one by one below
+        .flexible(true)
+        .from_reader(file);
+
+    let mut names: Option<Vec<String>> = if options.has_header {
+        let header = reader.headers().map_err(io::Error::from)?;
+        Some(header.iter().map(String::from).collect())
+    } else {
+        names
+    };
+    let null_token = options.null_token.clone();
+
+    let records = reader.into_records().map(move |record| {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line());
+                return (line, Err(e.to_string()));
+            }
+        };
+        let line = record.position().map_or(0, |p| p.line());
+
+        // without a header or names, the columns are named after the first record
+        let names = names.get_or_insert_with(|| {
+            (1..=record.len())
+                .map(|i| format!("column_{}", i))
+                .collect()
+        });
+        if record.len() != names.len() {
+            let reason =
+                format!("expected {} fields but found {}", names.len(), record.len());
+            return (line, Err(reason));
+        }
+
+        let object: Map<String, Value> = names
+            .iter()
+            .zip(record.iter())
+            .map(|(name, field)| {
+                let value = if field == null_token {
+                    Value::Null
+                } else {
+                    Value::String(field.to_string())
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        (line, Ok(Value::Object(object)))
+    });
+
+    Ok(Box::new(records))
+}
+
+fn read_json_records(file: File) -> impl Iterator<Item = Record> {
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line_number = i as u64 + 1;
+            let line = match line {
+                Ok(line) if line.trim().is_empty() => return None,
+                Ok(line) => line,
+                Err(e) => return Some((line_number, Err(e.to_string()))),
+            };
+
+            let record = match serde_json::from_str::<Value>(&line) {
+                Ok(value) if value.is_object() => Ok(value),
+                Ok(_) => Err(String::from("expected a JSON object")),
+                Err(e) => Err(e.to_string()),
+            };
+            Some((line_number, record))
+        })
+}
+
+/// Infer the schema of the given records. The fields of CSV records are strings, they
+/// are typed as numbers or booleans when all of their values can be read as such.
+pub fn infer_schema(
+    records: &[Value],
+    format: ImportFormat,
+) -> Result<Schema, PQRSError> {
+    if records.is_empty() {
+        return Err(InvalidArgument(String::from(
+            "There are no valid records to infer the schema from",
+        )));
+    }
+
+    let values = records.iter().map(|record| match format {
+        ImportFormat::Csv => Ok(type_csv_record(record)),
+        ImportFormat::Json | ImportFormat::Avro => Ok(record.clone()),
+    });
+    let schema = infer_json_schema_from_iterator(values)?;
+
+    // keep the columns in the order they first appear in, columns that only
+    // contain nulls are written as strings
+    let mut order: Vec<&String> = Vec::new();
+    for record in records.iter().filter_map(|r| r.as_object()) {
+        for name in record.keys() {
+            if !order.contains(&name) {
+                order.push(name);
+            }
+        }
+    }
+    let fields: Vec<Field> = order
+        .iter()
+        .map(|name| match schema.field_with_name(name) {
+            Ok(field) if field.data_type() != &DataType::Null => field.clone(),
+            _ => Field::new(name, DataType::Utf8, true),
+        })
+        .collect();
+
+    Ok(Schema::new(fields))
+}
+
+/// Give the fields of a CSV record the most specific JSON type they can be read as
+fn type_csv_record(record: &Value) -> Value {
+    match record {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        Value::String(s) => type_csv_field(s),
+                        value => value.clone(),
+                    };
+                    (name.clone(), value)
+                })
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+fn type_csv_field(field: &str) -> Value {
+    if let Ok(v) = field.parse::<i64>() {
+        Value::from(v)
+    } else if let Some(v) = field.parse::<f64>().ok().and_then(Number::from_f64) {
+        Value::Number(v)
+    } else if let Ok(v) = field.parse::<bool>() {
+        Value::Bool(v)
+    } else {
+        Value::String(field.to_string())
+    }
+}
+
+/// Convert the record to the given schema, so that every value can be decoded to the
+/// type of its column. Columns that are not part of the schema are dropped, and an
+/// error describes the first value that does not fit the schema.
+pub fn conform_record(record: &Value, schema: &Schema) -> Result<Value, String> {
+    let record = record
+        .as_object()
+        .ok_or_else(|| String::from("expected a JSON object"))?;
+    conform_fields(record, schema.fields(), "").map(Value::Object)
+}
+
+fn conform_fields(
+    record: &Map<String, Value>,
+    fields: &[Field],
+    prefix: &str,
+) -> Result<Map<String, Value>, String> {
+    let mut conformed = Map::new();
+    for field in fields {
+        let path = format!("{}{}", prefix, field.name());
+        let value = match record.get(field.name()).unwrap_or(&Value::Null) {
+            Value::Null if !field.is_nullable() => {
+                return Err(format!("missing value for the required column {}", path))
+            }
+            Value::Null => Value::Null,
+            value => conform(value, field.data_type(), &path)?,
+        };
+        conformed.insert(field.name().clone(), value);
+    }
+    Ok(conformed)
+}
+
+fn conform(value: &Value, data_type: &DataType, path: &str) -> Result<Value, String> {
+    let invalid = || {
+        format!(
+            "invalid value {} for the {:?} column {}",
+            value, data_type, path
+        )
+    };
+
+    let conformed = match (value, data_type) {
+        (Value::Null, _) => Some(Value::Null),
+        (Value::Bool(_), DataType::Boolean) => Some(value.clone()),
+        (Value::String(s), DataType::Boolean) => s.parse::<bool>().ok().map(Value::Bool),
+        (Value::Number(n), t) if is_integer(t) => {
+            Some(value.clone()).filter(|_| !n.is_f64())
+        }
+        (Value::String(s), t) if is_integer(t) => s.parse::<i64>().ok().map(Value::from),
+        (Value::Number(_), t) if is_float(t) => Some(value.clone()),
+        (Value::String(s), t) if is_float(t) => s
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        (Value::String(_), DataType::Utf8) | (Value::String(_), DataType::LargeUtf8) => {
+            Some(value.clone())
+        }
+        // other values are kept as their JSON text, e.g. a column of numbers and strings
+        (value, DataType::Utf8) | (value, DataType::LargeUtf8) => {
+            Some(Value::String(value.to_string()))
+        }
+        (Value::Object(record), DataType::Struct(fields)) => {
+            let prefix = format!("{}.", path);
+            return conform_fields(record, fields, &prefix).map(Value::Object);
+        }
+        (Value::Array(values), DataType::List(field))
+        | (Value::Array(values), DataType::LargeList(field)) => {
+            return values
+                .iter()
+                .map(|value| conform(value, field.data_type(), path))
+                .collect::<Result<Vec<Value>, String>>()
+                .map(Value::Array);
+        }
+        _ => None,
+    };
+
+    conformed.ok_or_else(invalid)
+}
+
+fn is_integer(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+    )
+}
+
+fn is_float(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Float16 | DataType::Float32 | DataType::Float64
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_infers_csv_types() {
+        let records = vec![
+            json!({"id": "1", "price": "1.5", "active": "true", "name": "a"}),
+            json!({"id": "2", "price": "2", "active": "false", "name": null}),
+        ];
+        let schema = infer_schema(&records, ImportFormat::Csv).unwrap();
+
+        let types: Vec<(&str, &DataType)> = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type()))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("id", &DataType::Int64),
+                ("price", &DataType::Float64),
+                ("active", &DataType::Boolean),
+                ("name", &DataType::Utf8),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_conforms_records_to_the_schema() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("code", DataType::Utf8, true),
+        ]);
+
+        // strings keep their original text, even when they look like numbers
+        let record = json!({"id": "7", "code": "007", "extra": 1});
+        assert_eq!(
+            conform_record(&record, &schema),
+            Ok(json!({"id": 7, "code": "007"}))
+        );
+
+        let record = json!({"id": 7, "code": 42});
+        assert_eq!(
+            conform_record(&record, &schema),
+            Ok(json!({"id": 7, "code": "42"}))
+        );
+
+        assert!(conform_record(&json!({"id": "seven"}), &schema).is_err());
+        assert!(conform_record(&json!({"code": "007"}), &schema).is_err());
+    }
+}
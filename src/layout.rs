@@ -0,0 +1,525 @@
+use crate::errors::PQRSError;
+use crate::footer::{metadata_to_json, read_footer, PARQUET_MAGIC};
+use crate::pages::{chunk_start, read_raw_page_headers};
+use crate::report::Entry;
+use log::warn;
+use parquet::basic::PageType;
+use parquet_format::FileMetaData;
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use thrift::protocol::{TCompactInputProtocol, TInputProtocol, TType};
+
+/// The number of bytes read to look for a bloom filter header, which is a few bytes long
+const BLOOM_FILTER_HEADER_MAX_SIZE: u64 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionKind {
+    Magic,
+    ColumnChunk,
+    Page(PageType),
+    ColumnIndex,
+    OffsetIndex,
+    BloomFilter,
+    FileMetadata,
+    /// The length of the file metadata and the magic number at the end of the file
+    Footer,
+    /// Bytes that are not referenced by the metadata
+    Gap,
+    /// Bytes referenced by two regions
+    Overlap,
+}
+
+impl fmt::Display for RegionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RegionKind::Magic => "magic",
+            RegionKind::ColumnChunk => "column chunk",
+            RegionKind::Page(PageType::DATA_PAGE) => "data page",
+            RegionKind::Page(PageType::DATA_PAGE_V2) => "data page v2",
+            RegionKind::Page(PageType::DICTIONARY_PAGE) => "dictionary page",
+            RegionKind::Page(PageType::INDEX_PAGE) => "index page",
+            RegionKind::ColumnIndex => "column index",
+            RegionKind::OffsetIndex => "offset index",
+            RegionKind::BloomFilter => "bloom filter",
+            RegionKind::FileMetadata => "file metadata",
+            RegionKind::Footer => "footer",
+            RegionKind::Gap => "gap",
+            RegionKind::Overlap => "overlap",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A range of bytes of the file, from start included to end excluded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub kind: RegionKind,
+    pub start: u64,
+    pub end: u64,
+    pub row_group: Option<usize>,
+    pub column: Option<String>,
+    pub note: Option<String>,
+}
+
+impl Region {
+    fn new(kind: RegionKind, start: u64, end: u64) -> Self {
+        Region {
+            kind,
+            start,
+            end,
+            row_group: None,
+            column: None,
+            note: None,
+        }
+    }
+
+    fn of_chunk(mut self, row_group: usize, column: &str) -> Self {
+        self.row_group = Some(row_group);
+        self.column = Some(column.to_string());
+        self
+    }
+
+    pub fn size(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Whether the region is an issue of the layout rather than a part of the file
+    pub fn is_issue(&self) -> bool {
+        matches!(self.kind, RegionKind::Gap | RegionKind::Overlap)
+    }
+
+    /// Whether the region is nested in the previous column chunk
+    pub fn is_page(&self) -> bool {
+        matches!(self.kind, RegionKind::Page(_))
+    }
+
+    fn label(&self) -> String {
+        match (&self.column, self.row_group) {
+            (Some(column), Some(row_group)) => {
+                format!(
+                    "the {} of column {} in row group {}",
+                    self.kind, column, row_group
+                )
+            }
+            _ => format!("the {}", self.kind),
+        }
+    }
+
+    pub fn entry(&self, file_name: &str) -> Entry {
+        vec![
+            ("path", json!(file_name)),
+            ("kind", json!(self.kind.to_string())),
+            ("start", json!(self.start)),
+            ("end", json!(self.end)),
+            ("size", json!(self.size())),
+            ("row_group", json!(self.row_group)),
+            ("column", json!(self.column)),
+            ("note", json!(self.note)),
+        ]
+    }
+}
+
+/// Read the thrift encoded file metadata at the end of the file, along with its raw bytes
+pub fn read_file_metadata(file: &File) -> Result<(FileMetaData, Vec<u8>), PQRSError> {
+    let metadata = read_footer(&mut BufReader::new(file.try_clone()?))?;
+    let mut protocol = TCompactInputProtocol::new(metadata.as_slice());
+    let file_metadata = FileMetaData::read_from_in_protocol(&mut protocol)?;
+
+    Ok((file_metadata, metadata))
+}
+
+/// Return the offset and length of the bloom filter of a column chunk, if any. These
+/// fields are newer than the metadata structs used by pqrs, so they are read from the
+/// generic decoding of the footer. Some writers only set the offset.
+fn bloom_filter_location(
+    document: &Value,
+    row_group: usize,
+    column: usize,
+) -> Option<(u64, Option<u64>)> {
+    let meta_data = &document["row_groups"][row_group]["columns"][column]["meta_data"];
+    let offset = meta_data["bloom_filter_offset"].as_u64()?;
+    Some((offset, meta_data["bloom_filter_length"].as_u64()))
+}
+
+/// Return the regions of the file in the order of their offsets, the pages of a column
+/// chunk following it. The bytes that are not part of any region are returned as gaps,
+/// and the bytes that are part of two regions as overlaps.
+pub fn read_layout(file: &File) -> Result<Vec<Region>, PQRSError> {
+    let file_size = file.metadata()?.len();
+    let (metadata, raw_metadata) = read_file_metadata(file)?;
+    let document = metadata_to_json(&raw_metadata)?;
+    let metadata_length = raw_metadata.len() as u64;
+
+    let mut magic = Region::new(RegionKind::Magic, 0, PARQUET_MAGIC.len() as u64);
+    let mut bytes = [0; 4];
+    read_at(file, 0, &mut bytes)?;
+    if bytes != PARQUET_MAGIC {
+        magic.note = Some(String::from("not the Parquet magic number"));
+    }
+
+    // the regions of the file, along with the pages of the column chunks
+    let mut regions = vec![(magic, Vec::new())];
+    for (row_group, group) in metadata.row_groups.iter().enumerate() {
+        for (index, chunk) in group.columns.iter().enumerate() {
+            let column = match &chunk.meta_data {
+                Some(meta_data) => meta_data.path_in_schema.join("."),
+                None => String::from("(unknown)"),
+            };
+            if let Some(path) = &chunk.file_path {
+                warn!(
+                    "The column {} of row group {} is stored in the file {}",
+                    column, row_group, path
+                );
+            } else if let Some(meta_data) = &chunk.meta_data {
+                let start = chunk_start(
+                    meta_data.dictionary_page_offset,
+                    meta_data.data_page_offset,
+                );
+                let end = start + meta_data.total_compressed_size.max(0) as u64;
+                let mut region = Region::new(RegionKind::ColumnChunk, start, end)
+                    .of_chunk(row_group, &column);
+
+                let pages = match read_raw_page_headers(file, start, end.min(file_size)) {
+                    Ok(headers) => headers
+                        .iter()
+                        .map(|(offset, header_size, header)| {
+                            let page_end =
+                                offset + header_size + header.compressed_page_size as u64;
+                            let mut page = Region::new(
+                                RegionKind::Page(PageType::from(header.type_)),
+                                *offset,
+                                page_end,
+                            )
+                            .of_chunk(row_group, &column);
+                            if page_end > end {
+                                page.note =
+                                    Some(String::from("ends after the column chunk"));
+                            }
+                            page
+                        })
+                        .collect(),
+                    Err(e) => {
+                        region.note =
+                            Some(format!("unable to read the page headers: {}", e));
+                        Vec::new()
+                    }
+                };
+                regions.push((region, pages));
+            }
+
+            let indexes = [
+                (
+                    RegionKind::ColumnIndex,
+                    chunk.column_index_offset,
+                    chunk.column_index_length,
+                ),
+                (
+                    RegionKind::OffsetIndex,
+                    chunk.offset_index_offset,
+                    chunk.offset_index_length,
+                ),
+            ];
+            for (kind, offset, length) in indexes.iter() {
+                if let (Some(offset), Some(length)) = (offset, length) {
+                    let (start, length) = (*offset as u64, *length as u64);
+                    let region = Region::new(*kind, start, start + length)
+                        .of_chunk(row_group, &column);
+                    regions.push((region, Vec::new()));
+                }
+            }
+
+            // without a length the size is read from the header of the bloom filter
+            if let Some((start, length)) =
+                bloom_filter_location(&document, row_group, index)
+            {
+                let mut region = Region::new(RegionKind::BloomFilter, start, start)
+                    .of_chunk(row_group, &column);
+                match length {
+                    Some(length) => region.end = start + length,
+                    None => match read_bloom_filter_size(file, start, file_size)? {
+                        Some(size) => region.end = start + size,
+                        None => {
+                            region.note = Some(String::from(
+                                "unable to read the bloom filter header",
+                            ))
+                        }
+                    },
+                }
+                regions.push((region, Vec::new()));
+            }
+        }
+    }
+
+    let metadata_start = file_size - 8 - metadata_length;
+    regions.push((
+        Region::new(RegionKind::FileMetadata, metadata_start, file_size - 8),
+        Vec::new(),
+    ));
+    regions.push((
+        Region::new(RegionKind::Footer, file_size - 8, file_size),
+        Vec::new(),
+    ));
+    regions.sort_by_key(|(region, _)| (region.start, region.end));
+
+    let mut layout = Vec::new();
+    let mut position = 0;
+    let mut previous: Option<String> = None;
+    for (mut region, pages) in regions {
+        if region.start > position {
+            layout.push(read_gap(file, position, region.start)?);
+        } else if region.start < position {
+            let mut overlap =
+                Region::new(RegionKind::Overlap, region.start, position.min(region.end));
+            overlap.note = Some(format!(
+                "{} overlaps {}",
+                region.label(),
+                previous.clone().unwrap_or_default()
+            ));
+            layout.push(overlap);
+        }
+        if region.end > file_size && region.note.is_none() {
+            region.note = Some(String::from("ends after the end of the file"));
+        }
+
+        if region.end > position {
+            position = region.end;
+            previous = Some(region.label());
+        }
+        layout.push(region);
+        layout.extend(pages);
+    }
+
+    Ok(layout)
+}
+
+/// Return the gap between two regions of the file, noted as a possible bloom filter when
+/// it starts with what looks like a bloom filter header that the metadata does not reference
+fn read_gap(file: &File, start: u64, end: u64) -> Result<Region, PQRSError> {
+    let mut gap = Region::new(RegionKind::Gap, start, end);
+    let size = read_bloom_filter_size(file, start, end)?;
+    if let Some(size) = size.filter(|size| start + size <= end) {
+        gap.note = Some(format!("possible bloom filter of {} bytes", size));
+    }
+
+    Ok(gap)
+}
+
+/// Return the size of the bloom filter at the given offset, read from its header
+fn read_bloom_filter_size(
+    file: &File,
+    start: u64,
+    end: u64,
+) -> Result<Option<u64>, PQRSError> {
+    if start >= end {
+        return Ok(None);
+    }
+    let mut header = vec![0; (end - start).min(BLOOM_FILTER_HEADER_MAX_SIZE) as usize];
+    read_at(file, start, &mut header)?;
+
+    Ok(bloom_filter_size(&header))
+}
+
+fn read_at(file: &File, offset: u64, buffer: &mut [u8]) -> Result<(), PQRSError> {
+    let mut file = file.try_clone()?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buffer)?;
+    Ok(())
+}
+
+/// Return the size of the bloom filter, header included, when the data starts with a
+/// bloom filter header
+fn bloom_filter_size(data: &[u8]) -> Option<u64> {
+    let mut remaining = data;
+    let num_bytes = {
+        let mut protocol = TCompactInputProtocol::new(&mut remaining);
+        read_bloom_filter_header(&mut protocol).ok()??
+    };
+    let header_size = (data.len() - remaining.len()) as u64;
+
+    // the size of the bitset is always a power of two
+    if num_bytes > 0 && (num_bytes as u32).is_power_of_two() {
+        Some(header_size + num_bytes as u64)
+    } else {
+        None
+    }
+}
+
+/// Read a bloom filter header, returning the size of its bitset. The header is made up of
+/// the size of the bitset and of the algorithm, hash and compression unions.
+fn read_bloom_filter_header(
+    protocol: &mut dyn TInputProtocol,
+) -> thrift::Result<Option<i32>> {
+    let mut num_bytes = None;
+    let mut fields = Vec::new();
+
+    protocol.read_struct_begin()?;
+    loop {
+        let field = protocol.read_field_begin()?;
+        match (field.field_type, field.id) {
+            (TType::Stop, _) => break,
+            (TType::I32, Some(1)) => num_bytes = Some(protocol.read_i32()?),
+            (TType::Struct, Some(id)) if (2..=4).contains(&id) => {
+                protocol.skip(TType::Struct)?
+            }
+            _ => return Ok(None),
+        }
+        fields.push(field.id);
+        protocol.read_field_end()?;
+    }
+    protocol.read_struct_end()?;
+
+    // the algorithm and the hash are required
+    if fields.contains(&Some(2)) && fields.contains(&Some(3)) {
+        Ok(num_bytes)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The fill colors of the regions in the SVG map
+fn color(kind: RegionKind) -> &'static str {
+    match kind {
+        RegionKind::Magic | RegionKind::Footer => "#555555",
+        RegionKind::ColumnChunk => "#4e79a7",
+        RegionKind::Page(PageType::DICTIONARY_PAGE) => "#f28e2b",
+        RegionKind::Page(_) => "#76b7b2",
+        RegionKind::ColumnIndex | RegionKind::OffsetIndex => "#59a14f",
+        RegionKind::BloomFilter => "#b07aa1",
+        RegionKind::FileMetadata => "#9c755f",
+        RegionKind::Gap | RegionKind::Overlap => "#e15759",
+    }
+}
+
+/// Draw the regions as an SVG map of the file: the regions on a first row, the pages of the
+/// column chunks below them and the gaps and overlaps on a last row
+pub fn to_svg(regions: &[Region], file_size: u64) -> String {
+    const WIDTH: f64 = 1200.0;
+    const ROW_HEIGHT: f64 = 40.0;
+
+    let scale = WIDTH / file_size.max(1) as f64;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"12\">\n",
+        WIDTH,
+        ROW_HEIGHT * 3.0 + 20.0
+    );
+    for (row, name) in ["regions", "pages", "issues"].iter().enumerate() {
+        svg.push_str(&format!(
+            "  <text x=\"0\" y=\"{}\">{}</text>\n",
+            ROW_HEIGHT * (row as f64 + 1.0) - 2.0,
+            name
+        ));
+    }
+
+    for region in regions {
+        let row = if region.is_issue() {
+            2.0
+        } else if region.is_page() {
+            1.0
+        } else {
+            0.0
+        };
+        let mut title = format!(
+            "{} [{}, {}) {} bytes",
+            region.label(),
+            region.start,
+            region.end,
+            region.size()
+        );
+        if let Some(note) = &region.note {
+            title.push_str(&format!(": {}", note));
+        }
+        svg.push_str(&format!(
+            "  <rect x=\"{:.2}\" y=\"{}\" width=\"{:.2}\" height=\"{}\" fill=\"{}\" stroke=\"white\" stroke-width=\"0.5\"><title>{}</title></rect>\n",
+            region.start as f64 * scale,
+            ROW_HEIGHT * row + 2.0,
+            (region.size() as f64 * scale).max(1.0),
+            ROW_HEIGHT - 16.0,
+            color(region.kind),
+            escape(&title)
+        ));
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use thrift::protocol::{
+        TCompactOutputProtocol, TFieldIdentifier, TOutputProtocol, TStructIdentifier,
+    };
+
+    fn bloom_filter_header(num_bytes: i32) -> Vec<u8> {
+        let mut data = Vec::new();
+        {
+            let mut protocol = TCompactOutputProtocol::new(&mut data);
+            let empty_struct = |protocol: &mut TCompactOutputProtocol<&mut Vec<u8>>,
+                                id: i16| {
+                protocol
+                    .write_field_begin(&TFieldIdentifier::new("", TType::Struct, id))
+                    .unwrap();
+                protocol
+                    .write_struct_begin(&TStructIdentifier::new("BloomFilterHeader"))
+                    .unwrap();
+                protocol.write_field_stop().unwrap();
+                protocol.write_struct_end().unwrap();
+                protocol.write_field_end().unwrap();
+            };
+            protocol
+                .write_struct_begin(&TStructIdentifier::new("BloomFilterHeader"))
+                .unwrap();
+            protocol
+                .write_field_begin(&TFieldIdentifier::new("", TType::I32, 1))
+                .unwrap();
+            protocol.write_i32(num_bytes).unwrap();
+            protocol.write_field_end().unwrap();
+            empty_struct(&mut protocol, 2);
+            empty_struct(&mut protocol, 3);
+            protocol.write_field_stop().unwrap();
+            protocol.write_struct_end().unwrap();
+            protocol.flush().unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn it_finds_bloom_filters() {
+        let header = bloom_filter_header(32);
+        assert_eq!(bloom_filter_size(&header), Some(header.len() as u64 + 32));
+        assert_eq!(bloom_filter_size(&bloom_filter_header(33)), None);
+        assert_eq!(bloom_filter_size(&[0, 0, 0, 0]), None);
+        assert_eq!(bloom_filter_size(&[0xff, 0x12]), None);
+    }
+
+    #[test]
+    fn it_notes_possible_bloom_filters_in_gaps() {
+        let mut data = bloom_filter_header(32);
+        let size = data.len() as u64 + 32;
+        data.extend(vec![0; 32]);
+        data.extend(vec![0xab; 10]);
+        let mut file = tempfile::tempfile().unwrap();
+        std::io::Write::write_all(&mut file, &data).unwrap();
+
+        // the bloom filter is not referenced by the metadata, so it is still a gap
+        let gap = read_gap(&file, 0, data.len() as u64).unwrap();
+        assert_eq!(gap.kind, RegionKind::Gap);
+        assert_eq!((gap.start, gap.end), (0, data.len() as u64));
+        assert_eq!(
+            gap.note,
+            Some(format!("possible bloom filter of {} bytes", size))
+        );
+
+        let gap = read_gap(&file, size, data.len() as u64).unwrap();
+        assert_eq!(gap.note, None);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::errors::PQRSError;
+use crate::stats::{decimal_from_bytes, StatValue};
+use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+use parquet::column::reader::{get_typed_column_reader, ColumnReader};
+use parquet::data_type::{
+    BoolType, ByteArrayType, DoubleType, FixedLenByteArrayType, FloatType, Int32Type,
+    Int64Type, Int96Type,
+};
+use parquet::schema::types::{ColumnDescriptor, Type};
+use std::fmt;
+
+/// The number of levels read at once from the column reader
+static LEVELS_BATCH_SIZE: usize = 1024;
+
+/// A single entry of a column: its repetition and definition levels, and its value
+/// when the definition level is the maximum one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triple {
+    pub rep: i16,
+    pub def: i16,
+    pub value: Option<String>,
+}
+
+/// A field on the path from the root to a leaf column, with the levels it adds
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathLevel {
+    pub name: String,
+    pub repetition: Repetition,
+    /// The definition level of the values for which this field is defined
+    pub def: i16,
+    /// The repetition level of the new elements of this field, for repeated fields
+    pub rep: Option<i16>,
+}
+
+/// Return the fields on the path to the leaf column, the root of the schema excluded
+pub fn get_path_levels(root: &Type, column: &ColumnDescriptor) -> Vec<PathLevel> {
+    let mut levels = Vec::new();
+    let (mut def, mut rep) = (0, 0);
+    let mut field = root;
+
+    for name in column.path().parts() {
+        let child = match field.get_fields().iter().find(|f| f.name() == name) {
+            Some(child) => child,
+            None => break,
+        };
+        let info = child.get_basic_info();
+        let repetition = if info.has_repetition() {
+            info.repetition()
+        } else {
+            Repetition::REQUIRED
+        };
+        if repetition != Repetition::REQUIRED {
+            def += 1;
+        }
+        if repetition == Repetition::REPEATED {
+            rep += 1;
+        }
+
+        levels.push(PathLevel {
+            name: name.clone(),
+            repetition,
+            def,
+            rep: if repetition == Repetition::REPEATED {
+                Some(rep)
+            } else {
+                None
+            },
+        });
+        field = child;
+    }
+
+    levels
+}
+
+/// Read the levels and values of the column chunk, stopping after the given number of
+/// records if any. The values are formatted according to the logical type of the column.
+pub fn read_triples(
+    reader: ColumnReader,
+    column: &ColumnDescriptor,
+    limit: Option<usize>,
+) -> Result<Vec<Triple>, PQRSError> {
+    // the bytes of strings are quoted, to tell them apart from nulls and numbers
+    let is_string = column.physical_type() == PhysicalType::BYTE_ARRAY
+        && matches!(
+            column.converted_type(),
+            ConvertedType::UTF8 | ConvertedType::ENUM | ConvertedType::JSON
+        );
+    let is_decimal = column.converted_type() == ConvertedType::DECIMAL;
+    let unsigned = matches!(
+        column.converted_type(),
+        ConvertedType::UINT_8
+            | ConvertedType::UINT_16
+            | ConvertedType::UINT_32
+            | ConvertedType::UINT_64
+    );
+    let bytes = |data: &[u8]| {
+        if is_string {
+            format!("{:?}", String::from_utf8_lossy(data))
+        } else if is_decimal {
+            StatValue::Int(decimal_from_bytes(data)).display(column)
+        } else {
+            StatValue::Bytes(data.to_vec()).display(column)
+        }
+    };
+
+    macro_rules! read {
+        ($reader:expr, $data_type:ty, $format:expr) => {{
+            let mut reader = get_typed_column_reader::<$data_type>($reader);
+            let format = $format;
+            let mut triples = Vec::new();
+            let mut records = 0;
+            let mut values = vec![Default::default(); LEVELS_BATCH_SIZE];
+            let mut def_levels = vec![0; LEVELS_BATCH_SIZE];
+            let mut rep_levels = vec![0; LEVELS_BATCH_SIZE];
+            loop {
+                let (values_read, levels_read) = reader.read_batch(
+                    LEVELS_BATCH_SIZE,
+                    Some(&mut def_levels),
+                    Some(&mut rep_levels),
+                    &mut values,
+                )?;
+                // no levels are read for the columns that are required and not repeated
+                let count = levels_read.max(values_read);
+                if count == 0 {
+                    break;
+                }
+
+                // the values are only stored for the levels that are fully defined
+                let mut next_value = 0;
+                for i in 0..count {
+                    let (rep, def) = (rep_levels[i], def_levels[i]);
+                    if rep == 0 {
+                        if limit.map_or(false, |l| records >= l) {
+                            return Ok(triples);
+                        }
+                        records += 1;
+                    }
+                    let value = if def == column.max_def_level() {
+                        next_value += 1;
+                        Some(format(&values[next_value - 1]))
+                    } else {
+                        None
+                    };
+                    triples.push(Triple { rep, def, value });
+                }
+            }
+            triples
+        }};
+    }
+
+    let triples = match reader {
+        ColumnReader::BoolColumnReader(r) => read!(r, BoolType, |v: &bool| v.to_string()),
+        ColumnReader::Int32ColumnReader(r) => read!(r, Int32Type, |v: &i32| {
+            let value = if unsigned {
+                *v as u32 as i128
+            } else {
+                *v as i128
+            };
+            StatValue::Int(value).display(column)
+        }),
+        ColumnReader::Int64ColumnReader(r) => read!(r, Int64Type, |v: &i64| {
+            let value = if unsigned {
+                *v as u64 as i128
+            } else {
+                *v as i128
+            };
+            StatValue::Int(value).display(column)
+        }),
+        ColumnReader::Int96ColumnReader(r) => {
+            read!(r, Int96Type, |v: &parquet::data_type::Int96| format!(
+                "{:?}",
+                v.data()
+            ))
+        }
+        ColumnReader::FloatColumnReader(r) => {
+            read!(r, FloatType, |v: &f32| v.to_string())
+        }
+        ColumnReader::DoubleColumnReader(r) => {
+            read!(r, DoubleType, |v: &f64| v.to_string())
+        }
+        ColumnReader::ByteArrayColumnReader(r) => {
+            read!(r, ByteArrayType, |v: &parquet::data_type::ByteArray| bytes(
+                v.data()
+            ))
+        }
+        ColumnReader::FixedLenByteArrayColumnReader(r) => {
+            read!(
+                r,
+                FixedLenByteArrayType,
+                |v: &<FixedLenByteArrayType as parquet::data_type::DataType>::T| bytes(
+                    v.data()
+                )
+            )
+        }
+    };
+
+    Ok(triples)
+}
+
+/// The value of a leaf column within a record, with one level of lists per repeated
+/// field on the path to the column
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    List(Vec<Node>),
+    /// The value or one of the fields on the path to it is null
+    Null,
+    Value(String),
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::List(nodes) => {
+                write!(f, "[")?;
+                for (i, node) in nodes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", node)?;
+                }
+                write!(f, "]")
+            }
+            Node::Null => write!(f, "null"),
+            Node::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Assemble the triples into records, the way the record reader does: a repetition level
+/// of 0 starts a new record and any other level `r` adds an element to the list of the
+/// `r`-th repeated field, a definition level lower than the one of a repeated field
+/// means that its list is empty or null.
+pub fn assemble_records(triples: &[Triple], levels: &[PathLevel]) -> Vec<Node> {
+    // the definition levels at which the lists of the repeated fields have elements
+    let list_levels: Vec<i16> = levels
+        .iter()
+        .filter(|l| l.rep.is_some())
+        .map(|l| l.def)
+        .collect();
+    let max_def = levels.last().map_or(0, |l| l.def);
+
+    let mut records: Vec<Node> = Vec::new();
+    for triple in triples {
+        let leaf = |def: i16| match &triple.value {
+            Some(value) if def == max_def => Node::Value(value.clone()),
+            _ => Node::Null,
+        };
+
+        if list_levels.is_empty() {
+            records.push(leaf(triple.def));
+            continue;
+        }
+        if triple.rep == 0 {
+            records.push(Node::List(Vec::new()));
+        }
+        let mut node = match records.last_mut() {
+            Some(node) => node,
+            None => continue,
+        };
+
+        for (depth, list_def) in list_levels.iter().enumerate() {
+            // a list without elements, or one of the fields before it is null
+            if triple.def < *list_def {
+                if triple.def < list_def - 1 {
+                    *node = Node::Null;
+                }
+                break;
+            }
+
+            let list = match node {
+                Node::List(list) => list,
+                _ => break,
+            };
+            // the lists before the repetition level continue with their last element,
+            // the others get a new one
+            if depth as i16 + 1 >= triple.rep {
+                let is_leaf = depth + 1 == list_levels.len();
+                list.push(if is_leaf {
+                    leaf(triple.def)
+                } else {
+                    Node::List(Vec::new())
+                });
+            }
+            node = match list.last_mut() {
+                Some(node) => node,
+                None => break,
+            };
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(defs: &[(i16, Option<i16>)]) -> Vec<PathLevel> {
+        defs.iter()
+            .map(|(def, rep)| PathLevel {
+                name: String::new(),
+                repetition: if rep.is_some() {
+                    Repetition::REPEATED
+                } else {
+                    Repetition::OPTIONAL
+                },
+                def: *def,
+                rep: *rep,
+            })
+            .collect()
+    }
+
+    fn triple(rep: i16, def: i16, value: Option<&str>) -> Triple {
+        Triple {
+            rep,
+            def,
+            value: value.map(String::from),
+        }
+    }
+
+    #[test]
+    fn it_assembles_lists() {
+        // optional group city (LIST) { repeated group bag { optional element } }
+        let levels = levels(&[(1, None), (2, Some(1)), (3, None)]);
+        let triples = vec![
+            triple(0, 3, Some("a")),
+            triple(1, 2, None),
+            triple(1, 3, Some("b")),
+            triple(0, 1, None),
+            triple(0, 0, None),
+        ];
+
+        let records: Vec<String> = assemble_records(&triples, &levels)
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(records, vec!["[a, null, b]", "[]", "null"]);
+    }
+
+    #[test]
+    fn it_assembles_nested_lists() {
+        let levels = levels(&[(1, Some(1)), (2, Some(2))]);
+        let triples = vec![
+            triple(0, 2, Some("1")),
+            triple(2, 2, Some("2")),
+            triple(1, 1, None),
+            triple(1, 2, Some("3")),
+        ];
+
+        let records: Vec<String> = assemble_records(&triples, &levels)
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(records, vec!["[[1, 2], [], [3]]"]);
+    }
+}
@@ -4,10 +4,27 @@ use env_logger::Env;
 
 use crate::errors::PQRSError;
 
+mod avro;
 mod command;
 mod commands;
+mod ddl;
+mod dictionary;
+mod diff;
 mod errors;
+mod expression;
+mod footer;
+mod import;
+mod layout;
+mod levels;
+mod output;
+mod pages;
+mod profile;
+mod query;
+mod report;
+mod stats;
+mod units;
 mod utils;
+mod writer;
 
 fn main() -> Result<(), PQRSError> {
     let matches = App::new("pqrs")
@@ -23,6 +40,7 @@ fn main() -> Result<(), PQRSError> {
                 .global(true)
                 .help("Show debug output"),
         )
+        .arg(report::MetadataFormat::arg())
         .subcommands(vec![
             commands::cat::CatCommand::command(),
             commands::schema::SchemaCommand::command(),
@@ -31,6 +49,17 @@ fn main() -> Result<(), PQRSError> {
             commands::size::SizeCommand::command(),
             commands::sample::SampleCommand::command(),
             commands::merge::MergeCommand::command(),
+            commands::query::QueryCommand::command(),
+            commands::stats::StatsCommand::command(),
+            commands::profile::ProfileCommand::command(),
+            commands::convert::ConvertCommand::command(),
+            commands::import::ImportCommand::command(),
+            commands::schema_diff::SchemaDiffCommand::command(),
+            commands::pages::PagesCommand::command(),
+            commands::dictionary::DictionaryCommand::command(),
+            commands::levels::LevelsCommand::command(),
+            commands::layout::LayoutCommand::command(),
+            commands::footer::FooterCommand::command(),
         ])
         .get_matches();
 
@@ -0,0 +1,391 @@
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::SchemaMismatch;
+use crate::expression;
+use clap::{Arg, ArgMatches, ErrorKind};
+use parquet::basic::{ConvertedType, Repetition};
+use parquet::record::{Field, Row};
+use parquet::schema::types::Type;
+use serde_json::{Map, Number, Value};
+use std::fmt;
+use std::io::{self, BufWriter, Stdout, Write};
+
+/// The formats in which the records of a parquet file can be printed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// The json-like format produced by the `Display` implementation of `Row`
+    Default,
+    /// JSON lines, one object per record
+    Json,
+    /// Delimiter separated values with RFC 4180 style quoting
+    Csv,
+    /// Tab separated values with backslash escaping
+    Tsv,
+}
+
+/// The output settings shared by all the commands that print records
+#[derive(Clone, Copy)]
+pub struct OutputFormat {
+    pub format: Format,
+    pub delimiter: char,
+}
+
+impl OutputFormat {
+    /// Return the clap arguments used to configure the output format
+    pub(crate) fn args() -> Vec<Arg<'static, 'static>> {
+        vec![
+            Arg::with_name("json")
+                .long("json")
+                .short("j")
+                .takes_value(false)
+                .required(false)
+                .conflicts_with("format")
+                .help("Use JSON lines format for printing"),
+            Arg::with_name("format")
+                .long("format")
+                .short("f")
+                .takes_value(true)
+                .required(false)
+                .possible_values(&["default", "json", "csv", "tsv"])
+                .help("The format to use for printing records"),
+            Arg::with_name("delimiter")
+                .long("delimiter")
+                .takes_value(true)
+                .required(false)
+                .default_value(",")
+                .validator(validate_delimiter)
+                .help("The field delimiter to use with the csv format"),
+        ]
+    }
+
+    pub(crate) fn new(matches: &ArgMatches) -> Self {
+        let format = match matches.value_of("format") {
+            Some("json") => Format::Json,
+            Some("csv") => Format::Csv,
+            Some("tsv") => Format::Tsv,
+            _ if matches.is_present("json") => Format::Json,
+            _ => Format::Default,
+        };
+        // the delimiter only applies to the csv format, it has a default value so
+        // clap cannot tell whether it was given
+        if matches.occurrences_of("delimiter") > 0 && format != Format::Csv {
+            clap::Error::with_description(
+                "The argument '--delimiter' can only be used with '--format csv'",
+                ErrorKind::ArgumentConflict,
+            )
+            .exit();
+        }
+
+        // the validator makes sure that the delimiter is a single character
+        let delimiter = matches
+            .value_of("delimiter")
+            .and_then(|d| d.chars().next())
+            .unwrap_or(',');
+
+        Self { format, delimiter }
+    }
+}
+
+impl fmt::Debug for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.format)?;
+        if self.format == Format::Csv {
+            write!(f, " (delimiter: {:?})", self.delimiter)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Make sure that the given delimiter is exactly one character long
+fn validate_delimiter(delimiter: String) -> Result<(), String> {
+    if delimiter.chars().count() == 1 {
+        Ok(())
+    } else {
+        Err(format!(
+            "The delimiter must be a single character, got: {:?}",
+            delimiter
+        ))
+    }
+}
+
+/// Prints records in the requested format, writing a header row for delimited formats
+/// before the first record. All the records of delimited formats must have the same columns.
+pub struct RowPrinter {
+    output: OutputFormat,
+    writer: BufWriter<Stdout>,
+    header: Option<Vec<String>>,
+}
+
+impl RowPrinter {
+    pub fn new(output: OutputFormat) -> Self {
+        Self {
+            output,
+            writer: BufWriter::new(io::stdout()),
+            header: None,
+        }
+    }
+
+    /// Print a single record, the schema is the (possibly projected) schema the
+    /// record was read with and is used to flatten the record into columns
+    pub fn print(&mut self, row: &Row, schema: &Type) -> Result<(), PQRSError> {
+        match self.output.format {
+            Format::Default => {
+                write_row(&mut self.writer, row, schema)?;
+                writeln!(self.writer)?
+            }
+            Format::Json => writeln!(self.writer, "{}", json_row(row, schema))?,
+            Format::Csv | Format::Tsv => {
+                let mut names = Vec::new();
+                column_names(schema, "", &mut names);
+                match &self.header {
+                    Some(header) if *header != names => {
+                        return Err(SchemaMismatch(format!(
+                            "the delimited output needs the same columns for all the records, \
+                             got {} after {}",
+                            names.join(", "),
+                            header.join(", ")
+                        )))
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.write_line(&names)?;
+                        self.header = Some(names);
+                    }
+                }
+
+                let mut values = Vec::new();
+                column_values(schema, Some(row), &mut values);
+                self.write_line(&values)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Print a single record made up of plain values, such as the result of a query
+    pub fn print_values(
+        &mut self,
+        names: &[String],
+        values: &[expression::Value],
+    ) -> Result<(), PQRSError> {
+        match self.output.format {
+            Format::Default => {
+                let fields: Vec<String> = names
+                    .iter()
+                    .zip(values)
+                    .map(|(name, value)| format!("{}: {}", name, display_value(value)))
+                    .collect();
+                writeln!(self.writer, "{{{}}}", fields.join(", "))?
+            }
+            Format::Json => {
+                let map: Map<String, Value> = names
+                    .iter()
+                    .cloned()
+                    .zip(values.iter().map(json_value))
+                    .collect();
+                writeln!(self.writer, "{}", Value::Object(map))?
+            }
+            Format::Csv | Format::Tsv => {
+                if self.header.as_deref() != Some(names) {
+                    self.write_line(names)?;
+                    self.header = Some(names.to_vec());
+                }
+
+                let cells: Vec<String> = values
+                    .iter()
+                    .map(|value| match value {
+                        expression::Value::Null => String::new(),
+                        expression::Value::Str(s) => s.clone(),
+                        value => display_value(value),
+                    })
+                    .collect();
+                self.write_line(&cells)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Flush any buffered output to stdout
+    pub fn flush(&mut self) -> Result<(), PQRSError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_line(&mut self, cells: &[String]) -> io::Result<()> {
+        let (delimiter, escaped): (String, Vec<String>) = match self.output.format {
+            Format::Tsv => (
+                String::from("\t"),
+                cells.iter().map(|c| escape_tsv(c)).collect(),
+            ),
+            _ => (
+                self.output.delimiter.to_string(),
+                cells
+                    .iter()
+                    .map(|c| escape_csv(c, self.output.delimiter))
+                    .collect(),
+            ),
+        };
+
+        writeln!(self.writer, "{}", escaped.join(&delimiter))
+    }
+}
+
+/// Format a plain value the same way the fields of a record are displayed
+fn display_value(value: &expression::Value) -> String {
+    match value {
+        expression::Value::Null => String::from("null"),
+        expression::Value::Bool(b) => b.to_string(),
+        expression::Value::Int(v) => v.to_string(),
+        expression::Value::Float(v) => v.to_string(),
+        expression::Value::Str(s) => format!("\"{}\"", s),
+        expression::Value::Json(v) => v.to_string(),
+    }
+}
+
+fn json_value(value: &expression::Value) -> Value {
+    match value {
+        expression::Value::Null => Value::Null,
+        expression::Value::Bool(b) => Value::Bool(*b),
+        expression::Value::Int(v) => Value::from(*v),
+        expression::Value::Float(v) => Number::from_f64(*v)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        expression::Value::Str(s) => Value::String(s.clone()),
+        expression::Value::Json(v) => v.clone(),
+    }
+}
+
+/// Quote the given cell if it contains the delimiter, a quote or a line break.
+/// Quotes inside the cell are escaped by doubling them.
+pub(crate) fn escape_csv(cell: &str, delimiter: char) -> String {
+    if cell.contains(|c: char| c == delimiter || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// Escape tabs, line breaks and backslashes so every record stays on a single line
+fn escape_tsv(cell: &str) -> String {
+    cell.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Return the fields of the record that are part of the given group type. The record
+/// can contain more fields than the schema, e.g. the columns that were only read to
+/// evaluate a filter, but the fields are always in the same order as the schema.
+fn selected_fields<'a>(
+    row: &'a Row,
+    group: &'a Type,
+) -> Vec<(&'a Type, &'a String, &'a Field)> {
+    let mut fields = group.get_fields().iter().peekable();
+    let mut selected = Vec::new();
+    for (name, value) in row.get_column_iter() {
+        if let Some(&field) = fields.peek() {
+            if field.name() == name {
+                selected.push((field.as_ref(), name, value));
+                fields.next();
+            }
+        }
+    }
+
+    selected
+}
+
+/// Write the record in the same json-like format as the `Display` implementation of `Row`
+fn write_row<W: Write>(out: &mut W, row: &Row, group: &Type) -> io::Result<()> {
+    write!(out, "{{")?;
+    for (i, (field, name, value)) in selected_fields(row, group).into_iter().enumerate() {
+        if i > 0 {
+            write!(out, ", ")?;
+        }
+        write!(out, "{}: ", name)?;
+        match value {
+            Field::Group(nested) if is_struct(field) => write_row(out, nested, field)?,
+            _ => write!(out, "{}", value)?,
+        }
+    }
+    write!(out, "}}")
+}
+
+/// Convert the record into a JSON object, similar to `Row::to_json_value`
+fn json_row(row: &Row, group: &Type) -> Value {
+    let mut map = Map::new();
+    for (field, name, value) in selected_fields(row, group) {
+        let json = match value {
+            Field::Group(nested) if is_struct(field) => json_row(nested, field),
+            _ => value.to_json_value(),
+        };
+        map.insert(name.clone(), json);
+    }
+
+    Value::Object(map)
+}
+
+/// Groups are flattened into one column per leaf (`country.name`) unless they
+/// represent a list or a map, in which case the whole value goes into a single column
+pub(crate) fn is_struct(field: &Type) -> bool {
+    if !field.is_group() {
+        return false;
+    }
+
+    let info = field.get_basic_info();
+    if info.has_repetition() && info.repetition() == Repetition::REPEATED {
+        return false;
+    }
+
+    !matches!(
+        info.converted_type(),
+        ConvertedType::LIST | ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE
+    )
+}
+
+/// Collect the flattened column names for the given group type
+fn column_names(group: &Type, prefix: &str, names: &mut Vec<String>) {
+    for field in group.get_fields() {
+        let name = if prefix.is_empty() {
+            field.name().to_string()
+        } else {
+            format!("{}.{}", prefix, field.name())
+        };
+
+        if is_struct(field) {
+            column_names(field, &name, names);
+        } else {
+            names.push(name);
+        }
+    }
+}
+
+/// Collect the flattened values of the given record, in the same order as `column_names`.
+/// A missing record (a null group) produces empty values for all its leaves.
+fn column_values(group: &Type, row: Option<&Row>, values: &mut Vec<String>) {
+    for field in group.get_fields() {
+        let value = row.and_then(|r| {
+            r.get_column_iter()
+                .find(|(name, _)| name.as_str() == field.name())
+                .map(|(_, value)| value)
+        });
+
+        if is_struct(field) {
+            match value {
+                Some(Field::Group(nested)) => column_values(field, Some(nested), values),
+                _ => column_values(field, None, values),
+            }
+        } else {
+            values.push(value.map(format_cell).unwrap_or_default());
+        }
+    }
+}
+
+/// Format a single value for delimited output, lists and maps are written as JSON
+fn format_cell(field: &Field) -> String {
+    match field.to_json_value() {
+        Value::Null => String::new(),
+        Value::String(s) => s,
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,200 @@
+use crate::errors::PQRSError;
+use crate::report::Entry;
+use crate::stats::StatValue;
+use parquet::basic::{Encoding, PageType};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::ColumnChunkMetaData;
+use parquet::file::statistics::from_thrift;
+use parquet_format::PageHeader;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use thrift::protocol::TCompactInputProtocol;
+
+/// The header of a single page of a column chunk, as written in the file
+#[derive(Debug, Clone)]
+pub struct PageInfo {
+    pub page_type: PageType,
+    /// The offset of the page header from the start of the file
+    pub offset: u64,
+    pub header_size: u64,
+    pub compressed_size: i32,
+    pub uncompressed_size: i32,
+    pub num_values: Option<i32>,
+    pub encoding: Option<Encoding>,
+    pub def_level_encoding: Option<Encoding>,
+    pub rep_level_encoding: Option<Encoding>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub null_count: Option<u64>,
+    pub has_crc: bool,
+}
+
+impl PageInfo {
+    pub fn entry(&self, file_name: &str, row_group: usize, column: &str) -> Entry {
+        let encoding = |e: Option<Encoding>| e.map(|e| e.to_string());
+
+        vec![
+            ("path", json!(file_name)),
+            ("row_group", json!(row_group)),
+            ("column", json!(column)),
+            ("page_type", json!(self.page_type.to_string())),
+            ("offset", json!(self.offset)),
+            ("header_size", json!(self.header_size)),
+            ("compressed_size", json!(self.compressed_size)),
+            ("uncompressed_size", json!(self.uncompressed_size)),
+            ("num_values", json!(self.num_values)),
+            ("encoding", json!(encoding(self.encoding))),
+            (
+                "definition_level_encoding",
+                json!(encoding(self.def_level_encoding)),
+            ),
+            (
+                "repetition_level_encoding",
+                json!(encoding(self.rep_level_encoding)),
+            ),
+            ("min", json!(self.min)),
+            ("max", json!(self.max)),
+            ("null_count", json!(self.null_count)),
+            ("crc", json!(self.has_crc)),
+        ]
+    }
+}
+
+/// Read the headers of all the pages of the column chunk. The page headers are read
+/// directly from the file, since the page reader does not expose their offsets, sizes
+/// and checksums, and the page contents are skipped without being decompressed.
+pub fn read_page_headers(
+    file: &File,
+    chunk: &ColumnChunkMetaData,
+) -> Result<Vec<PageInfo>, PQRSError> {
+    let start = chunk_start(chunk.dictionary_page_offset(), chunk.data_page_offset());
+    let end = start + chunk.compressed_size() as u64;
+
+    let headers = read_raw_page_headers(file, start, end)?;
+    Ok(headers
+        .iter()
+        .map(|(offset, header_size, header)| {
+            page_info(header, *offset, *header_size, chunk)
+        })
+        .collect())
+}
+
+/// Return the offset of the first page of a column chunk
+pub fn chunk_start(dictionary_page_offset: Option<i64>, data_page_offset: i64) -> u64 {
+    // some writers set the dictionary page offset to 0 when there is no dictionary
+    let offset = match dictionary_page_offset {
+        Some(offset) if offset > 0 => offset.min(data_page_offset),
+        _ => data_page_offset,
+    };
+    offset as u64
+}
+
+/// Read the thrift page headers between the given offsets, along with the offset and the
+/// size of each header
+pub fn read_raw_page_headers(
+    file: &File,
+    start: u64,
+    end: u64,
+) -> Result<Vec<(u64, u64, PageHeader)>, PQRSError> {
+    let mut reader = CountingReader {
+        inner: BufReader::new(file.try_clone()?),
+        count: 0,
+    };
+    let mut headers = Vec::new();
+    let mut offset = start;
+    while offset < end {
+        reader.inner.seek(SeekFrom::Start(offset))?;
+        reader.count = 0;
+        let header = {
+            let mut protocol = TCompactInputProtocol::new(&mut reader);
+            PageHeader::read_from_in_protocol(&mut protocol)?
+        };
+        let header_size = reader.count;
+        if header.compressed_page_size < 0 {
+            return Err(ParquetError::General(format!(
+                "Invalid page size {} in the page header at offset {}",
+                header.compressed_page_size, offset
+            ))
+            .into());
+        }
+
+        let next = offset + header_size + header.compressed_page_size as u64;
+        headers.push((offset, header_size, header));
+        offset = next;
+    }
+
+    Ok(headers)
+}
+
+fn page_info(
+    header: &PageHeader,
+    offset: u64,
+    header_size: u64,
+    chunk: &ColumnChunkMetaData,
+) -> PageInfo {
+    let mut info = PageInfo {
+        page_type: PageType::from(header.type_),
+        offset,
+        header_size,
+        compressed_size: header.compressed_page_size,
+        uncompressed_size: header.uncompressed_page_size,
+        num_values: None,
+        encoding: None,
+        def_level_encoding: None,
+        rep_level_encoding: None,
+        min: None,
+        max: None,
+        null_count: None,
+        has_crc: header.crc.is_some(),
+    };
+
+    let statistics = if let Some(page) = &header.data_page_header {
+        info.num_values = Some(page.num_values);
+        info.encoding = Some(Encoding::from(page.encoding));
+        info.def_level_encoding = Some(Encoding::from(page.definition_level_encoding));
+        info.rep_level_encoding = Some(Encoding::from(page.repetition_level_encoding));
+        page.statistics.clone()
+    } else if let Some(page) = &header.data_page_header_v2 {
+        // the levels of v2 pages are always encoded with RLE, outside of the values
+        info.num_values = Some(page.num_values);
+        info.encoding = Some(Encoding::from(page.encoding));
+        info.def_level_encoding = Some(Encoding::RLE);
+        info.rep_level_encoding = Some(Encoding::RLE);
+        info.null_count = Some(page.num_nulls as u64);
+        page.statistics.clone()
+    } else if let Some(page) = &header.dictionary_page_header {
+        info.num_values = Some(page.num_values);
+        info.encoding = Some(Encoding::from(page.encoding));
+        None
+    } else {
+        None
+    };
+
+    let column = chunk.column_descr();
+    if let Some(statistics) = from_thrift(column.physical_type(), statistics) {
+        if let Some((min, max)) = StatValue::from_statistics(&statistics, column) {
+            info.min = Some(min.display(column));
+            info.max = Some(max.display(column));
+        }
+        if info.null_count.is_none() {
+            info.null_count = Some(statistics.null_count());
+        }
+    }
+
+    info
+}
+
+/// Keep track of the number of bytes read, to find where the page header ends
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+        Ok(read)
+    }
+}
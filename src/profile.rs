@@ -0,0 +1,447 @@
+use crate::expression::Value;
+use crate::query::value_from_array;
+use arrow::array::{as_struct_array, Array, ArrayRef};
+use arrow::datatypes::DataType;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// The number of values kept to estimate the quantiles in approximate mode
+static QUANTILE_SAMPLE_SIZE: usize = 10_000;
+
+/// The quantiles reported for numeric columns
+pub static QUANTILES: [f64; 5] = [0.05, 0.25, 0.5, 0.75, 0.95];
+
+/// The summary of the values of a single column, computed by scanning all the records
+pub struct ColumnProfile {
+    pub name: String,
+    pub data_type: DataType,
+    /// The number of values that are not null
+    pub count: u64,
+    pub nulls: u64,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    numeric: Moments,
+    /// All the numeric values, or a uniform sample of them in approximate mode
+    numeric_values: Vec<f64>,
+    lengths: Option<(usize, usize, usize)>,
+    frequencies: Frequencies,
+    rng: StdRng,
+}
+
+/// The running count, mean and sum of squared differences, see Welford's algorithm
+#[derive(Default)]
+struct Moments {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+}
+
+/// How often every value occurs, either exactly or approximately for large columns
+enum Frequencies {
+    Exact(HashMap<String, u64>),
+    Approximate(HyperLogLog, SpaceSaving),
+}
+
+impl ColumnProfile {
+    pub fn new(name: String, data_type: DataType, approximate: bool, top: usize) -> Self {
+        let frequencies = if approximate {
+            Frequencies::Approximate(
+                HyperLogLog::new(),
+                SpaceSaving::new((top * 10).max(100)),
+            )
+        } else {
+            Frequencies::Exact(HashMap::new())
+        };
+
+        Self {
+            name,
+            data_type,
+            count: 0,
+            nulls: 0,
+            min: None,
+            max: None,
+            numeric: Moments::default(),
+            numeric_values: Vec::new(),
+            lengths: None,
+            frequencies,
+            // a fixed seed keeps the approximate quantiles stable between runs
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    pub fn add(&mut self, value: Value) {
+        let number = match &value {
+            Value::Null => {
+                self.nulls += 1;
+                return;
+            }
+            Value::Int(v) => Some(*v as f64),
+            Value::Float(v) => Some(*v),
+            _ => None,
+        };
+        self.count += 1;
+
+        if let Value::Str(s) = &value {
+            let length = s.chars().count();
+            self.lengths = Some(match self.lengths {
+                Some((min, max, total)) => {
+                    (min.min(length), max.max(length), total + length)
+                }
+                None => (length, length, length),
+            });
+        }
+
+        if let Some(number) = number.filter(|n| !n.is_nan()) {
+            self.numeric.add(number);
+            match self.frequencies {
+                Frequencies::Approximate(..)
+                    if self.numeric_values.len() >= QUANTILE_SAMPLE_SIZE =>
+                {
+                    // reservoir sampling keeps a uniform sample of all the values
+                    let index = self.rng.gen_range(0..self.numeric.count) as usize;
+                    if index < QUANTILE_SAMPLE_SIZE {
+                        self.numeric_values[index] = number;
+                    }
+                }
+                _ => self.numeric_values.push(number),
+            }
+        }
+
+        let key = value_key(&value);
+        match &mut self.frequencies {
+            Frequencies::Exact(counts) => *counts.entry(key).or_insert(0) += 1,
+            Frequencies::Approximate(distinct, top) => {
+                distinct.add(&key);
+                top.add(key);
+            }
+        }
+
+        if self
+            .min
+            .as_ref()
+            .map_or(true, |min| value.compare(min) == Some(Ordering::Less))
+        {
+            self.min = Some(value.clone());
+        }
+        if self
+            .max
+            .as_ref()
+            .map_or(true, |max| value.compare(max) == Some(Ordering::Greater))
+        {
+            self.max = Some(value);
+        }
+    }
+
+    /// The percentage of null values
+    pub fn null_percentage(&self) -> f64 {
+        let total = self.count + self.nulls;
+        if total == 0 {
+            return 0.0;
+        }
+        self.nulls as f64 * 100.0 / total as f64
+    }
+
+    /// The number of distinct values, which is an estimate in approximate mode
+    pub fn distinct(&self) -> u64 {
+        match &self.frequencies {
+            Frequencies::Exact(counts) => counts.len() as u64,
+            Frequencies::Approximate(distinct, _) => distinct.estimate(),
+        }
+    }
+
+    pub fn is_approximate(&self) -> bool {
+        matches!(self.frequencies, Frequencies::Approximate(..))
+    }
+
+    /// The mean and sample standard deviation of numeric columns
+    pub fn mean_stddev(&self) -> Option<(f64, f64)> {
+        if self.numeric.count == 0 {
+            return None;
+        }
+        let variance = if self.numeric.count > 1 {
+            self.numeric.m2 / (self.numeric.count - 1) as f64
+        } else {
+            0.0
+        };
+
+        Some((self.numeric.mean, variance.sqrt()))
+    }
+
+    /// The values at the given quantiles of numeric columns
+    pub fn quantiles(&self, quantiles: &[f64]) -> Option<Vec<f64>> {
+        if self.numeric_values.is_empty() {
+            return None;
+        }
+
+        let mut sorted = self.numeric_values.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Some(quantiles.iter().map(|&q| quantile(&sorted, q)).collect())
+    }
+
+    /// The min, max and average number of characters of string columns
+    pub fn lengths(&self) -> Option<(usize, usize, f64)> {
+        self.lengths
+            .map(|(min, max, total)| (min, max, total as f64 / self.count as f64))
+    }
+
+    /// The most frequent values with their number of occurrences, most frequent first.
+    /// In approximate mode the counts are upper bounds.
+    pub fn top(&self, k: usize) -> Vec<(String, u64)> {
+        let mut counts: Vec<(String, u64)> = match &self.frequencies {
+            Frequencies::Exact(counts) => {
+                counts.iter().map(|(v, c)| (v.clone(), *c)).collect()
+            }
+            Frequencies::Approximate(_, top) => {
+                top.counts.iter().map(|(v, c)| (v.clone(), *c)).collect()
+            }
+        };
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(k);
+        counts
+    }
+}
+
+/// The key used to count the occurrences of a value, strings are used as they are
+pub fn value_key(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(v) => v.to_string(),
+        Value::Float(v) => v.to_string(),
+        Value::Json(v) => v.to_string(),
+        Value::Null => String::new(),
+    }
+}
+
+/// Linear interpolation between the closest ranks of the sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Create a profile for every leaf column of the given batch schema, the children of
+/// struct columns are profiled separately using a dotted path
+pub fn create_profiles(
+    batch: &RecordBatch,
+    approximate: bool,
+    top: usize,
+) -> Vec<ColumnProfile> {
+    leaf_columns(batch)
+        .into_iter()
+        .map(|(name, array)| {
+            ColumnProfile::new(name, array.data_type().clone(), approximate, top)
+        })
+        .collect()
+}
+
+/// Add all the values of the batch to the profiles created by `create_profiles`
+pub fn profile_batch(profiles: &mut [ColumnProfile], batch: &RecordBatch) {
+    for (profile, (_, array)) in profiles.iter_mut().zip(leaf_columns(batch)) {
+        for index in 0..array.len() {
+            profile.add(profile_value(&array, index));
+        }
+    }
+}
+
+fn leaf_columns(batch: &RecordBatch) -> Vec<(String, ArrayRef)> {
+    let mut leaves = Vec::new();
+    for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+        collect_leaves(field.name().to_string(), array, &mut leaves);
+    }
+
+    leaves
+}
+
+fn collect_leaves(name: String, array: &ArrayRef, leaves: &mut Vec<(String, ArrayRef)>) {
+    match array.data_type() {
+        DataType::Struct(fields) => {
+            let array = as_struct_array(array);
+            for (field, child) in fields.iter().zip(array.columns()) {
+                collect_leaves(format!("{}.{}", name, field.name()), child, leaves);
+            }
+        }
+        _ => leaves.push((name, array.clone())),
+    }
+}
+
+/// Lists and maps are profiled using their string representation
+fn profile_value(array: &ArrayRef, index: usize) -> Value {
+    match array.data_type() {
+        DataType::List(_) | DataType::LargeList(_) if array.is_valid(index) => {
+            array_value_to_string(array, index)
+                .map(Value::Str)
+                .unwrap_or(Value::Null)
+        }
+        _ => value_from_array(array, index),
+    }
+}
+
+/// Estimates the number of distinct values using a fixed amount of memory,
+/// with a standard error of about 1%
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// The number of bits of the hash used to pick a register
+    const PRECISION: u32 = 14;
+
+    fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << Self::PRECISION],
+        }
+    }
+
+    fn add<T: Hash + ?Sized>(&mut self, value: &T) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - Self::PRECISION)) as usize;
+        // the position of the first set bit in the remaining bits of the hash
+        let rank =
+            ((hash << Self::PRECISION).leading_zeros() + 1).min(64 - Self::PRECISION + 1);
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+
+        // use linear counting for small cardinalities
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            return (m * (m / zeros as f64).ln()).round() as u64;
+        }
+
+        estimate.round() as u64
+    }
+}
+
+/// Keeps track of the most frequent values using a fixed number of counters,
+/// see the Space-Saving algorithm by Metwally et al.
+struct SpaceSaving {
+    capacity: usize,
+    counts: HashMap<String, u64>,
+}
+
+impl SpaceSaving {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            counts: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: String) {
+        if let Some(count) = self.counts.get_mut(&value) {
+            *count += 1;
+            return;
+        }
+
+        if self.counts.len() < self.capacity {
+            self.counts.insert(value, 1);
+            return;
+        }
+
+        // the least frequent value is replaced, the new value inherits its count
+        let (least, count) = self
+            .counts
+            .iter()
+            .min_by_key(|(_, &count)| count)
+            .map(|(v, &c)| (v.clone(), c))
+            .unwrap();
+        self.counts.remove(&least);
+        self.counts.insert(value, count + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_profiles_values() {
+        let mut profile =
+            ColumnProfile::new(String::from("x"), DataType::Int64, false, 3);
+        for value in &[1, 2, 2, 3, 3, 3] {
+            profile.add(Value::Int(*value));
+        }
+        profile.add(Value::Null);
+
+        assert_eq!(profile.count, 6);
+        assert_eq!(profile.nulls, 1);
+        assert_eq!(profile.distinct(), 3);
+        assert_eq!(profile.min, Some(Value::Int(1)));
+        assert_eq!(profile.max, Some(Value::Int(3)));
+        let (mean, stddev) = profile.mean_stddev().unwrap();
+        assert!((mean - 14.0 / 6.0).abs() < 1e-9);
+        assert!((stddev - 0.816_496_580_927_726).abs() < 1e-9);
+        assert_eq!(
+            profile.quantiles(&[0.0, 0.5, 1.0]),
+            Some(vec![1.0, 2.5, 3.0])
+        );
+        assert_eq!(
+            profile.top(2),
+            vec![(String::from("3"), 3), (String::from("2"), 2)]
+        );
+    }
+
+    #[test]
+    fn it_profiles_string_lengths() {
+        let mut profile = ColumnProfile::new(String::from("s"), DataType::Utf8, false, 3);
+        for value in &["a", "abc", "ab"] {
+            profile.add(Value::Str(value.to_string()));
+        }
+
+        assert_eq!(profile.lengths(), Some((1, 3, 2.0)));
+        assert_eq!(profile.mean_stddev(), None);
+    }
+
+    #[test]
+    fn it_estimates_distinct_values() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..100_000 {
+            hll.add(&i);
+        }
+        let estimate = hll.estimate() as f64;
+        assert!((estimate - 100_000.0).abs() / 100_000.0 < 0.05);
+
+        let mut small = HyperLogLog::new();
+        for i in 0..10 {
+            small.add(&(i % 5));
+        }
+        assert_eq!(small.estimate(), 5);
+    }
+
+    #[test]
+    fn it_finds_frequent_values() {
+        let mut top = SpaceSaving::new(10);
+        for i in 0..1000 {
+            top.add((i % 50).to_string());
+            top.add(String::from("frequent"));
+        }
+
+        let (value, _) = top.counts.iter().max_by_key(|(_, &count)| count).unwrap();
+        assert_eq!(value, "frequent");
+    }
+}
@@ -0,0 +1,803 @@
+use crate::errors::PQRSError;
+use crate::errors::PQRSError::{InvalidExpression, UnknownColumn};
+use crate::expression::{tokenize_with_keywords, Expr, Parser, Record, Token, Value};
+use crate::output::RowPrinter;
+use crate::utils::ParquetData;
+use arrow::array::{
+    as_boolean_array, as_large_list_array, as_largestring_array, as_list_array,
+    as_primitive_array, as_string_array, as_struct_array, Array, ArrayRef,
+};
+use arrow::datatypes::{
+    DataType, Field, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    Schema, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The keywords of queries, on top of the ones of the filter expressions which are
+/// shared with `--where` and must not reserve more column names
+static SQL_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "GROUP", "BY", "ORDER", "ASC", "DESC", "LIMIT", "AS",
+];
+
+/// The aggregate functions supported in queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Aggregate::Count => "COUNT",
+            Aggregate::Sum => "SUM",
+            Aggregate::Avg => "AVG",
+            Aggregate::Min => "MIN",
+            Aggregate::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A single item in the select list of a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectItem {
+    /// `*`, all the top level columns of the table
+    Wildcard,
+    /// An expression evaluated for every record, along with its output name
+    Expr(Expr, String),
+    /// An aggregate function along with its output name, the expression is None for COUNT(*)
+    Aggregate(Aggregate, Option<Expr>, String),
+}
+
+/// A parsed SELECT statement
+#[derive(Debug)]
+pub struct Query {
+    pub items: Vec<SelectItem>,
+    pub table: String,
+    pub filter: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    /// The expressions to sort by, along with a flag that is set for descending order
+    pub order_by: Vec<(Expr, bool)>,
+    pub limit: Option<usize>,
+}
+
+impl Query {
+    /// Parse a query of the form
+    /// `SELECT items FROM table [WHERE expr] [GROUP BY exprs] [ORDER BY exprs] [LIMIT n]`
+    pub fn parse(sql: &str) -> Result<Query, PQRSError> {
+        let mut parser = Parser::new(tokenize_with_keywords(sql, SQL_KEYWORDS)?);
+
+        parser.expect_keyword("SELECT")?;
+        let mut items = vec![parse_select_item(&mut parser)?];
+        while parser.accept_symbol(",") {
+            items.push(parse_select_item(&mut parser)?);
+        }
+
+        parser.expect_keyword("FROM")?;
+        let table = match parser.next_token() {
+            Some(Token::Identifier(name)) => name,
+            _ => {
+                return Err(InvalidExpression(String::from(
+                    "Expected a table name after FROM",
+                )))
+            }
+        };
+
+        let filter = if parser.accept_keyword("WHERE") {
+            Some(parser.parse_expr()?)
+        } else {
+            None
+        };
+
+        let mut group_by = Vec::new();
+        if parser.accept_keyword("GROUP") {
+            parser.expect_keyword("BY")?;
+            group_by.push(parser.parse_expr()?);
+            while parser.accept_symbol(",") {
+                group_by.push(parser.parse_expr()?);
+            }
+        }
+
+        let mut order_by = Vec::new();
+        if parser.accept_keyword("ORDER") {
+            parser.expect_keyword("BY")?;
+            loop {
+                let expr = parser.parse_expr()?;
+                let descending = parser.accept_keyword("DESC");
+                if !descending {
+                    parser.accept_keyword("ASC");
+                }
+                order_by.push((expr, descending));
+
+                if !parser.accept_symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let limit =
+            if parser.accept_keyword("LIMIT") {
+                match parser.next_token() {
+                    Some(Token::Number(n)) => Some(n.parse::<usize>().map_err(|_| {
+                        InvalidExpression(format!("Invalid LIMIT: {}", n))
+                    })?),
+                    _ => {
+                        return Err(InvalidExpression(String::from(
+                            "Expected a number after LIMIT",
+                        )))
+                    }
+                }
+            } else {
+                None
+            };
+
+        parser.expect_end()?;
+
+        Ok(Query {
+            items,
+            table,
+            filter,
+            group_by,
+            order_by,
+            limit,
+        })
+    }
+
+    /// Run the query against the given data and print the resulting records
+    pub fn execute(
+        &self,
+        data: &ParquetData,
+        printer: &mut RowPrinter,
+    ) -> Result<(), PQRSError> {
+        let items = self.expand_wildcard(&data.schema);
+        self.validate_columns(&items, &data.schema)?;
+
+        let names: Vec<String> = items
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expr(_, name) | SelectItem::Aggregate(_, _, name) => {
+                    name.clone()
+                }
+                SelectItem::Wildcard => unreachable!("wildcards are expanded"),
+            })
+            .collect();
+        let order_by = self.resolve_order_by(&items, &names)?;
+
+        let is_aggregate = !self.group_by.is_empty()
+            || items
+                .iter()
+                .any(|item| matches!(item, SelectItem::Aggregate(_, _, _)));
+        // without sorting, the records can be limited while they are computed
+        let limit = if order_by.is_empty() {
+            self.limit
+        } else {
+            None
+        };
+
+        let mut results = if is_aggregate {
+            self.aggregate(&items, data)?
+        } else {
+            self.project(&items, data, limit)
+        };
+
+        results.sort_by(|a, b| {
+            for (index, descending) in &order_by {
+                let ordering = compare_for_sort(&a[*index], &b[*index]);
+                let ordering = if *descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+
+        for values in results.iter().take(self.limit.unwrap_or(usize::MAX)) {
+            printer.print_values(&names, values)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace `*` with the top level columns of the table
+    fn expand_wildcard(&self, schema: &Schema) -> Vec<SelectItem> {
+        let mut items = Vec::new();
+        for item in &self.items {
+            match item {
+                SelectItem::Wildcard => {
+                    for field in schema.fields() {
+                        items.push(SelectItem::Expr(
+                            Expr::Column(field.name().clone()),
+                            field.name().clone(),
+                        ));
+                    }
+                }
+                item => items.push(item.clone()),
+            }
+        }
+
+        items
+    }
+
+    /// Make sure all the columns referenced by the query are present in the table
+    fn validate_columns(
+        &self,
+        items: &[SelectItem],
+        schema: &Schema,
+    ) -> Result<(), PQRSError> {
+        let mut exprs: Vec<&Expr> = Vec::new();
+        for item in items {
+            match item {
+                SelectItem::Expr(expr, _) | SelectItem::Aggregate(_, Some(expr), _) => {
+                    exprs.push(expr)
+                }
+                _ => {}
+            }
+        }
+        exprs.extend(self.filter.iter());
+        exprs.extend(self.group_by.iter());
+
+        for expr in exprs {
+            for column in expr.columns() {
+                let path: Vec<&str> = column.split('.').collect();
+                if !has_column(schema.fields(), &path) {
+                    let mut available = Vec::new();
+                    get_column_names(schema.fields(), "", &mut available);
+                    return Err(UnknownColumn(column, available.join(", ")));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the output column for each of the ORDER BY expressions. The expressions can
+    /// refer to an output name, a 1-based position or one of the selected expressions.
+    fn resolve_order_by(
+        &self,
+        items: &[SelectItem],
+        names: &[String],
+    ) -> Result<Vec<(usize, bool)>, PQRSError> {
+        let mut order_by = Vec::new();
+        for (expr, descending) in &self.order_by {
+            let index = match expr {
+                Expr::Literal(Value::Int(n))
+                    if *n >= 1 && (*n as usize) <= names.len() =>
+                {
+                    Some(*n as usize - 1)
+                }
+                Expr::Column(name) if names.contains(name) => {
+                    names.iter().position(|n| n == name)
+                }
+                expr => items.iter().position(|item| match item {
+                    SelectItem::Expr(e, _) => e == expr,
+                    _ => false,
+                }),
+            };
+
+            match index {
+                Some(index) => order_by.push((index, *descending)),
+                None => {
+                    return Err(InvalidExpression(format!(
+                        "ORDER BY {} must refer to a selected column",
+                        expr
+                    )))
+                }
+            }
+        }
+
+        Ok(order_by)
+    }
+
+    /// Evaluate the selected expressions for every record matching the filter
+    fn project(
+        &self,
+        items: &[SelectItem],
+        data: &ParquetData,
+        limit: Option<usize>,
+    ) -> Vec<Vec<Value>> {
+        let mut results = Vec::new();
+        for record in records(&data.batches) {
+            if limit.map_or(false, |limit| results.len() >= limit) {
+                break;
+            }
+            if !self.filter.as_ref().map_or(true, |f| f.matches(&record)) {
+                continue;
+            }
+
+            let values = items
+                .iter()
+                .map(|item| match item {
+                    SelectItem::Expr(expr, _) => expr.evaluate(&record),
+                    _ => Value::Null,
+                })
+                .collect();
+            results.push(values);
+        }
+
+        results
+    }
+
+    /// Group the records matching the filter and compute the aggregates for every group.
+    /// Without a GROUP BY clause all the records belong to a single group.
+    fn aggregate(
+        &self,
+        items: &[SelectItem],
+        data: &ParquetData,
+    ) -> Result<Vec<Vec<Value>>, PQRSError> {
+        // plain expressions have to be one of the grouping expressions
+        let mut outputs = Vec::new();
+        let mut aggregates = Vec::new();
+        for item in items {
+            match item {
+                SelectItem::Expr(expr, _) => match self.group_by.iter().position(|e| e == expr) {
+                    Some(index) => outputs.push(Output::Key(index)),
+                    None => {
+                        return Err(InvalidExpression(format!(
+                            "{} must appear in the GROUP BY clause or be used in an aggregate",
+                            expr
+                        )))
+                    }
+                },
+                SelectItem::Aggregate(aggregate, expr, _) => {
+                    outputs.push(Output::Aggregate(aggregates.len()));
+                    aggregates.push((*aggregate, expr.as_ref()));
+                }
+                SelectItem::Wildcard => unreachable!("wildcards are expanded"),
+            }
+        }
+
+        let new_accumulators = || -> Vec<Accumulator> {
+            aggregates
+                .iter()
+                .map(|(a, _)| Accumulator::new(*a))
+                .collect()
+        };
+
+        // the groups are kept in the order they are first seen
+        let mut groups: Vec<(Vec<Value>, Vec<Accumulator>)> = Vec::new();
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        if self.group_by.is_empty() {
+            groups.push((Vec::new(), new_accumulators()));
+        }
+
+        for record in records(&data.batches) {
+            if !self.filter.as_ref().map_or(true, |f| f.matches(&record)) {
+                continue;
+            }
+
+            let key: Vec<Value> =
+                self.group_by.iter().map(|e| e.evaluate(&record)).collect();
+            let index = if self.group_by.is_empty() {
+                0
+            } else {
+                let next_index = groups.len();
+                let index = *group_index
+                    .entry(format!("{:?}", key))
+                    .or_insert(next_index);
+                if index == next_index {
+                    groups.push((key, new_accumulators()));
+                }
+                index
+            };
+
+            let accumulators = &mut groups[index].1;
+            for (accumulator, (_, expr)) in accumulators.iter_mut().zip(&aggregates) {
+                // COUNT(*) counts every record, even the ones made up of nulls
+                let value = match expr {
+                    Some(expr) => expr.evaluate(&record),
+                    None => Value::Bool(true),
+                };
+                accumulator.update(value);
+            }
+        }
+
+        Ok(groups
+            .iter()
+            .map(|(key, accumulators)| {
+                outputs
+                    .iter()
+                    .map(|output| match output {
+                        Output::Key(index) => key[*index].clone(),
+                        Output::Aggregate(index) => accumulators[*index].finish(),
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// Parse a single item of the select list along with its optional alias
+fn parse_select_item(parser: &mut Parser) -> Result<SelectItem, PQRSError> {
+    if parser.accept_symbol("*") {
+        return Ok(SelectItem::Wildcard);
+    }
+
+    // aggregates are only supported at the top level of a select item
+    let aggregate = match (parser.peek(), parser.peek_at(1)) {
+        (Some(Token::Identifier(name)), Some(Token::Symbol("("))) => {
+            match name.to_uppercase().as_str() {
+                "COUNT" => Some(Aggregate::Count),
+                "SUM" => Some(Aggregate::Sum),
+                "AVG" => Some(Aggregate::Avg),
+                "MIN" => Some(Aggregate::Min),
+                "MAX" => Some(Aggregate::Max),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    if let Some(aggregate) = aggregate {
+        // skip the function name and the opening parenthesis
+        parser.next_token();
+        parser.next_token();
+        let expr = if aggregate == Aggregate::Count && parser.accept_symbol("*") {
+            None
+        } else {
+            Some(parser.parse_expr()?)
+        };
+        parser.expect_symbol(")")?;
+
+        let name = match &expr {
+            Some(expr) => format!("{}({})", aggregate, expr),
+            None => format!("{}(*)", aggregate),
+        };
+        let name = parse_alias(parser)?.unwrap_or(name);
+        return Ok(SelectItem::Aggregate(aggregate, expr, name));
+    }
+
+    let expr = parser.parse_expr()?;
+    let name = parse_alias(parser)?.unwrap_or_else(|| expr.to_string());
+    Ok(SelectItem::Expr(expr, name))
+}
+
+/// Parse an optional `[AS] alias`
+fn parse_alias(parser: &mut Parser) -> Result<Option<String>, PQRSError> {
+    let explicit = parser.accept_keyword("AS");
+    match parser.peek() {
+        Some(Token::Identifier(alias)) => {
+            let alias = alias.clone();
+            parser.next_token();
+            Ok(Some(alias))
+        }
+        _ if explicit => Err(parser.unexpected("an alias")),
+        _ => Ok(None),
+    }
+}
+
+/// Where the value of an output column comes from in aggregate queries
+enum Output {
+    /// The value of one of the GROUP BY expressions
+    Key(usize),
+    /// The result of one of the aggregates
+    Aggregate(usize),
+}
+
+/// The running state of an aggregate function for a single group
+enum Accumulator {
+    Count(i64),
+    Sum(Option<Value>),
+    Avg(f64, i64),
+    Min(Option<Value>),
+    Max(Option<Value>),
+}
+
+impl Accumulator {
+    fn new(aggregate: Aggregate) -> Self {
+        match aggregate {
+            Aggregate::Count => Accumulator::Count(0),
+            Aggregate::Sum => Accumulator::Sum(None),
+            Aggregate::Avg => Accumulator::Avg(0.0, 0),
+            Aggregate::Min => Accumulator::Min(None),
+            Aggregate::Max => Accumulator::Max(None),
+        }
+    }
+
+    /// Add a value to the aggregate, nulls are ignored by all the aggregates
+    fn update(&mut self, value: Value) {
+        if value == Value::Null {
+            return;
+        }
+
+        match self {
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::Sum(sum) => {
+                *sum = match (sum.take(), value) {
+                    (None, value @ Value::Int(_)) | (None, value @ Value::Float(_)) => {
+                        Some(value)
+                    }
+                    (Some(Value::Int(a)), Value::Int(b)) => {
+                        Some(match a.checked_add(b) {
+                            Some(total) => Value::Int(total),
+                            None => Value::Float(a as f64 + b as f64),
+                        })
+                    }
+                    (Some(a), b) => match (as_float(&a), as_float(&b)) {
+                        (Some(a), Some(b)) => Some(Value::Float(a + b)),
+                        _ => Some(a),
+                    },
+                    (None, _) => None,
+                }
+            }
+            Accumulator::Avg(sum, count) => {
+                if let Some(value) = as_float(&value) {
+                    *sum += value;
+                    *count += 1;
+                }
+            }
+            Accumulator::Min(current) => {
+                if current
+                    .as_ref()
+                    .map_or(true, |c| value.compare(c) == Some(Ordering::Less))
+                {
+                    *current = Some(value);
+                }
+            }
+            Accumulator::Max(current) => {
+                if current
+                    .as_ref()
+                    .map_or(true, |c| value.compare(c) == Some(Ordering::Greater))
+                {
+                    *current = Some(value);
+                }
+            }
+        }
+    }
+
+    fn finish(&self) -> Value {
+        match self {
+            Accumulator::Count(count) => Value::Int(*count),
+            Accumulator::Avg(_, 0) => Value::Null,
+            Accumulator::Avg(sum, count) => Value::Float(sum / *count as f64),
+            Accumulator::Sum(value)
+            | Accumulator::Min(value)
+            | Accumulator::Max(value) => value.clone().unwrap_or(Value::Null),
+        }
+    }
+}
+
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(v) => Some(*v as f64),
+        Value::Float(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Compare two values for sorting, nulls and values that cannot be compared sort last
+fn compare_for_sort(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        (a, b) => a.compare(b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// A single record of a record batch
+struct BatchRecord<'a> {
+    batch: &'a RecordBatch,
+    index: usize,
+}
+
+impl Record for BatchRecord<'_> {
+    fn get(&self, path: &str) -> Value {
+        let schema = self.batch.schema();
+        let mut parts = path.split('.');
+        let mut array = match parts.next().and_then(|name| schema.index_of(name).ok()) {
+            Some(index) => self.batch.column(index).clone(),
+            None => return Value::Null,
+        };
+
+        // nested fields are resolved through the children of struct arrays
+        for part in parts {
+            let child = match array.data_type() {
+                DataType::Struct(_) => {
+                    as_struct_array(&array).column_by_name(part).cloned()
+                }
+                _ => None,
+            };
+            match child {
+                Some(child) => array = child,
+                None => return Value::Null,
+            }
+        }
+
+        value_from_array(&array, self.index)
+    }
+}
+
+/// Iterate over all the records in the given batches
+fn records(batches: &[RecordBatch]) -> impl Iterator<Item = BatchRecord<'_>> {
+    batches.iter().flat_map(|batch| {
+        (0..batch.num_rows()).map(move |index| BatchRecord { batch, index })
+    })
+}
+
+/// Convert a single value of an arrow array, types that cannot be compared natively
+/// such as dates and timestamps are compared using their string representation
+pub(crate) fn value_from_array(array: &ArrayRef, index: usize) -> Value {
+    if array.is_null(index) {
+        return Value::Null;
+    }
+
+    match array.data_type() {
+        DataType::Boolean => Value::Bool(as_boolean_array(array).value(index)),
+        DataType::Int8 => {
+            Value::Int(as_primitive_array::<Int8Type>(array).value(index) as i64)
+        }
+        DataType::Int16 => {
+            Value::Int(as_primitive_array::<Int16Type>(array).value(index) as i64)
+        }
+        DataType::Int32 => {
+            Value::Int(as_primitive_array::<Int32Type>(array).value(index) as i64)
+        }
+        DataType::Int64 => {
+            Value::Int(as_primitive_array::<Int64Type>(array).value(index))
+        }
+        DataType::UInt8 => {
+            Value::Int(as_primitive_array::<UInt8Type>(array).value(index) as i64)
+        }
+        DataType::UInt16 => {
+            Value::Int(as_primitive_array::<UInt16Type>(array).value(index) as i64)
+        }
+        DataType::UInt32 => {
+            Value::Int(as_primitive_array::<UInt32Type>(array).value(index) as i64)
+        }
+        DataType::UInt64 => {
+            Value::Float(as_primitive_array::<UInt64Type>(array).value(index) as f64)
+        }
+        DataType::Float32 => {
+            Value::Float(as_primitive_array::<Float32Type>(array).value(index) as f64)
+        }
+        DataType::Float64 => {
+            Value::Float(as_primitive_array::<Float64Type>(array).value(index))
+        }
+        DataType::Utf8 => Value::Str(as_string_array(array).value(index).to_string()),
+        DataType::LargeUtf8 => {
+            Value::Str(as_largestring_array(array).value(index).to_string())
+        }
+        // nested values are kept as JSON, the way `cat --json` prints them
+        DataType::Struct(_) | DataType::List(_) | DataType::LargeList(_) => {
+            Value::Json(json_from_array(array, index))
+        }
+        _ => array_value_to_string(array, index)
+            .map(Value::Str)
+            .unwrap_or(Value::Null),
+    }
+}
+
+/// Convert a single value of an arrow array into JSON, including structs and lists
+fn json_from_array(array: &ArrayRef, index: usize) -> JsonValue {
+    if array.is_null(index) {
+        return JsonValue::Null;
+    }
+
+    match array.data_type() {
+        DataType::Struct(_) => {
+            let array = as_struct_array(array);
+            let fields: JsonMap<String, JsonValue> = array
+                .column_names()
+                .into_iter()
+                .zip(array.columns())
+                .map(|(name, child)| (name.to_string(), json_from_array(child, index)))
+                .collect();
+            JsonValue::Object(fields)
+        }
+        DataType::List(_) => {
+            let values = as_list_array(array).value(index);
+            JsonValue::Array(
+                (0..values.len())
+                    .map(|i| json_from_array(&values, i))
+                    .collect(),
+            )
+        }
+        DataType::LargeList(_) => {
+            let values = as_large_list_array(array).value(index);
+            JsonValue::Array(
+                (0..values.len())
+                    .map(|i| json_from_array(&values, i))
+                    .collect(),
+            )
+        }
+        _ => match value_from_array(array, index) {
+            Value::Null => JsonValue::Null,
+            Value::Bool(value) => json!(value),
+            Value::Int(value) => json!(value),
+            Value::Float(value) => json!(value),
+            Value::Str(value) => json!(value),
+            Value::Json(value) => value,
+        },
+    }
+}
+
+/// Check if the given path resolves to a column, nested fields are resolved through structs
+fn has_column(fields: &[Field], path: &[&str]) -> bool {
+    match fields.iter().find(|f| f.name() == path[0]) {
+        Some(_) if path.len() == 1 => true,
+        Some(field) => match field.data_type() {
+            DataType::Struct(children) => has_column(children, &path[1..]),
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+/// Collect the dotted names of all the columns that can be used in a query
+fn get_column_names(fields: &[Field], prefix: &str, names: &mut Vec<String>) {
+    for field in fields {
+        let name = if prefix.is_empty() {
+            field.name().clone()
+        } else {
+            format!("{}.{}", prefix, field.name())
+        };
+
+        names.push(name.clone());
+        if let DataType::Struct(children) = field.data_type() {
+            get_column_names(children, &name, names);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aggregate, Query, SelectItem};
+    use crate::expression::Expr;
+
+    #[test]
+    fn it_parses_queries() {
+        let query = Query::parse(
+            "SELECT continent, COUNT(*) AS countries, max(country.name) FROM t \
+             WHERE continent IS NOT NULL GROUP BY continent ORDER BY countries DESC LIMIT 2",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.items,
+            vec![
+                SelectItem::Expr(
+                    Expr::Column(String::from("continent")),
+                    String::from("continent")
+                ),
+                SelectItem::Aggregate(Aggregate::Count, None, String::from("countries")),
+                SelectItem::Aggregate(
+                    Aggregate::Max,
+                    Some(Expr::Column(String::from("country.name"))),
+                    String::from("MAX(country.name)")
+                ),
+            ]
+        );
+        assert_eq!(query.table, "t");
+        assert!(query.filter.is_some());
+        assert_eq!(
+            query.group_by,
+            vec![Expr::Column(String::from("continent"))]
+        );
+        assert_eq!(
+            query.order_by,
+            vec![(Expr::Column(String::from("countries")), true)]
+        );
+        assert_eq!(query.limit, Some(2));
+    }
+
+    #[test]
+    fn it_rejects_invalid_queries() {
+        assert!(Query::parse("SELECT FROM t").is_err());
+        assert!(Query::parse("SELECT * t").is_err());
+        assert!(Query::parse("SELECT * FROM t LIMIT x").is_err());
+        assert!(Query::parse("SELECT a AS FROM t").is_err());
+    }
+}
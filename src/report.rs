@@ -0,0 +1,136 @@
+use crate::errors::PQRSError;
+use crate::output::escape_csv;
+use clap::{Arg, ArgMatches};
+use serde_json::{Map, Value};
+use std::io::{self, BufWriter, Write};
+
+/// The formats in which the metadata commands (rowcount, size, schema) print their results
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetadataFormat {
+    /// The human readable output of every command
+    Text,
+    /// A single JSON document
+    Json,
+    /// One line per entry with a header line
+    Csv,
+}
+
+impl MetadataFormat {
+    /// Return the global clap argument used to configure the metadata format
+    pub(crate) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name("output-format")
+            .long("output-format")
+            .takes_value(true)
+            .global(true)
+            .required(false)
+            .possible_values(&["text", "json", "csv"])
+            .help("The format used by the metadata commands, e.g. rowcount, size and stats [default: text]")
+    }
+
+    pub(crate) fn new(matches: &ArgMatches) -> Self {
+        match matches.value_of("output-format") {
+            Some("json") => MetadataFormat::Json,
+            Some("csv") => MetadataFormat::Csv,
+            _ => MetadataFormat::Text,
+        }
+    }
+}
+
+/// A single entry of a structured report, the fields are kept in the order they are printed
+pub type Entry = Vec<(&'static str, Value)>;
+
+/// Print the entries as a single JSON document, with the entries under the given key, or
+/// as CSV with one line per entry. The total, if any, is printed as an extra `total`
+/// object or as the last CSV line with `total` in the first column.
+pub fn print_report(
+    format: MetadataFormat,
+    key: &str,
+    entries: &[Entry],
+    total: Option<Entry>,
+) -> Result<(), PQRSError> {
+    let mut writer = BufWriter::new(io::stdout());
+
+    match format {
+        MetadataFormat::Csv => {
+            let header: Vec<&str> = match entries.first() {
+                Some(entry) => entry.iter().map(|(name, _)| *name).collect(),
+                None => return Ok(()),
+            };
+            write_csv_line(&mut writer, header.iter().map(|name| name.to_string()))?;
+            for entry in entries {
+                write_csv_line(&mut writer, entry.iter().map(|(_, value)| cell(value)))?;
+            }
+            if let Some(total) = total {
+                // the fields that are not totalled are left empty
+                let cells = header.iter().enumerate().map(|(i, name)| {
+                    match total.iter().find(|(field, _)| field == name) {
+                        Some((_, value)) => cell(value),
+                        None if i == 0 => String::from("total"),
+                        None => String::new(),
+                    }
+                });
+                write_csv_line(&mut writer, cells)?;
+            }
+        }
+        _ => {
+            let mut document = Map::new();
+            document.insert(
+                key.to_string(),
+                Value::Array(entries.iter().map(to_json).collect()),
+            );
+            if let Some(total) = total {
+                document.insert(String::from("total"), to_json(&total));
+            }
+            writeln!(writer, "{}", Value::Object(document))?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Print the rows as a table with aligned columns, for the text output of the commands
+pub fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
+
+fn to_json(entry: &Entry) -> Value {
+    Value::Object(
+        entry
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect(),
+    )
+}
+
+/// Format a single value for CSV output, arrays and objects are written as JSON
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn write_csv_line<W: Write, I: Iterator<Item = String>>(
+    writer: &mut W,
+    cells: I,
+) -> io::Result<()> {
+    let escaped: Vec<String> = cells.map(|c| escape_csv(&c, ',')).collect();
+    writeln!(writer, "{}", escaped.join(","))
+}
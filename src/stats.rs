@@ -0,0 +1,420 @@
+use crate::errors::PQRSError;
+use crate::expression::get_column;
+use crate::utils::get_projected_schema;
+use log::debug;
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use parquet::record::Field;
+use parquet::schema::types::{ColumnDescPtr, ColumnDescriptor};
+use std::cmp::Ordering;
+use std::fmt::Write;
+use std::fs::File;
+
+/// A min or max value of a column, in a form that can be compared across row groups.
+/// Integers also hold unsigned values, dates, timestamps and unscaled decimals.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum StatValue {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Bytes(Vec<u8>),
+}
+
+impl StatValue {
+    /// Return the min and max values from the statistics of a column chunk,
+    /// if they were written and can be decoded
+    pub fn from_statistics(
+        statistics: &Statistics,
+        column: &ColumnDescriptor,
+    ) -> Option<(StatValue, StatValue)> {
+        if !statistics.has_min_max_set() {
+            return None;
+        }
+
+        let is_decimal = column.converted_type() == ConvertedType::DECIMAL;
+        let unsigned = matches!(
+            column.converted_type(),
+            ConvertedType::UINT_8
+                | ConvertedType::UINT_16
+                | ConvertedType::UINT_32
+                | ConvertedType::UINT_64
+        );
+        let bytes = |data: &[u8]| {
+            if is_decimal {
+                StatValue::Int(decimal_from_bytes(data))
+            } else {
+                StatValue::Bytes(data.to_vec())
+            }
+        };
+
+        let range = match statistics {
+            Statistics::Boolean(s) => {
+                (StatValue::Bool(*s.min()), StatValue::Bool(*s.max()))
+            }
+            Statistics::Int32(s) if unsigned => (
+                StatValue::Int(*s.min() as u32 as i128),
+                StatValue::Int(*s.max() as u32 as i128),
+            ),
+            Statistics::Int32(s) => (
+                StatValue::Int(*s.min() as i128),
+                StatValue::Int(*s.max() as i128),
+            ),
+            Statistics::Int64(s) if unsigned => (
+                StatValue::Int(*s.min() as u64 as i128),
+                StatValue::Int(*s.max() as u64 as i128),
+            ),
+            Statistics::Int64(s) => (
+                StatValue::Int(*s.min() as i128),
+                StatValue::Int(*s.max() as i128),
+            ),
+            // the order of int96 values is undefined, writers should not produce them
+            Statistics::Int96(_) => return None,
+            Statistics::Float(s) => (
+                StatValue::Float(*s.min() as f64),
+                StatValue::Float(*s.max() as f64),
+            ),
+            Statistics::Double(s) => {
+                (StatValue::Float(*s.min()), StatValue::Float(*s.max()))
+            }
+            Statistics::ByteArray(s) => (bytes(s.min().data()), bytes(s.max().data())),
+            Statistics::FixedLenByteArray(s) => {
+                (bytes(s.min().data()), bytes(s.max().data()))
+            }
+        };
+
+        Some(range)
+    }
+
+    /// Convert a value read from the records, returns None for nulls and nested values
+    pub fn from_field(field: &Field) -> Option<StatValue> {
+        let value = match field {
+            Field::Bool(b) => StatValue::Bool(*b),
+            Field::Byte(v) => StatValue::Int(*v as i128),
+            Field::Short(v) => StatValue::Int(*v as i128),
+            Field::Int(v) => StatValue::Int(*v as i128),
+            Field::Long(v) => StatValue::Int(*v as i128),
+            Field::UByte(v) => StatValue::Int(*v as i128),
+            Field::UShort(v) => StatValue::Int(*v as i128),
+            Field::UInt(v) => StatValue::Int(*v as i128),
+            Field::ULong(v) => StatValue::Int(*v as i128),
+            Field::Float(v) => StatValue::Float(*v as f64),
+            Field::Double(v) => StatValue::Float(*v),
+            Field::Decimal(d) => StatValue::Int(decimal_from_bytes(d.data())),
+            Field::Str(s) => StatValue::Bytes(s.as_bytes().to_vec()),
+            Field::Bytes(b) => StatValue::Bytes(b.data().to_vec()),
+            Field::Date(v) => StatValue::Int(*v as i128),
+            Field::TimestampMillis(v) => StatValue::Int(*v as i128),
+            Field::TimestampMicros(v) => StatValue::Int(*v as i128),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Format the value according to the logical type of the column,
+    /// e.g. dates and timestamps in ISO 8601 and decimals with their scale
+    pub fn display(&self, column: &ColumnDescriptor) -> String {
+        match (self, column.converted_type()) {
+            (StatValue::Int(days), ConvertedType::DATE) => format_date(*days as i64),
+            (StatValue::Int(millis), ConvertedType::TIMESTAMP_MILLIS) => {
+                format_timestamp(*millis as i64, 3)
+            }
+            (StatValue::Int(micros), ConvertedType::TIMESTAMP_MICROS) => {
+                format_timestamp(*micros as i64, 6)
+            }
+            (StatValue::Int(millis), ConvertedType::TIME_MILLIS) => {
+                format_time(*millis as i64, 3)
+            }
+            (StatValue::Int(micros), ConvertedType::TIME_MICROS) => {
+                format_time(*micros as i64, 6)
+            }
+            (StatValue::Int(unscaled), ConvertedType::DECIMAL) => {
+                format_decimal(*unscaled, column.type_scale())
+            }
+            (StatValue::Bool(b), _) => b.to_string(),
+            (StatValue::Int(v), _) => v.to_string(),
+            (StatValue::Float(v), _) => v.to_string(),
+            (StatValue::Bytes(data), _) => {
+                // byte arrays without a string annotation are often strings anyway,
+                // anything that is not valid UTF8 is printed in hex
+                let is_string = column.physical_type() == PhysicalType::BYTE_ARRAY;
+                match std::str::from_utf8(data) {
+                    Ok(s)
+                        if is_string
+                            || column.converted_type() == ConvertedType::UTF8 =>
+                    {
+                        s.to_string()
+                    }
+                    _ => {
+                        let mut hex = String::from("0x");
+                        for byte in data {
+                            let _ = write!(hex, "{:02x}", byte);
+                        }
+                        hex
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The statistics of a column aggregated across all the row groups of a file
+#[derive(Debug, Default)]
+pub struct ColumnStats {
+    pub min: Option<StatValue>,
+    pub max: Option<StatValue>,
+    /// None if any row group does not have a null count
+    pub null_count: Option<u64>,
+    /// Only known if the file has a single row group with a distinct count
+    pub distinct_count: Option<u64>,
+    pub row_groups: usize,
+    pub row_groups_with_statistics: usize,
+    /// True if all the row groups have the statistics needed for the file level values
+    pub complete: bool,
+}
+
+impl ColumnStats {
+    pub fn new() -> Self {
+        Self {
+            null_count: Some(0),
+            complete: true,
+            ..Default::default()
+        }
+    }
+
+    /// Add the statistics of a single column chunk
+    pub fn add_chunk(
+        &mut self,
+        statistics: Option<&Statistics>,
+        num_values: i64,
+        column: &ColumnDescriptor,
+    ) {
+        self.row_groups += 1;
+        let statistics = match statistics {
+            Some(statistics) => statistics,
+            None => {
+                self.complete = false;
+                self.null_count = None;
+                self.distinct_count = None;
+                return;
+            }
+        };
+
+        self.row_groups_with_statistics += 1;
+        self.null_count = self.null_count.map(|n| n + statistics.null_count());
+        self.distinct_count = if self.row_groups == 1 {
+            statistics.distinct_count()
+        } else {
+            None
+        };
+
+        match StatValue::from_statistics(statistics, column) {
+            Some((min, max)) => self.add_range(min, max),
+            // a chunk with only nulls does not have min and max values
+            None if statistics.null_count() as i64 == num_values => {}
+            None => self.complete = false,
+        }
+    }
+
+    /// Add a value read from the records
+    pub fn add_value(&mut self, value: Option<StatValue>) {
+        match value {
+            Some(value) => self.add_range(value.clone(), value),
+            None => self.null_count = self.null_count.map(|n| n + 1),
+        }
+    }
+
+    fn add_range(&mut self, min: StatValue, max: StatValue) {
+        if self.min.as_ref().map_or(true, |current| {
+            min.partial_cmp(current) == Some(Ordering::Less)
+        }) {
+            self.min = Some(min);
+        }
+        if self.max.as_ref().map_or(true, |current| {
+            max.partial_cmp(current) == Some(Ordering::Greater)
+        }) {
+            self.max = Some(max);
+        }
+    }
+}
+
+/// The statistics of a single column of a file
+pub struct ColumnSummary {
+    pub column: ColumnDescPtr,
+    pub stats: ColumnStats,
+    /// True if the statistics were computed from the records instead of the metadata
+    pub computed: bool,
+}
+
+/// Aggregate the statistics of every column across the row groups of the given file.
+/// When `compute` is set, the records are scanned for the columns that do not have
+/// complete statistics, which is only possible for columns that are not repeated.
+pub fn get_column_stats(
+    file: File,
+    compute: bool,
+) -> Result<Vec<ColumnSummary>, PQRSError> {
+    let parquet_reader = SerializedFileReader::new(file)?;
+    let metadata = parquet_reader.metadata();
+
+    let mut summaries: Vec<ColumnSummary> = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| ColumnSummary {
+            column: column.clone(),
+            stats: ColumnStats::new(),
+            computed: false,
+        })
+        .collect();
+
+    for row_group in metadata.row_groups() {
+        for (summary, chunk) in summaries.iter_mut().zip(row_group.columns()) {
+            summary.stats.add_chunk(
+                chunk.statistics(),
+                chunk.num_values(),
+                &summary.column,
+            );
+        }
+    }
+
+    let missing: Vec<usize> = summaries
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.stats.complete && s.column.max_rep_level() == 0)
+        .map(|(i, _)| i)
+        .collect();
+    if !compute || missing.is_empty() {
+        return Ok(summaries);
+    }
+
+    let paths: Vec<String> = missing
+        .iter()
+        .map(|&i| summaries[i].column.path().string())
+        .collect();
+    debug!("Computing the statistics of: {}", paths.join(", "));
+
+    let columns: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+    let projection = get_projected_schema(metadata.file_metadata().schema(), &columns)?;
+    for &i in &missing {
+        summaries[i].stats = ColumnStats {
+            row_groups: summaries[i].stats.row_groups,
+            row_groups_with_statistics: summaries[i].stats.row_groups_with_statistics,
+            ..ColumnStats::new()
+        };
+        summaries[i].computed = true;
+    }
+
+    for row in parquet_reader.get_row_iter(Some(projection))? {
+        for (&i, path) in missing.iter().zip(&paths) {
+            let value = get_column(&row, path).and_then(StatValue::from_field);
+            summaries[i].stats.add_value(value);
+        }
+    }
+
+    Ok(summaries)
+}
+
+/// Interpret big endian two's complement bytes as a signed integer
+pub(crate) fn decimal_from_bytes(data: &[u8]) -> i128 {
+    let negative = data.first().map_or(false, |b| b & 0x80 != 0);
+    let mut value: i128 = if negative { -1 } else { 0 };
+    for byte in data.iter().rev().take(16).rev() {
+        value = (value << 8) | *byte as i128;
+    }
+
+    value
+}
+
+fn format_decimal(unscaled: i128, scale: i32) -> String {
+    if scale <= 0 {
+        return unscaled.to_string();
+    }
+
+    let digits = unscaled.abs().to_string();
+    let scale = scale as usize;
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    let sign = if unscaled < 0 { "-" } else { "" };
+    format!("{}{}.{}", sign, integer, fraction)
+}
+
+/// Convert the number of days since 1970-01-01 into a year, month and day,
+/// see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Format a time of day given in units of 10^-digits seconds
+fn format_time(value: i64, digits: u32) -> String {
+    let per_second = 10i64.pow(digits);
+    let seconds = value.div_euclid(per_second);
+    let fraction = value.rem_euclid(per_second);
+    format!(
+        "{:02}:{:02}:{:02}.{:0width$}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        fraction,
+        width = digits as usize
+    )
+}
+
+/// Format a timestamp given in units of 10^-digits seconds since the epoch
+fn format_timestamp(value: i64, digits: u32) -> String {
+    let per_day = 86_400 * 10i64.pow(digits);
+    format!(
+        "{} {}",
+        format_date(value.div_euclid(per_day)),
+        format_time(value.rem_euclid(per_day), digits)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_logical_types() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(18_644), "2021-01-17");
+        assert_eq!(format_date(-1), "1969-12-31");
+        assert_eq!(format_date(11_016), "2000-02-29");
+        assert_eq!(
+            format_timestamp(1_610_841_600_123, 3),
+            "2021-01-17 00:00:00.123"
+        );
+        assert_eq!(format_timestamp(-1, 6), "1969-12-31 23:59:59.999999");
+        assert_eq!(format_time(45_296_789, 3), "12:34:56.789");
+        assert_eq!(format_decimal(12345, 2), "123.45");
+        assert_eq!(format_decimal(-5, 3), "-0.005");
+        assert_eq!(format_decimal(42, 0), "42");
+    }
+
+    #[test]
+    fn it_decodes_decimal_bytes() {
+        assert_eq!(decimal_from_bytes(&[0x30, 0x39]), 12345);
+        assert_eq!(decimal_from_bytes(&[0xff, 0xfb]), -5);
+        assert_eq!(decimal_from_bytes(&[0x00, 0x00, 0x00, 0x80]), 128);
+        assert_eq!(decimal_from_bytes(&[]), 0);
+    }
+}
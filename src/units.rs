@@ -0,0 +1,175 @@
+use std::fmt;
+
+/// The systems of units used to print and parse sizes in bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitSystem {
+    /// Powers of 1024: KiB, MiB, GiB, TiB, PiB
+    Iec,
+    /// Powers of 1000: kB, MB, GB, TB, PB
+    Si,
+}
+
+impl UnitSystem {
+    fn base(self) -> f64 {
+        match self {
+            UnitSystem::Iec => 1024.0,
+            UnitSystem::Si => 1000.0,
+        }
+    }
+
+    /// The names of the units, from the smallest to the biggest
+    fn units(self) -> [&'static str; 5] {
+        match self {
+            UnitSystem::Iec => ["KiB", "MiB", "GiB", "TiB", "PiB"],
+            UnitSystem::Si => ["kB", "MB", "GB", "TB", "PB"],
+        }
+    }
+}
+
+/// Format the given size using the biggest unit that keeps the value at or above 1,
+/// with the given number of decimals. Sizes below 1 KiB (or 1 kB) are printed in bytes.
+pub fn format_size(bytes: i64, system: UnitSystem, precision: usize) -> String {
+    let base = system.base();
+    let mut value = bytes as f64;
+    if value.abs() < base {
+        return format!("{} Bytes", bytes);
+    }
+
+    let units = system.units();
+    let mut unit = 0;
+    value /= base;
+    // move on to the next unit when the value would be rounded up to the base,
+    // so that 1048575 bytes are printed as 1.00 MiB instead of 1024.00 KiB
+    while unit + 1 < units.len() && round(value.abs(), precision) >= base {
+        value /= base;
+        unit += 1;
+    }
+
+    format!("{:.*} {}", precision, value, units[unit])
+}
+
+fn round(value: f64, precision: usize) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    (value * factor).round() / factor
+}
+
+/// The reasons a size cannot be parsed
+#[derive(Debug, PartialEq)]
+pub enum ParseSizeError {
+    /// The number is missing, negative or cannot be parsed
+    InvalidNumber(String),
+    /// The unit is not one of B, kB, MB, GB, TB, PB, KiB, MiB, GiB, TiB or PiB
+    UnknownUnit(String),
+}
+
+impl fmt::Display for ParseSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSizeError::InvalidNumber(size) => write!(f, "Invalid size: {:?}", size),
+            ParseSizeError::UnknownUnit(unit) => write!(
+                f,
+                "Unknown unit {:?}, expected one of B, kB, MB, GB, TB, PB, KiB, MiB, GiB, TiB, PiB",
+                unit
+            ),
+        }
+    }
+}
+
+/// Parse a size such as `128MiB`, `1.5 GB` or `4096` (bytes) into a number of bytes.
+/// Units are case insensitive, fractional sizes are rounded to the nearest byte.
+pub fn parse_size(size: &str) -> Result<u64, ParseSizeError> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| ParseSizeError::InvalidNumber(size.to_string()))?;
+
+    let unit = unit.trim();
+    let multiplier = if unit.is_empty() || unit.eq_ignore_ascii_case("b") {
+        1.0
+    } else {
+        [UnitSystem::Iec, UnitSystem::Si]
+            .iter()
+            .find_map(|system| {
+                system
+                    .units()
+                    .iter()
+                    .position(|u| u.eq_ignore_ascii_case(unit))
+                    .map(|i| system.base().powi(i as i32 + 1))
+            })
+            .ok_or_else(|| ParseSizeError::UnknownUnit(unit.to_string()))?
+    };
+
+    Ok((value * multiplier).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_sizes() {
+        assert_eq!(format_size(0, UnitSystem::Iec, 3), "0 Bytes");
+        assert_eq!(format_size(1023, UnitSystem::Iec, 3), "1023 Bytes");
+        assert_eq!(format_size(1024, UnitSystem::Iec, 3), "1.000 KiB");
+        assert_eq!(format_size(63085, UnitSystem::Iec, 3), "61.606 KiB");
+        assert_eq!(format_size(63085, UnitSystem::Iec, 0), "62 KiB");
+        assert_eq!(format_size(2040109465, UnitSystem::Iec, 1), "1.9 GiB");
+        assert_eq!(format_size(999, UnitSystem::Si, 2), "999 Bytes");
+        assert_eq!(format_size(1000, UnitSystem::Si, 2), "1.00 kB");
+        assert_eq!(format_size(1_500_000, UnitSystem::Si, 2), "1.50 MB");
+        assert_eq!(format_size(-2048, UnitSystem::Iec, 1), "-2.0 KiB");
+    }
+
+    #[test]
+    fn it_moves_to_the_next_unit_when_rounding_up() {
+        assert_eq!(format_size(1024 * 1024 - 1, UnitSystem::Iec, 2), "1.00 MiB");
+        assert_eq!(
+            format_size(1024 * 1024 - 1, UnitSystem::Iec, 3),
+            "1023.999 KiB"
+        );
+        assert_eq!(format_size(999_999, UnitSystem::Si, 2), "1.00 MB");
+        // there is no unit after PiB
+        assert_eq!(format_size(i64::MAX, UnitSystem::Iec, 0), "8192 PiB");
+    }
+
+    #[test]
+    fn it_parses_sizes() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("12B"), Ok(12));
+        assert_eq!(parse_size("128MiB"), Ok(128 * 1024 * 1024));
+        assert_eq!(parse_size("128mib"), Ok(128 * 1024 * 1024));
+        assert_eq!(parse_size("1.5 KiB"), Ok(1536));
+        assert_eq!(parse_size("2kB"), Ok(2000));
+        assert_eq!(parse_size("1GB"), Ok(1_000_000_000));
+        assert_eq!(parse_size("1PiB"), Ok(1 << 50));
+    }
+
+    #[test]
+    fn it_rejects_invalid_sizes() {
+        assert!(matches!(
+            parse_size(""),
+            Err(ParseSizeError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_size("MiB"),
+            Err(ParseSizeError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_size("-1MiB"),
+            Err(ParseSizeError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_size("1.2.3"),
+            Err(ParseSizeError::InvalidNumber(_))
+        ));
+        assert_eq!(
+            parse_size("12 parsecs"),
+            Err(ParseSizeError::UnknownUnit(String::from("parsecs")))
+        );
+    }
+}
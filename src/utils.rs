@@ -1,23 +1,38 @@
 use crate::errors::PQRSError;
-use crate::errors::PQRSError::CouldNotOpenFile;
+use crate::errors::PQRSError::{CouldNotOpenFile, SchemaMismatch, UnknownColumn};
+use crate::expression::Expr;
+use crate::output::{is_struct, RowPrinter};
+use crate::report::Entry;
+use arrow::array::{new_null_array, Array, ArrayRef};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatchReader;
 use arrow::{datatypes::Schema, record_batch::RecordBatch};
 use log::debug;
-use parquet::arrow::{ArrowReader, ArrowWriter, ParquetFileArrowReader};
+use parquet::arrow::{
+    parquet_to_arrow_schema, ArrowReader, ArrowWriter, ParquetFileArrowReader,
+};
+use parquet::basic::{Compression, ConvertedType, Encoding};
+use parquet::file::properties::WriterProperties;
 use parquet::file::reader::{FileReader, SerializedFileReader};
 use parquet::record::Row;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use parquet::schema::types::{ColumnDescriptor, SchemaDescriptor, Type};
+use parquet_format::FileMetaData;
+use rand::rngs::StdRng;
+use rand::seq::{index, SliceRandom};
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::ops::Add;
 use std::path::Path;
 use std::sync::Arc;
+use thrift::protocol::TCompactInputProtocol;
 
-// calculate the sizes in bytes for one KiB, MiB, GiB, TiB, PiB
-static ONE_KI_B: i64 = 1024;
-static ONE_MI_B: i64 = ONE_KI_B * 1024;
-static ONE_GI_B: i64 = ONE_MI_B * 1024;
-static ONE_TI_B: i64 = ONE_GI_B * 1024;
-static ONE_PI_B: i64 = ONE_TI_B * 1024;
+// the number of records read from a parquet file in a single record batch
+pub(crate) static BATCH_SIZE: usize = 1024;
 
 /// Check if a particular path is present on the filesystem
 pub fn check_path_present(file_path: &str) -> bool {
@@ -35,75 +50,400 @@ pub fn open_file(file_name: &str) -> Result<File, PQRSError> {
     Ok(file)
 }
 
-/// Print the given number of records in either json or json-like format
+/// Return the schema the records should be read with, either the full file schema
+/// or a projection of it that only contains the given columns
+fn get_read_schema(
+    parquet_reader: &SerializedFileReader<File>,
+    columns: Option<&[&str]>,
+) -> Result<Type, PQRSError> {
+    let file_schema = parquet_reader.metadata().file_metadata().schema();
+    match columns {
+        Some(columns) => get_projected_schema(file_schema, columns),
+        None => Ok(file_schema.clone()),
+    }
+}
+
+/// Print the given number of records using the given printer. Only the records that
+/// match the filter are printed, row groups that cannot contain any matching records
+/// based on their statistics are skipped without being read.
 pub fn print_rows(
     file: File,
     num_records: Option<i64>,
-    json: bool,
+    columns: Option<&[&str]>,
+    filter: Option<&Expr>,
+    printer: &mut RowPrinter,
 ) -> Result<(), PQRSError> {
+    let null_counts = match filter {
+        Some(_) => get_null_counts(&file)?,
+        None => Vec::new(),
+    };
     let parquet_reader = SerializedFileReader::new(file)?;
-    // get_row_iter allows us to iterate the parquet file one record at a time
-    let mut iter = parquet_reader.get_row_iter(None)?;
-
-    let mut start: i64 = 0;
-    let end: i64 = num_records.unwrap_or(0);
-    // if num_records is None, print all the files
-    let all_records = num_records.is_none();
-
-    // print either all records, or the requested number of records
-    while all_records || start < end {
-        match iter.next() {
-            Some(row) => {
-                println!("CurrentRow {} {}", start, end);
-                print_row(&row, json);
+    let file_schema = parquet_reader.metadata().file_metadata().schema();
+    // the records are printed using the requested columns only
+    let schema = get_read_schema(&parquet_reader, columns)?;
+
+    // the columns used by the filter have to be read as well, even if they are not printed
+    let filter_columns = filter.map(|f| f.columns()).unwrap_or_default();
+    let filter_columns: Vec<&str> = filter_columns.iter().map(|c| c.as_str()).collect();
+    if !filter_columns.is_empty() {
+        // make sure the columns used by the filter are present in the file
+        get_projected_schema(file_schema, &filter_columns)?;
+    }
+
+    // passing a projection makes sure that the column chunks that are not selected are never read
+    let projection = match columns {
+        Some(columns) if !filter_columns.is_empty() => {
+            let mut all_columns = columns.to_vec();
+            all_columns.extend(filter_columns.iter());
+            Some(get_projected_schema(file_schema, &all_columns)?)
+        }
+        Some(_) => Some(schema.clone()),
+        None => None,
+    };
+
+    let num_row_groups = parquet_reader.num_row_groups();
+    let mut pruned_row_groups = 0;
+    let mut printed: i64 = 0;
+
+    'row_groups: for i in 0..num_row_groups {
+        if let Some(filter) = filter {
+            if !filter.may_match(parquet_reader.metadata().row_group(i), &null_counts[i])
+            {
+                pruned_row_groups += 1;
+                continue;
+            }
+        }
+
+        // get_row_iter allows us to iterate the row group one record at a time
+        let row_group_reader = parquet_reader.get_row_group(i)?;
+        for row in row_group_reader.get_row_iter(projection.clone())? {
+            // print either all records, or the requested number of records
+            if num_records.map_or(false, |n| printed >= n) {
+                break 'row_groups;
+            }
+
+            if filter.map_or(true, |f| f.matches(&row)) {
+                printer.print(&row, &schema)?;
+                printed += 1;
             }
-            None => break,
         }
-        start += 1;
+    }
+
+    if let Some(filter) = filter {
+        debug!(
+            "Pruned {} out of {} row groups using the filter: {}",
+            pruned_row_groups, num_row_groups, filter
+        );
     }
 
     Ok(())
 }
 
-/// Print the random sample of given size in either json or json-like format
+/// Return the null counts of the column chunks of every row group, as they are written in
+/// the file metadata. The statistics parsed by the parquet reader report a missing null
+/// count as 0, which cannot be told apart from a column chunk without nulls.
+fn get_null_counts(file: &File) -> Result<Vec<Vec<Option<i64>>>, PQRSError> {
+    // the file metadata is followed by its length and the magic number
+    let mut file = file.try_clone()?;
+    let mut buffer = [0; 8];
+    file.seek(SeekFrom::End(-8))?;
+    file.read_exact(&mut buffer)?;
+    let metadata_length =
+        u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+    let mut metadata = vec![0; metadata_length as usize];
+    file.seek(SeekFrom::End(-8 - metadata_length as i64))?;
+    file.read_exact(&mut metadata)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut protocol = TCompactInputProtocol::new(metadata.as_slice());
+    let metadata = FileMetaData::read_from_in_protocol(&mut protocol)?;
+    Ok(metadata
+        .row_groups
+        .iter()
+        .map(|row_group| {
+            row_group
+                .columns
+                .iter()
+                .map(|chunk| {
+                    chunk
+                        .meta_data
+                        .as_ref()
+                        .and_then(|meta_data| meta_data.statistics.as_ref())
+                        .and_then(|statistics| statistics.null_count)
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// The number of records to sample from a file
+#[derive(Debug, Clone, Copy)]
+pub enum SampleSize {
+    /// Exactly this many records, or all of them if the file is smaller
+    Records(usize),
+    /// Every record is picked independently with this probability
+    Fraction(f64),
+}
+
+/// How the sampled records are picked from the file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMethod {
+    /// Every record in the file has the same chance of being picked
+    Uniform,
+    /// Whole row groups are picked at random, which is faster but the records
+    /// in the sample are not independent of each other
+    RowGroupCluster,
+}
+
+/// The settings used to pick a random sample of records
+#[derive(Debug)]
+pub struct SampleOptions {
+    pub size: SampleSize,
+    pub method: SampleMethod,
+    /// Seed for the random number generator, to get the same sample every time
+    pub seed: Option<u64>,
+    /// Print the sampled records in the order they appear in the file
+    pub preserve_order: bool,
+}
+
+/// Print a random sample of records using the given printer. The records to sample
+/// are picked using the row counts from the metadata, so only the row groups that
+/// contain sampled records are read and only the sampled records are kept in memory.
 pub fn print_rows_random(
     file: File,
-    sample_size: i64,
-    json: bool,
+    options: &SampleOptions,
+    columns: Option<&[&str]>,
+    printer: &mut RowPrinter,
 ) -> Result<(), PQRSError> {
-    let parquet_reader = SerializedFileReader::new(file.try_clone()?)?;
-    let mut iter = parquet_reader.get_row_iter(None)?;
-
-    // find the number of records present in the file
-    let total_records_in_file: i64 = get_row_count(file)?;
-    // push all the indexes into the vector initially
-    let mut indexes = (0..total_records_in_file).collect::<Vec<_>>();
-    debug!("Original indexes: {:?}", indexes);
-
-    // shuffle the indexes to randomize the vector
-    let mut rng = thread_rng();
-    indexes.shuffle(&mut rng);
-    debug!("Shuffled indexes: {:?}", indexes);
-
-    // take only the given number of records from the vector
-    indexes = indexes
-        .into_iter()
-        .take(sample_size as usize)
-        .collect::<Vec<_>>();
+    let parquet_reader = SerializedFileReader::new(file)?;
+    let schema = get_read_schema(&parquet_reader, columns)?;
+    let projection = columns.map(|_| schema.clone());
+
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    // the first record index and the number of records for every row group
+    let mut row_groups = Vec::new();
+    let mut total_records_in_file = 0;
+    for row_group in parquet_reader.metadata().row_groups() {
+        let num_rows = row_group.num_rows() as usize;
+        row_groups.push((total_records_in_file, num_rows));
+        total_records_in_file += num_rows;
+    }
+
+    if options.method == SampleMethod::RowGroupCluster {
+        let mut picked = pick_row_groups(&row_groups, options.size, &mut rng);
+        if options.preserve_order {
+            picked.sort_unstable();
+        }
+        debug!(
+            "Read {} out of {} row groups for the sample",
+            picked.len(),
+            row_groups.len()
+        );
+
+        // a fixed number of records is not necessarily made of whole row groups
+        let limit = match options.size {
+            SampleSize::Records(sample_size) => sample_size,
+            SampleSize::Fraction(_) => total_records_in_file,
+        };
+        let mut printed = 0;
+        'row_groups: for i in picked {
+            let row_group_reader = parquet_reader.get_row_group(i)?;
+            for row in row_group_reader.get_row_iter(projection.clone())? {
+                if printed >= limit {
+                    break 'row_groups;
+                }
+                printer.print(&row, &schema)?;
+                printed += 1;
+            }
+        }
+
+        return Ok(());
+    }
 
+    // the sampled indexes are in random order unless the file order is preserved
+    let mut indexes = pick_indexes(total_records_in_file, options.size, &mut rng);
+    let mut sorted = indexes.clone();
+    sorted.sort_unstable();
+    if options.preserve_order {
+        indexes = sorted.clone();
+    }
     debug!("Sampled indexes: {:?}", indexes);
 
-    let mut start: i64 = 0;
-    while let Some(row) = iter.next() {
-        if indexes.contains(&start) {
-            print_row(&row, json)
+    // the sampled rows are printed as soon as possible, rows that are read
+    // before their turn comes are kept until then. The records of a row group
+    // are decoded up to the last sampled one, as pages cannot be skipped without
+    // an offset index which this parquet version does not read.
+    let mut pending: HashMap<usize, Row> = HashMap::new();
+    let mut next = 0;
+    let mut wanted = sorted.into_iter().peekable();
+    let mut read_row_groups = 0;
+    for (i, &(first, num_rows)) in row_groups.iter().enumerate() {
+        let end = first + num_rows;
+        // row groups without any sampled records are never read
+        if !matches!(wanted.peek(), Some(&index) if index < end) {
+            continue;
+        }
+
+        read_row_groups += 1;
+        let row_group_reader = parquet_reader.get_row_group(i)?;
+        let rows = row_group_reader.get_row_iter(projection.clone())?;
+        for (current, row) in (first..).zip(rows) {
+            match wanted.peek() {
+                Some(&index) if index == current => {
+                    wanted.next();
+                    pending.insert(current, row);
+                }
+                Some(&index) if index < end => continue,
+                // no other record is sampled from this row group
+                _ => break,
+            }
+
+            while let Some(row) = indexes.get(next).and_then(|i| pending.remove(i)) {
+                printer.print(&row, &schema)?;
+                next += 1;
+            }
         }
-        start += 1;
     }
 
+    debug!(
+        "Read {} out of {} row groups for the sample",
+        read_row_groups,
+        row_groups.len()
+    );
+
     Ok(())
 }
 
+/// Pick the indexes of the sampled records, in random order
+fn pick_indexes(total: usize, size: SampleSize, rng: &mut StdRng) -> Vec<usize> {
+    match size {
+        SampleSize::Records(sample_size) => {
+            index::sample(rng, total, sample_size.min(total)).into_vec()
+        }
+        SampleSize::Fraction(fraction) => {
+            let mut indexes: Vec<usize> =
+                (0..total).filter(|_| rng.gen_bool(fraction)).collect();
+            indexes.shuffle(rng);
+            indexes
+        }
+    }
+}
+
+/// Pick whole row groups at random, in random order. For a fixed number of records,
+/// row groups are picked until they contain enough records.
+fn pick_row_groups(
+    row_groups: &[(usize, usize)],
+    size: SampleSize,
+    rng: &mut StdRng,
+) -> Vec<usize> {
+    let mut picked: Vec<usize> = (0..row_groups.len()).collect();
+    picked.shuffle(rng);
+
+    match size {
+        SampleSize::Records(sample_size) => {
+            let mut records = 0;
+            picked
+                .into_iter()
+                .take_while(|&i| {
+                    let enough = records >= sample_size;
+                    records += row_groups[i].1;
+                    !enough
+                })
+                .collect()
+        }
+        SampleSize::Fraction(fraction) => picked
+            .into_iter()
+            .filter(|_| rng.gen_bool(fraction))
+            .collect(),
+    }
+}
+
+/// Return a projection of the given schema that only contains the given columns.
+/// Nested fields can be selected using a dotted path, e.g. `country.name`
+pub fn get_projected_schema(schema: &Type, columns: &[&str]) -> Result<Type, PQRSError> {
+    let paths: Vec<Vec<&str>> = columns.iter().map(|c| c.split('.').collect()).collect();
+
+    // make sure all the columns are present before building the projection
+    for (column, path) in columns.iter().zip(&paths) {
+        if !is_valid_path(schema, path) {
+            let mut available = Vec::new();
+            get_column_paths(schema, "", &mut available);
+            return Err(UnknownColumn(column.to_string(), available.join(", ")));
+        }
+    }
+
+    project_group(schema, &paths)
+}
+
+/// Check if the given path resolves to a field, only nested groups can be traversed
+fn is_valid_path(group: &Type, path: &[&str]) -> bool {
+    match group.get_fields().iter().find(|f| f.name() == path[0]) {
+        Some(_) if path.len() == 1 => true,
+        Some(field) if is_struct(field) => is_valid_path(field, &path[1..]),
+        _ => false,
+    }
+}
+
+/// Collect the dotted paths of all the fields that can be selected
+fn get_column_paths(group: &Type, prefix: &str, paths: &mut Vec<String>) {
+    for field in group.get_fields() {
+        let path = if prefix.is_empty() {
+            field.name().to_string()
+        } else {
+            format!("{}.{}", prefix, field.name())
+        };
+
+        paths.push(path.clone());
+        if is_struct(field) {
+            get_column_paths(field, &path, paths);
+        }
+    }
+}
+
+/// Build a copy of the given group that only keeps the fields selected by the paths.
+/// The field order of the original schema is maintained.
+fn project_group(group: &Type, paths: &[Vec<&str>]) -> Result<Type, PQRSError> {
+    let mut fields = Vec::new();
+    for field in group.get_fields() {
+        let selected: Vec<Vec<&str>> = paths
+            .iter()
+            .filter(|path| path[0] == field.name())
+            .map(|path| path[1..].to_vec())
+            .collect();
+
+        if selected.is_empty() {
+            continue;
+        }
+
+        if selected.iter().any(|path| path.is_empty()) {
+            // the field itself was selected, keep all of its children
+            fields.push(field.clone());
+        } else {
+            fields.push(Arc::new(project_group(field, &selected)?));
+        }
+    }
+
+    // projections are matched against the file schema, so the type information of the
+    // group needs to be preserved. The root of the schema does not have a repetition.
+    let info = group.get_basic_info();
+    let mut builder = Type::group_type_builder(info.name())
+        .with_converted_type(info.converted_type())
+        .with_logical_type(info.logical_type())
+        .with_fields(&mut fields);
+    if info.has_repetition() {
+        builder = builder.with_repetition(info.repetition());
+    }
+    if info.has_id() {
+        builder = builder.with_id(info.id());
+    }
+
+    Ok(builder.build()?)
+}
+
 /// A representation of Parquet file in a form that can be used for merging
 #[derive(Debug)]
 pub struct ParquetData {
@@ -135,19 +475,67 @@ impl Add for ParquetData {
     }
 }
 
-/// Return the row batches, rows and schema for a given parquet file
-pub fn get_row_batches(input: &str) -> Result<ParquetData, PQRSError> {
+/// Return the arrow schema of the given parquet file, only the footer is read and the
+/// file is closed before returning
+pub fn get_arrow_schema(input: &str) -> Result<Schema, PQRSError> {
+    let file = open_file(input)?;
+    let file_reader = SerializedFileReader::new(file)?;
+    let file_metadata = file_reader.metadata().file_metadata();
+
+    Ok(parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )?)
+}
+
+/// Return the arrow schema of the given parquet file along with a reader that reads the
+/// file one record batch at a time. Only the footer is read until the reader is iterated.
+pub fn get_batch_reader(
+    input: &str,
+) -> Result<(Schema, Box<dyn RecordBatchReader>), PQRSError> {
     let file = open_file(input)?;
-    let file_reader = SerializedFileReader::new(file).unwrap();
+    let file_reader = SerializedFileReader::new(file)?;
     let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
 
     let schema = arrow_reader.get_schema()?;
-    let record_batch_reader = arrow_reader.get_record_reader(1024)?;
+    let record_batch_reader = arrow_reader.get_record_reader(BATCH_SIZE)?;
+
+    Ok((schema, Box::new(record_batch_reader)))
+}
+
+/// Like `get_batch_reader`, but only the leaf columns selected by the given paths are
+/// read, the column chunks of the other columns are never decoded
+pub fn get_projected_batch_reader(
+    input: &str,
+    columns: &[&str],
+) -> Result<(Schema, Box<dyn RecordBatchReader>), PQRSError> {
+    let file = open_file(input)?;
+    let file_reader = SerializedFileReader::new(file)?;
+
+    let mut indices = Vec::new();
+    for column in columns {
+        let schema_descr = file_reader.metadata().file_metadata().schema_descr();
+        indices.extend(get_column_indices(schema_descr, Some(column))?);
+    }
+    indices.sort_unstable();
+    indices.dedup();
+
+    let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+    let schema = arrow_reader.get_schema_by_columns(indices.clone(), true)?;
+    let record_batch_reader =
+        arrow_reader.get_record_reader_by_columns(indices, BATCH_SIZE)?;
+
+    Ok((schema, Box::new(record_batch_reader)))
+}
+
+/// Return the row batches, rows and schema for a given parquet file
+pub fn get_row_batches(input: &str) -> Result<ParquetData, PQRSError> {
+    let (schema, record_batch_reader) = get_batch_reader(input)?;
     let mut batches: Vec<RecordBatch> = Vec::new();
 
     let mut rows = 0;
     for maybe_batch in record_batch_reader {
-        let record_batch = maybe_batch.unwrap();
+        let record_batch = maybe_batch?;
         rows += record_batch.num_rows();
 
         batches.push(record_batch);
@@ -160,19 +548,49 @@ pub fn get_row_batches(input: &str) -> Result<ParquetData, PQRSError> {
     })
 }
 
-/// Write a parquet file to the output location based on the given parquet input
-pub fn write_parquet(data: ParquetData, output: &str) -> Result<(), PQRSError> {
+/// Return the compression codec used by the first column chunk of the given file,
+/// files without any data are considered to be uncompressed
+pub fn get_compression(file_name: &str) -> Result<Compression, PQRSError> {
+    let file = open_file(file_name)?;
+    let reader = SerializedFileReader::new(file)?;
+    let compression = reader
+        .metadata()
+        .row_groups()
+        .first()
+        .and_then(|row_group| row_group.columns().first())
+        .map(|column| column.compression())
+        .unwrap_or(Compression::UNCOMPRESSED);
+
+    Ok(compression)
+}
+
+/// Write the given record batches to a parquet file at the output location and return
+/// the number of rows written. Batches are written as soon as they are produced, so
+/// only the batch being written needs to be held in memory.
+pub fn write_parquet<I>(
+    schema: &Schema,
+    batches: I,
+    output: &str,
+    props: WriterProperties,
+) -> Result<usize, PQRSError>
+where
+    I: IntoIterator<Item = ArrowResult<RecordBatch>>,
+{
     let file = File::create(output)?;
-    let fields = data.schema.fields().to_vec();
+    let fields = schema.fields().to_vec();
     // the schema from the record batch might not contain the file specific metadata
     // drop the schema to make sure that we don't fail in that case
     let schema_without_metadata = Schema::new(fields);
 
-    let mut writer = ArrowWriter::try_new(file, Arc::new(schema_without_metadata), None)?;
+    let mut writer =
+        ArrowWriter::try_new(file, Arc::new(schema_without_metadata), Some(props))?;
 
     // write record batches one at a time
     // record batches are not combined
-    for record_batch in data.batches.iter() {
+    let mut rows = 0;
+    for record_batch in batches {
+        let record_batch = record_batch?;
+        rows += record_batch.num_rows();
         writer.write(&record_batch)?;
     }
 
@@ -180,29 +598,211 @@ pub fn write_parquet(data: ParquetData, output: &str) -> Result<(), PQRSError> {
     // if the writer is not closed properly, the metadata footer needed by the parquet
     // format would be corrupt
     writer.close()?;
-    Ok(())
+    Ok(rows)
+}
+
+/// How the schemas of the inputs are combined when merging files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaMode {
+    /// All the inputs must have the same columns with the same types
+    Strict,
+    /// Keep every column present in any input, filling missing columns with nulls
+    Union,
+    /// Keep only the columns present in all the inputs
+    Intersect,
 }
 
-/// Print the given parquet rows in json or json-like format
-fn print_row(row: &Row, use_json: bool) {
-    if use_json {
-        println!("{}", row.to_json_value());
-    } else {
-        println!("{}", row.to_string());
+/// Combine the schemas of the given inputs into the schema used for the output.
+/// Columns are matched by name and compatible types are widened, e.g. Int32 to Int64.
+pub fn merge_schemas(
+    inputs: &[(&str, &Schema)],
+    mode: SchemaMode,
+) -> Result<Schema, PQRSError> {
+    let (first_name, first_schema) = inputs[0];
+
+    if mode == SchemaMode::Strict {
+        let mut differences = Vec::new();
+        for (name, schema) in &inputs[1..] {
+            differences.append(&mut get_schema_differences(
+                first_name,
+                first_schema,
+                name,
+                schema,
+            ));
+        }
+        if !differences.is_empty() {
+            return Err(SchemaMismatch(differences.join("; ")));
+        }
+    }
+
+    let mut fields: Vec<Field> = Vec::new();
+    for (name, schema) in inputs {
+        for field in schema.fields() {
+            match fields.iter_mut().find(|f| f.name() == field.name()) {
+                Some(existing) => {
+                    let data_type = widen_type(existing.data_type(), field.data_type())
+                        .ok_or_else(|| {
+                        SchemaMismatch(format!(
+                            "column {} is {:?} in some inputs but {:?} in {}",
+                            field.name(),
+                            existing.data_type(),
+                            field.data_type(),
+                            name
+                        ))
+                    })?;
+                    let nullable = existing.is_nullable() || field.is_nullable();
+                    *existing = Field::new(field.name(), data_type, nullable);
+                }
+                None => fields.push(Field::new(
+                    field.name(),
+                    field.data_type().clone(),
+                    field.is_nullable(),
+                )),
+            }
+        }
     }
+
+    // a column that is missing from some of the inputs is either dropped or
+    // has to be nullable so that it can be filled with nulls
+    let is_missing = |field: &Field| {
+        inputs
+            .iter()
+            .any(|(_, schema)| schema.field_with_name(field.name()).is_err())
+    };
+    let fields: Vec<Field> = match mode {
+        SchemaMode::Intersect => fields.into_iter().filter(|f| !is_missing(f)).collect(),
+        _ => fields
+            .into_iter()
+            .map(|f| {
+                let nullable = f.is_nullable() || is_missing(&f);
+                Field::new(f.name(), f.data_type().clone(), nullable)
+            })
+            .collect(),
+    };
+
+    if fields.is_empty() {
+        return Err(SchemaMismatch(String::from(
+            "the inputs do not have any columns in common",
+        )));
+    }
+
+    Ok(Schema::new(fields))
 }
 
-/// Return the number of rows in the given parquet file
-pub fn get_row_count(file: File) -> Result<i64, PQRSError> {
-    let parquet_reader = SerializedFileReader::new(file)?;
-    let row_group_metadata = parquet_reader.metadata().row_groups();
-    // The parquet file is made up of blocks (also called row groups)
-    // The row group metadata contains information about all the row groups present in the data
-    // Each row group maintains the number of rows present in the block
-    // Summing across all the row groups contains the total number of rows present in the file
-    let total_num_rows = row_group_metadata.iter().map(|rg| rg.num_rows()).sum();
+/// Describe how the schema of an input differs from the schema of the first input
+fn get_schema_differences(
+    expected_name: &str,
+    expected: &Schema,
+    actual_name: &str,
+    actual: &Schema,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    for field in expected.fields() {
+        match actual.field_with_name(field.name()) {
+            Err(_) => differences.push(format!(
+                "column {} is missing from {}",
+                field.name(),
+                actual_name
+            )),
+            Ok(other) if other.data_type() != field.data_type() => {
+                differences.push(format!(
+                    "column {} is {:?} in {} but {:?} in {}",
+                    field.name(),
+                    field.data_type(),
+                    expected_name,
+                    other.data_type(),
+                    actual_name
+                ))
+            }
+            Ok(_) => {}
+        }
+    }
+    for field in actual.fields() {
+        if expected.field_with_name(field.name()).is_err() {
+            differences.push(format!(
+                "column {} is present in {} but not in {}",
+                field.name(),
+                actual_name,
+                expected_name
+            ));
+        }
+    }
+
+    differences
+}
+
+/// Return the type that can hold the values of both the given types without loss,
+/// if there is one. Only types of the same kind are widened, except for signed and
+/// unsigned integers which are widened to a larger signed integer, and integers of up
+/// to 32 bits and floats which can both be widened to Float64.
+fn widen_type(a: &DataType, b: &DataType) -> Option<DataType> {
+    if a == b {
+        return Some(a.clone());
+    }
+
+    // the kind of each type along with its rank within the kind
+    let rank = |data_type: &DataType| match data_type {
+        DataType::Int8 => Some((0, 1)),
+        DataType::Int16 => Some((0, 2)),
+        DataType::Int32 => Some((0, 3)),
+        DataType::Int64 => Some((0, 4)),
+        DataType::UInt8 => Some((1, 1)),
+        DataType::UInt16 => Some((1, 2)),
+        DataType::UInt32 => Some((1, 3)),
+        DataType::UInt64 => Some((1, 4)),
+        DataType::Float32 => Some((2, 1)),
+        DataType::Float64 => Some((2, 2)),
+        DataType::Utf8 => Some((3, 1)),
+        DataType::LargeUtf8 => Some((3, 2)),
+        _ => None,
+    };
 
-    Ok(total_num_rows)
+    match (rank(a)?, rank(b)?) {
+        ((kind_a, rank_a), (kind_b, rank_b)) if kind_a == kind_b => {
+            Some(if rank_a >= rank_b {
+                a.clone()
+            } else {
+                b.clone()
+            })
+        }
+        // the signed type needs one more bit than the unsigned one, so that the unsigned
+        // values are still in range. Nothing can hold both Int64 and UInt64.
+        ((0, signed), (1, unsigned)) | ((1, unsigned), (0, signed)) => {
+            match signed.max(unsigned + 1) {
+                2 => Some(DataType::Int16),
+                3 => Some(DataType::Int32),
+                4 => Some(DataType::Int64),
+                _ => None,
+            }
+        }
+        // 32 bit integers and floats can be represented exactly as doubles
+        ((kind_a, rank_a), (kind_b, rank_b))
+            if kind_a <= 2 && kind_b <= 2 && rank_a <= 3 && rank_b <= 3 =>
+        {
+            Some(DataType::Float64)
+        }
+        _ => None,
+    }
+}
+
+/// Convert the record batch to the given schema. Columns are matched by name, the
+/// columns missing from the batch are filled with nulls and the others are cast to
+/// the type in the schema.
+pub fn adapt_batch(batch: &RecordBatch, schema: &SchemaRef) -> ArrowResult<RecordBatch> {
+    let batch_schema = batch.schema();
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| match batch_schema.index_of(field.name()) {
+            Ok(index) if batch.column(index).data_type() == field.data_type() => {
+                Ok(batch.column(index).clone())
+            }
+            Ok(index) => cast(batch.column(index), field.data_type()),
+            Err(_) => Ok(new_null_array(field.data_type(), batch.num_rows())),
+        })
+        .collect::<ArrowResult<Vec<ArrayRef>>>()?;
+
+    RecordBatch::try_new(schema.clone(), columns)
 }
 
 /// Return the uncompressed and compressed size of the given file
@@ -213,8 +813,9 @@ pub fn get_size(file: File) -> Result<(i64, i64), PQRSError> {
     // Parquet format compresses data at a column level.
     // To calculate the size of the file (compressed or uncompressed), we need to sum
     // across all the row groups present in the parquet file. This is similar to how
-    // we calculate the row count in the method above.
-    // Do note that this size does not take the footer size into consideration.
+    // we calculate the row count for the file summary.
+    // Do note that this size does not take the footer size into consideration,
+    // use get_footer_size for that.
     let uncompressed_size = row_group_metadata
         .iter()
         .map(|rg| rg.total_byte_size())
@@ -227,30 +828,230 @@ pub fn get_size(file: File) -> Result<(i64, i64), PQRSError> {
     Ok((uncompressed_size, compressed_size))
 }
 
-/// Pretty print the given size using human readable format
-pub fn get_pretty_size(bytes: i64) -> String {
-    if bytes / ONE_KI_B < 1 {
-        return format!("{} Bytes", bytes);
+/// The metadata of a single file, as printed by the metadata commands
+/// when a structured output format is used
+pub struct FileSummary {
+    pub path: String,
+    pub rows: i64,
+    pub compressed_bytes: i64,
+    pub uncompressed_bytes: i64,
+    pub footer_bytes: u64,
+    /// The path and type of every leaf column
+    pub fields: Vec<(String, String)>,
+}
+
+impl FileSummary {
+    pub fn entry(&self) -> Entry {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, data_type)| json!({"name": name, "type": data_type}))
+            .collect();
+
+        vec![
+            ("path", json!(self.path)),
+            ("rows", json!(self.rows)),
+            ("compressed_bytes", json!(self.compressed_bytes)),
+            ("uncompressed_bytes", json!(self.uncompressed_bytes)),
+            ("footer_bytes", json!(self.footer_bytes)),
+            ("fields", Value::Array(fields)),
+        ]
+    }
+}
+
+/// Return the row count, sizes and columns of the given file, read from its metadata
+pub fn get_file_summary(file_name: &str) -> Result<FileSummary, PQRSError> {
+    let mut file = open_file(file_name)?;
+    let footer_bytes = get_footer_size(&mut file)?;
+    let parquet_reader = SerializedFileReader::new(file)?;
+    let metadata = parquet_reader.metadata();
+    let row_groups = metadata.row_groups();
+
+    let fields = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| (column.path().string(), get_column_type(column)))
+        .collect();
+
+    // each row group maintains the number of rows present in the block, and the sizes
+    // of its column chunks, summing across all the row groups gives the totals for the file
+    Ok(FileSummary {
+        path: file_name.to_string(),
+        rows: row_groups.iter().map(|rg| rg.num_rows()).sum(),
+        compressed_bytes: row_groups.iter().map(|rg| rg.compressed_size()).sum(),
+        uncompressed_bytes: row_groups.iter().map(|rg| rg.total_byte_size()).sum(),
+        footer_bytes,
+        fields,
+    })
+}
+
+/// Return the indices of the leaf columns selected by the given path, a group selects all
+/// of its nested columns and all the columns are selected when no path is given
+pub fn get_column_indices(
+    schema: &SchemaDescriptor,
+    column: Option<&str>,
+) -> Result<Vec<usize>, PQRSError> {
+    let paths: Vec<String> = schema.columns().iter().map(|c| c.path().string()).collect();
+    let column = match column {
+        Some(column) => column,
+        None => return Ok((0..paths.len()).collect()),
+    };
+
+    let prefix = format!("{}.", column);
+    let selected: Vec<usize> = (0..paths.len())
+        .filter(|&i| paths[i] == column || paths[i].starts_with(&prefix))
+        .collect();
+    if selected.is_empty() {
+        return Err(UnknownColumn(column.to_string(), paths.join(", ")));
+    }
+
+    Ok(selected)
+}
+
+/// Return the physical type of the column along with its converted type, if any
+pub fn get_column_type(column: &ColumnDescriptor) -> String {
+    match column.converted_type() {
+        ConvertedType::NONE => column.physical_type().to_string(),
+        converted_type => format!("{} ({})", column.physical_type(), converted_type),
     }
+}
+
+/// Return the totals of the row counts and sizes of the given files
+pub fn get_total_entry(summaries: &[FileSummary]) -> Entry {
+    vec![
+        ("rows", json!(summaries.iter().map(|s| s.rows).sum::<i64>())),
+        (
+            "compressed_bytes",
+            json!(summaries.iter().map(|s| s.compressed_bytes).sum::<i64>()),
+        ),
+        (
+            "uncompressed_bytes",
+            json!(summaries.iter().map(|s| s.uncompressed_bytes).sum::<i64>()),
+        ),
+        (
+            "footer_bytes",
+            json!(summaries.iter().map(|s| s.footer_bytes).sum::<u64>()),
+        ),
+    ]
+}
+
+/// Return the size of the footer of the given file, which is made up of the serialized
+/// file metadata followed by its length (4 bytes) and the magic number (4 bytes)
+pub fn get_footer_size(file: &mut File) -> Result<u64, PQRSError> {
+    let mut buffer = [0; 8];
+    file.seek(SeekFrom::End(-8))?;
+    file.read_exact(&mut buffer)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let metadata_length =
+        u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+    Ok(metadata_length as u64 + 8)
+}
 
-    if bytes / ONE_MI_B < 1 {
-        return format!("{:.3} KiB", bytes / ONE_KI_B);
+/// The sizes of a part of a file, either the footer, a single column chunk or the
+/// column chunks of a whole column or row group.
+pub struct PartSize {
+    /// Whether the part is the footer of the file rather than column chunks
+    pub footer: bool,
+    pub row_group: Option<usize>,
+    pub column: Option<String>,
+    pub compressed_bytes: i64,
+    pub uncompressed_bytes: i64,
+    pub codecs: Vec<Compression>,
+    pub encodings: Vec<Encoding>,
+}
+
+impl PartSize {
+    /// Return the ratio of uncompressed to compressed bytes
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed_bytes == 0 {
+            return 1.0;
+        }
+        self.uncompressed_bytes as f64 / self.compressed_bytes as f64
     }
 
-    if bytes / ONE_GI_B < 1 {
-        return format!("{:.3} MiB", bytes / ONE_MI_B);
+    pub fn codecs(&self) -> String {
+        let codecs: Vec<String> = self.codecs.iter().map(|c| c.to_string()).collect();
+        codecs.join(",")
     }
 
-    if bytes / ONE_TI_B < 1 {
-        return format!("{:.3} GiB", bytes / ONE_GI_B);
+    pub fn encodings(&self) -> String {
+        let encodings: Vec<String> =
+            self.encodings.iter().map(|e| e.to_string()).collect();
+        encodings.join(",")
     }
+}
+
+/// Return the sizes of the column chunks of the given file, summed by column and/or
+/// by row group, along with the footer as a part of its own so that the parts add up to
+/// the size of the file. The biggest parts come first.
+pub fn get_size_breakdown(
+    file_name: &str,
+    by_column: bool,
+    by_row_group: bool,
+) -> Result<Vec<PartSize>, PQRSError> {
+    let mut file = open_file(file_name)?;
+    let footer_bytes = get_footer_size(&mut file)? as i64;
+    let parquet_reader = SerializedFileReader::new(file)?;
 
-    if bytes / ONE_PI_B < 1 {
-        return format!("{:.3} TiB", bytes / ONE_TI_B);
+    let mut parts: Vec<PartSize> = Vec::new();
+    let mut positions: HashMap<(Option<usize>, Option<String>), usize> = HashMap::new();
+    for (i, row_group) in parquet_reader.metadata().row_groups().iter().enumerate() {
+        for chunk in row_group.columns() {
+            let row_group = if by_row_group { Some(i) } else { None };
+            let column = if by_column {
+                Some(chunk.column_path().string())
+            } else {
+                None
+            };
+
+            let position =
+                *positions
+                    .entry((row_group, column.clone()))
+                    .or_insert_with(|| {
+                        parts.push(PartSize {
+                            footer: false,
+                            row_group,
+                            column,
+                            compressed_bytes: 0,
+                            uncompressed_bytes: 0,
+                            codecs: Vec::new(),
+                            encodings: Vec::new(),
+                        });
+                        parts.len() - 1
+                    });
+
+            let part = &mut parts[position];
+            part.compressed_bytes += chunk.compressed_size();
+            part.uncompressed_bytes += chunk.uncompressed_size();
+            if !part.codecs.contains(&chunk.compression()) {
+                part.codecs.push(chunk.compression());
+            }
+            for encoding in chunk.encodings() {
+                if !part.encodings.contains(encoding) {
+                    part.encodings.push(*encoding);
+                }
+            }
+        }
     }
 
-    return format!("{:.3} PiB", bytes / ONE_PI_B);
+    // the footer is not compressed
+    parts.push(PartSize {
+        footer: true,
+        row_group: None,
+        column: None,
+        compressed_bytes: footer_bytes,
+        uncompressed_bytes: footer_bytes,
+        codecs: Vec::new(),
+        encodings: Vec::new(),
+    });
+
+    parts.sort_by(|a, b| b.compressed_bytes.cmp(&a.compressed_bytes));
+    Ok(parts)
 }
+
 #[cfg(test)]
 mod tests {
 
@@ -263,6 +1064,34 @@ mod tests {
     use parquet::file::properties::WriterProperties;
     use std::fs;
     use parquet::file::writer::{SerializedFileWriter, FileWriter};
+    use arrow::datatypes::DataType;
+    use super::widen_type;
+
+    #[test]
+    fn it_widens_signed_and_unsigned_integers() {
+        let widened = |a, b| widen_type(&a, &b);
+        assert_eq!(
+            widened(DataType::UInt8, DataType::Int8),
+            Some(DataType::Int16)
+        );
+        assert_eq!(
+            widened(DataType::Int16, DataType::UInt16),
+            Some(DataType::Int32)
+        );
+        assert_eq!(
+            widened(DataType::UInt32, DataType::Int32),
+            Some(DataType::Int64)
+        );
+        assert_eq!(
+            widened(DataType::UInt8, DataType::Int32),
+            Some(DataType::Int32)
+        );
+        assert_eq!(widened(DataType::UInt64, DataType::Int8), None);
+        assert_eq!(
+            widened(DataType::UInt32, DataType::Float32),
+            Some(DataType::Float64)
+        );
+    }
 
     #[test]
     fn it_writes_data() {
@@ -278,8 +1107,7 @@ mod tests {
 ";
         let schema = Arc::new(parse_message_type(message_type).unwrap());
         let props = Arc::new(WriterProperties::builder().build());
-        let file =
-            fs::File::create(&path).unwrap();
+        let file = fs::File::create(&path).unwrap();
         let mut writer = SerializedFileWriter::new(file, schema, props).unwrap();
         for _group in 0..1 {
             let mut row_group_writer = writer.next_row_group().unwrap();
@@ -307,7 +1135,9 @@ mod tests {
                     ByteArray::from(s.as_ref())
                 })
                 .collect();
-            while let Some(mut col_writer) = row_group_writer.next_column().expect("next column") {
+            while let Some(mut col_writer) =
+                row_group_writer.next_column().expect("next column")
+            {
                 match col_writer {
                     // ... write values to a column writer
                     // You can also use `get_typed_column_writer` method to extract typed writer.
@@ -350,4 +1180,4 @@ mod tests {
         let bytes = fs::read(&path).unwrap();
         assert_eq!(&bytes[0..4], &[b'P', b'A', b'R', b'1']);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,215 @@
+use crate::units::parse_size;
+use clap::{Arg, ArgMatches};
+use parquet::basic::Compression;
+use parquet::file::properties::{
+    WriterProperties, WriterPropertiesBuilder, WriterVersion,
+};
+use parquet::schema::types::ColumnPath;
+use std::fmt;
+
+/// The settings used when writing parquet files, anything that is not set
+/// falls back to the defaults of the parquet writer
+pub struct WriterOptions<'a> {
+    compression: Option<Compression>,
+    max_row_group_size: Option<usize>,
+    data_page_size: Option<usize>,
+    dictionary: bool,
+    dictionary_columns: Vec<(&'a str, bool)>,
+    statistics: bool,
+    writer_version: WriterVersion,
+    created_by: Option<&'a str>,
+}
+
+impl<'a> WriterOptions<'a> {
+    /// Return the clap arguments used to configure the parquet writer
+    pub(crate) fn args() -> Vec<Arg<'static, 'static>> {
+        vec![
+            Arg::with_name("compression")
+                .long("compression")
+                .takes_value(true)
+                .required(false)
+                .possible_values(&[
+                    "uncompressed",
+                    "snappy",
+                    "gzip",
+                    "lzo",
+                    "brotli",
+                    "lz4",
+                    "zstd",
+                ])
+                .help("The compression codec to use, defaults to the codec of the first input or to snappy when importing"),
+            Arg::with_name("max-row-group-size")
+                .long("max-row-group-size")
+                .takes_value(true)
+                .required(false)
+                .value_name("ROWS")
+                .validator(validate_row_group_size)
+                .help("The maximum number of rows in a row group"),
+            Arg::with_name("data-page-size")
+                .long("data-page-size")
+                .takes_value(true)
+                .required(false)
+                .value_name("SIZE")
+                .validator(validate_size)
+                .help("The target size of the data pages, e.g. 1MiB"),
+            Arg::with_name("dictionary")
+                .long("dictionary")
+                .takes_value(true)
+                .required(false)
+                .possible_values(&["on", "off"])
+                .default_value("on")
+                .help("Whether to use dictionary encoding for the columns"),
+            Arg::with_name("dictionary-column")
+                .long("dictionary-column")
+                .takes_value(true)
+                .required(false)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("COLUMN=on|off")
+                .validator(validate_dictionary_column)
+                .help("Enable or disable dictionary encoding for a single column"),
+            Arg::with_name("statistics")
+                .long("statistics")
+                .takes_value(true)
+                .required(false)
+                .possible_values(&["on", "off"])
+                .default_value("on")
+                .help("Whether to write the min, max and null count statistics of the column chunks"),
+            Arg::with_name("writer-version")
+                .long("writer-version")
+                .takes_value(true)
+                .required(false)
+                .possible_values(&["1.0", "2.0"])
+                .default_value("1.0")
+                .help("The version of the parquet format to write"),
+            Arg::with_name("created-by")
+                .long("created-by")
+                .takes_value(true)
+                .required(false)
+                .help("The application name written in the file metadata"),
+        ]
+    }
+
+    pub(crate) fn new(matches: &'a ArgMatches<'a>) -> Self {
+        // the validators make sure that the numbers and column settings are valid
+        let number = |name| matches.value_of(name).map(|v: &str| v.parse().unwrap());
+
+        Self {
+            compression: matches.value_of("compression").map(|codec| match codec {
+                "snappy" => Compression::SNAPPY,
+                "gzip" => Compression::GZIP,
+                "lzo" => Compression::LZO,
+                "brotli" => Compression::BROTLI,
+                "lz4" => Compression::LZ4,
+                "zstd" => Compression::ZSTD,
+                _ => Compression::UNCOMPRESSED,
+            }),
+            max_row_group_size: number("max-row-group-size"),
+            data_page_size: matches
+                .value_of("data-page-size")
+                .map(|size| parse_size(size).unwrap() as usize),
+            dictionary: matches.value_of("dictionary") != Some("off"),
+            dictionary_columns: matches
+                .values_of("dictionary-column")
+                .map(|values| values.map(parse_dictionary_column).collect())
+                .unwrap_or_default(),
+            statistics: matches.value_of("statistics") != Some("off"),
+            writer_version: match matches.value_of("writer-version") {
+                Some("2.0") => WriterVersion::PARQUET_2_0,
+                _ => WriterVersion::PARQUET_1_0,
+            },
+            created_by: matches.value_of("created-by"),
+        }
+    }
+
+    /// Build the writer properties, the default codec is used when no
+    /// compression codec was given explicitly
+    pub fn properties(&self, default_compression: Compression) -> WriterProperties {
+        self.builder(default_compression).build()
+    }
+
+    /// Return the builder of the writer properties, for the commands that need to set
+    /// more properties than the options, e.g. the key-value metadata
+    pub fn builder(&self, default_compression: Compression) -> WriterPropertiesBuilder {
+        let compression = self.compression.unwrap_or(default_compression);
+
+        let mut builder = WriterProperties::builder()
+            .set_compression(compression)
+            .set_dictionary_enabled(self.dictionary)
+            .set_statistics_enabled(self.statistics)
+            .set_writer_version(self.writer_version);
+        if let Some(size) = self.max_row_group_size {
+            builder = builder.set_max_row_group_size(size);
+        }
+        if let Some(size) = self.data_page_size {
+            builder = builder.set_data_pagesize_limit(size);
+        }
+        if let Some(created_by) = self.created_by {
+            builder = builder.set_created_by(created_by.to_string());
+        }
+        for (column, enabled) in &self.dictionary_columns {
+            let path = ColumnPath::new(column.split('.').map(String::from).collect());
+            builder = builder.set_column_dictionary_enabled(path, *enabled);
+        }
+
+        builder
+    }
+}
+
+impl<'a> fmt::Debug for WriterOptions<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.compression {
+            Some(compression) => write!(f, "compression: {}", compression)?,
+            None => write!(f, "compression: same as the first input")?,
+        }
+        if let Some(size) = self.max_row_group_size {
+            write!(f, ", max row group size: {}", size)?;
+        }
+        if let Some(size) = self.data_page_size {
+            write!(f, ", data page size: {}", size)?;
+        }
+        write!(f, ", dictionary: {}", self.dictionary)?;
+        for (column, enabled) in &self.dictionary_columns {
+            write!(f, ", dictionary for {}: {}", column, enabled)?;
+        }
+        write!(f, ", statistics: {}", self.statistics)?;
+        write!(f, ", writer version: {:?}", self.writer_version)?;
+        if let Some(created_by) = self.created_by {
+            write!(f, ", created by: {}", created_by)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Row groups need at least one row, the parquet writer panics otherwise
+fn validate_row_group_size(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(size) if size > 0 => Ok(()),
+        _ => Err(format!(
+            "Expected a number of rows greater than 0, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn validate_size(value: String) -> Result<(), String> {
+    parse_size(&value).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Make sure that the setting looks like `COLUMN=on` or `COLUMN=off`
+fn validate_dictionary_column(value: String) -> Result<(), String> {
+    match value.rsplit_once('=') {
+        Some((column, "on")) | Some((column, "off")) if !column.is_empty() => Ok(()),
+        _ => Err(format!(
+            "Expected a setting like COLUMN=on or COLUMN=off, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn parse_dictionary_column(value: &str) -> (&str, bool) {
+    // the validator makes sure that the value contains a separator
+    let (column, setting) = value.rsplit_once('=').unwrap();
+    (column, setting == "on")
+}
@@ -10,6 +10,13 @@ static CAT_JSON_OUTPUT: &'static str = r#"{"continent":"Europe","country":{"name
 {"continent":"Europe","country":{"name":"Greece","city":["Athens","Piraeus","Hania","Heraklion","Rethymnon","Fira"]}}
 {"continent":"North America","country":{"name":"Canada","city":["Toronto","Vancouver","St. John's","Saint John","Montreal","Halifax","Winnipeg","Calgary","Saskatoon","Ottawa","Yellowknife"]}}
 "#;
+static CAT_CSV_OUTPUT: &'static str = r#"continent,country.name,country.city
+Europe,France,"[""Paris"",""Nice"",""Marseilles"",""Cannes""]"
+Europe,Greece,"[""Athens"",""Piraeus"",""Hania"",""Heraklion"",""Rethymnon"",""Fira""]"
+"#;
+static CAT_TSV_OUTPUT: &'static str = "continent\tcountry.name\tcountry.city
+Europe\tFrance\t[\"Paris\",\"Nice\",\"Marseilles\",\"Cannes\"]
+";
 static SCHEMA_OUTPUT: &'static str = r#"message hive_schema {
   OPTIONAL BYTE_ARRAY continent (UTF8);
   OPTIONAL group country {
@@ -29,9 +36,9 @@ mod integration {
 
     // make sure any new commands added have a corresponding integration test here!
     use crate::{
-        CAT_JSON_OUTPUT, CAT_OUTPUT, CITIES_PARQUET_PATH, MERGED_FILE_NAME,
-        PEMS_1_PARQUET_PATH, PEMS_2_PARQUET_PATH, SAMPLE_PARTIAL_OUTPUT_1,
-        SAMPLE_PARTIAL_OUTPUT_2, SCHEMA_OUTPUT,
+        CAT_CSV_OUTPUT, CAT_JSON_OUTPUT, CAT_OUTPUT, CAT_TSV_OUTPUT, CITIES_PARQUET_PATH,
+        MERGED_FILE_NAME, PEMS_1_PARQUET_PATH, PEMS_2_PARQUET_PATH,
+        SAMPLE_PARTIAL_OUTPUT_1, SAMPLE_PARTIAL_OUTPUT_2, SCHEMA_OUTPUT,
     };
     use assert_cmd::Command;
     use predicates::prelude::*;
@@ -59,6 +66,485 @@ mod integration {
         Ok(())
     }
 
+    #[test]
+    fn validate_cat_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--format")
+            .arg("csv");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::starts_with(CAT_CSV_OUTPUT));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_csv_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--format")
+            .arg("csv")
+            .arg("--delimiter")
+            .arg(";");
+        cmd.assert().success().stdout(predicate::str::starts_with(
+            "continent;country.name;country.city\n",
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_delimiter_without_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--format")
+            .arg("tsv")
+            .arg("--delimiter")
+            .arg(";");
+        cmd.assert().failure().stderr(predicate::str::contains(
+            "can only be used with '--format csv'",
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_csv_different_columns() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--format")
+            .arg("csv");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("SchemaMismatch"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_head_tsv() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("head")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--format")
+            .arg("tsv")
+            .arg("-n")
+            .arg("1");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::similar(CAT_TSV_OUTPUT));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_columns() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--json")
+            .arg("--columns")
+            .arg("continent,country.name");
+        cmd.assert().success().stdout(predicate::str::starts_with(
+            r#"{"continent":"Europe","country":{"name":"France"}}"#,
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_unknown_column() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--columns")
+            .arg("country.population");
+        cmd.assert().failure().stderr(
+            predicate::str::contains("country.population")
+                .and(predicate::str::contains("continent, country, country.name")),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_where() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let lines: Vec<&str> = CAT_JSON_OUTPUT.split("\n").collect();
+        cmd.arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--json")
+            .arg("--where")
+            .arg("continent = 'Europe' AND country.name LIKE 'G%'");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::similar(format!("{}\n", lines[1])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_where_prunes_row_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("--debug")
+            .arg("cat")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--where")
+            .arg("continent IN ('Africa', 'Antarctica')");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::contains("Pruned 1 out of 1 row groups"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_cat_where_without_null_counts() -> Result<(), Box<dyn std::error::Error>>
+    {
+        use parquet::column::writer::ColumnWriter;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::{FileWriter, RowGroupWriter, SerializedFileWriter};
+        use parquet::schema::parser::parse_message_type;
+        use parquet_format::FileMetaData;
+        use std::sync::Arc;
+        use thrift::protocol::{
+            TCompactInputProtocol, TCompactOutputProtocol, TOutputProtocol,
+        };
+
+        // two records, the second one without a score
+        let dir = tempdir()?;
+        let file_path = dir.path().join("nulls.parquet");
+        let schema = Arc::new(parse_message_type(
+            "message schema { REQUIRED INT32 id; OPTIONAL INT32 score; }",
+        )?);
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer =
+            SerializedFileWriter::new(std::fs::File::create(&file_path)?, schema, props)?;
+        let mut row_group_writer = writer.next_row_group()?;
+        let mut column = 0;
+        while let Some(mut column_writer) = row_group_writer.next_column()? {
+            if let ColumnWriter::Int32ColumnWriter(ref mut typed_writer) = column_writer {
+                if column == 0 {
+                    typed_writer.write_batch(&[1, 2], None, None)?;
+                } else {
+                    typed_writer.write_batch(&[5], Some(&[1, 0]), None)?;
+                }
+            }
+            row_group_writer.close_column(column_writer)?;
+            column += 1;
+        }
+        writer.close_row_group(row_group_writer)?;
+        writer.close()?;
+
+        // remove the null counts from the statistics, as some writers do
+        let data = std::fs::read(&file_path)?;
+        let footer = &data[data.len() - 8..];
+        let length = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+        let metadata_start = data.len() - 8 - length as usize;
+        let mut metadata = FileMetaData::read_from_in_protocol(
+            &mut TCompactInputProtocol::new(&data[metadata_start..data.len() - 8]),
+        )?;
+        for row_group in &mut metadata.row_groups {
+            for chunk in &mut row_group.columns {
+                if let Some(statistics) = chunk
+                    .meta_data
+                    .as_mut()
+                    .and_then(|meta_data| meta_data.statistics.as_mut())
+                {
+                    statistics.null_count = None;
+                }
+            }
+        }
+        let mut encoded = Vec::new();
+        {
+            let mut protocol = TCompactOutputProtocol::new(&mut encoded);
+            metadata.write_to_out_protocol(&mut protocol)?;
+            protocol.flush()?;
+        }
+        let mut rewritten = data[..metadata_start].to_vec();
+        rewritten.extend(&encoded);
+        rewritten.extend(&(encoded.len() as u32).to_le_bytes());
+        rewritten.extend(b"PAR1");
+        std::fs::write(&file_path, rewritten)?;
+
+        // without a null count, the row group may contain nulls and cannot be pruned
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("--debug")
+            .arg("cat")
+            .arg(file_path.to_str().unwrap())
+            .arg("--json")
+            .arg("--where")
+            .arg("score IS NULL");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("\"id\":2"))
+            .stdout(predicate::str::contains("\"id\":1").not())
+            .stderr(predicate::str::contains("Pruned 0 out of 1 row groups"));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_head_where() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let lines: Vec<&str> = CAT_OUTPUT.split("\n").collect();
+        cmd.arg("head")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--where")
+            .arg("NOT continent = 'Europe'")
+            .arg("-n")
+            .arg("1");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::similar(format!("{}\n", lines[2])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_dictionary() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("dictionary")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("continent");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Row group 0, column continent: 2 values",
+            ))
+            .stdout(predicate::str::contains("North America"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_dictionary_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("dictionary")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("continent")
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let entries = document["values"].as_array().unwrap();
+
+        // the column chunk comes first, followed by the indices of its dictionary
+        let chunk = &entries[0];
+        assert_eq!(chunk["has_dictionary"], serde_json::json!(true));
+        assert_eq!(chunk["is_sorted"], serde_json::json!(false));
+        assert_eq!(chunk["nulls"], serde_json::json!(0));
+        assert_eq!(chunk["fallback_values"], serde_json::json!(0));
+        assert!(chunk["index"].is_null());
+
+        let counts: Vec<(String, u64)> = entries[1..]
+            .iter()
+            .map(|v| {
+                (
+                    v["value"].as_str().unwrap().to_string(),
+                    v["count"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (String::from("Europe"), 2),
+                (String::from("North America"), 1)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_layout() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("layout").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("column chunk"))
+            .stdout(predicate::str::contains("dictionary page"))
+            .stdout(predicate::str::contains("file metadata"))
+            .stdout(predicate::str::contains("No gaps or overlaps"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_layout_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("layout")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let regions = document["regions"].as_array().unwrap();
+        assert_eq!(regions[0]["kind"], "magic");
+        assert_eq!(regions[0]["end"], 4);
+        assert_eq!(regions.last().unwrap()["kind"], "footer");
+
+        // the regions other than the pages cover the whole file
+        let file_size = std::fs::metadata(CITIES_PARQUET_PATH)?.len();
+        let covered: u64 = regions
+            .iter()
+            .filter(|r| !r["kind"].as_str().unwrap().contains("page"))
+            .map(|r| r["size"].as_u64().unwrap())
+            .sum();
+        assert_eq!(covered, file_size);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_layout_gap() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let input = dir.path().join("gap.parquet");
+        let svg = dir.path().join("gap.svg");
+
+        // insert garbage between the last column chunk and the file metadata, which does
+        // not move the offsets written in the metadata
+        let data = std::fs::read(CITIES_PARQUET_PATH)?;
+        let footer = &data[data.len() - 8..];
+        let length = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+        let metadata_start = data.len() - 8 - length as usize;
+        let mut corrupted = data[..metadata_start].to_vec();
+        corrupted.extend(vec![0xab; 10]);
+        corrupted.extend(&data[metadata_start..]);
+        std::fs::write(&input, corrupted)?;
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("layout").arg(&input).arg("--svg").arg(&svg);
+        cmd.assert().success().stdout(predicate::str::contains(
+            "1 gap(s) of 10 bytes in total, 0 overlap(s)",
+        ));
+        assert!(std::fs::read_to_string(&svg)?.starts_with("<svg"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_footer() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("footer").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("num_rows: 3"))
+            .stdout(predicate::str::contains("name: hive_schema"))
+            .stdout(predicate::str::contains("path_in_schema:"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_footer_json_truncated() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("footer").arg(CITIES_PARQUET_PATH).arg("--json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(document["num_rows"], 3);
+        assert_eq!(document["schema"][0]["name"], "hive_schema");
+        assert_eq!(
+            document["row_groups"][0]["columns"][0]["meta_data"]["path_in_schema"],
+            serde_json::json!(["continent"])
+        );
+
+        // the footer can be read from the last bytes of the file only
+        let dir = tempdir()?;
+        let tail = dir.path().join("tail.bin");
+        let data = std::fs::read(CITIES_PARQUET_PATH)?;
+        let footer = &data[data.len() - 8..];
+        let length = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+        std::fs::write(&tail, &data[data.len() - 8 - length as usize..])?;
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("footer").arg(&tail).arg("--json");
+        cmd.assert().success().stdout(String::from_utf8(output)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_levels() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("levels")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("country.city.bag.array_element")
+            .arg("--limit")
+            .arg("1");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Max repetition level: 1"))
+            .stdout(predicate::str::contains("Max definition level: 4"))
+            .stdout(predicate::str::contains(
+                r#"0: ["Paris", "Nice", "Marseilles", "Cannes"]"#,
+            ))
+            .stdout(predicate::str::contains("Athens").not());
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_levels_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("levels")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("country.city.bag.array_element")
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let levels = document["levels"].as_array().unwrap();
+        assert_eq!(levels.len(), 21);
+        let first: Vec<(u64, u64, u64)> = levels
+            .iter()
+            .take(2)
+            .map(|l| {
+                (
+                    l["record"].as_u64().unwrap(),
+                    l["repetition_level"].as_u64().unwrap(),
+                    l["definition_level"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(first, vec![(0, 0, 4), (0, 1, 4)]);
+        assert_eq!(levels[4]["record"], 1);
+        assert_eq!(levels[4]["value"], r#""Athens""#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_levels_group_column() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("levels")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("country.city");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("UnknownColumn"));
+
+        Ok(())
+    }
+
     #[test]
     fn validate_head() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("pqrs")?;
@@ -97,60 +583,1327 @@ mod integration {
     }
 
     #[test]
-    fn validate_rowcount() -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_merge_many_inputs() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("pqrs")?;
-        cmd.arg("rowcount").arg(CITIES_PARQUET_PATH);
-        cmd.assert()
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge").arg("--input");
+        for _ in 0..10 {
+            cmd.arg(PEMS_1_PARQUET_PATH).arg(PEMS_2_PARQUET_PATH);
+        }
+        cmd.arg("--output").arg(file_name);
+        cmd.assert().success();
+
+        // the output contains all the rows from every input
+        let mut rowcount_cmd = Command::cargo_bin("pqrs")?;
+        rowcount_cmd.arg("rowcount").arg(file_name);
+        rowcount_cmd
+            .assert()
             .success()
-            .stdout(predicate::str::contains("3 rows"));
+            .stdout(predicate::str::contains(format!(
+                ": {} rows",
+                10 * (2693 + 2880)
+            )));
 
+        dir.close()?;
         Ok(())
     }
 
     #[test]
-    fn validate_sample() -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_merge_strict_schema_mismatch() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("pqrs")?;
-        cmd.arg("sample")
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
             .arg(CITIES_PARQUET_PATH)
-            .arg("--records")
-            .arg("1");
-        cmd.assert().success().stdout(
-            predicate::str::contains(SAMPLE_PARTIAL_OUTPUT_2)
-                .and(predicate::str::starts_with(SAMPLE_PARTIAL_OUTPUT_1)),
-        );
-
-        Ok(())
-    }
-
-    #[test]
-    fn validate_schema() -> Result<(), Box<dyn std::error::Error>> {
-        let mut cmd = Command::cargo_bin("pqrs")?;
-        cmd.arg("schema").arg(CITIES_PARQUET_PATH);
+            .arg("--output")
+            .arg(file_name);
         cmd.assert()
-            .success()
-            .stdout(predicate::str::contains(SCHEMA_OUTPUT));
+            .failure()
+            .stderr(predicate::str::contains("SchemaMismatch"))
+            .stderr(predicate::str::contains(
+                "is missing from data/cities.parquet",
+            ));
 
+        // nothing is written when the schemas do not match
+        assert!(!file_path.exists());
+
+        dir.close()?;
         Ok(())
     }
 
     #[test]
-    fn validate_uncompressed_size() -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_merge_intersect_without_common_columns(
+    ) -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("pqrs")?;
-        cmd.arg("size").arg(PEMS_1_PARQUET_PATH);
-        cmd.assert()
-            .success()
-            .stdout(predicate::str::contains("Uncompressed Size: 63085"));
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge")
+            .arg("--schema-mode")
+            .arg("intersect")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name);
+        cmd.assert().failure().stderr(predicate::str::contains(
+            "do not have any columns in common",
+        ));
 
+        dir.close()?;
         Ok(())
     }
 
     #[test]
-    fn validate_uncompressed_size_pretty() -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_merge_union() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("pqrs")?;
-        cmd.arg("size").arg(PEMS_1_PARQUET_PATH).arg("--pretty");
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge")
+            .arg("--schema-mode")
+            .arg("union")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name);
+        cmd.assert().success();
+
+        let mut rowcount_cmd = Command::cargo_bin("pqrs")?;
+        rowcount_cmd.arg("rowcount").arg(file_name);
+        rowcount_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("5573 rows"));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_merge_union_different_schemas() -> Result<(), Box<dyn std::error::Error>>
+    {
+        use arrow::array::{ArrayRef, Int32Array, Int64Array, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+        use std::sync::Arc;
+
+        let dir = tempdir()?;
+        let write = |name: &str,
+                     fields: Vec<Field>,
+                     columns: Vec<ArrayRef>|
+         -> Result<String, Box<dyn std::error::Error>> {
+            let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+            let file_path = dir.path().join(format!("{}.parquet", name));
+            let file = std::fs::File::create(&file_path)?;
+            let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+            writer.write(&batch)?;
+            writer.close()?;
+            Ok(file_path.to_str().unwrap().to_string())
+        };
+        let first = write(
+            "first",
+            vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("name", DataType::Utf8, true),
+            ],
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec![Some("one"), Some("two")])),
+            ],
+        )?;
+        let second = write(
+            "second",
+            vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("age", DataType::Int32, true),
+            ],
+            vec![
+                Arc::new(Int64Array::from(vec![5000000000])),
+                Arc::new(Int32Array::from(vec![Some(30)])),
+            ],
+        )?;
+
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("merge")
+            .arg("--schema-mode")
+            .arg("union")
+            .arg("--input")
+            .arg(&first)
+            .arg(&second)
+            .arg("--output")
+            .arg(file_name);
+        cmd.assert().success();
+
+        // the ids are widened to Int64 and the columns missing from an input are nullable
+        let mut schema_cmd = Command::cargo_bin("pqrs")?;
+        schema_cmd.arg("schema").arg(file_name);
+        schema_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("REQUIRED INT64 id;"))
+            .stdout(predicate::str::contains("OPTIONAL BYTE_ARRAY name (UTF8);"))
+            .stdout(predicate::str::contains("OPTIONAL INT32 age;"));
+
+        let mut cat_cmd = Command::cargo_bin("pqrs")?;
+        cat_cmd.arg("cat").arg(file_name).arg("--json");
+        cat_cmd.assert().success().stdout(predicate::str::similar(
+            r#"{"id":1,"name":"one","age":null}
+{"id":2,"name":"two","age":null}
+{"id":5000000000,"name":null,"age":30}
+"#,
+        ));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_merge_preserves_compression() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name);
+        cmd.assert().success();
+
+        let mut schema_cmd = Command::cargo_bin("pqrs")?;
+        schema_cmd.arg("schema").arg("--detailed").arg(file_name);
+        schema_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("compression: SNAPPY"));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_merge_writer_options() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--compression")
+            .arg("gzip")
+            .arg("--writer-version")
+            .arg("2.0")
+            .arg("--created-by")
+            .arg("pqrs integration test");
+        cmd.assert().success();
+
+        let mut schema_cmd = Command::cargo_bin("pqrs")?;
+        schema_cmd.arg("schema").arg("--detailed").arg(file_name);
+        schema_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("compression: GZIP"))
+            .stdout(predicate::str::contains("version: 2"))
+            .stdout(predicate::str::contains(
+                "created by: pqrs integration test",
+            ));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_merge_empty_row_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join(MERGED_FILE_NAME);
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("merge")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--max-row-group-size")
+            .arg("0");
+        cmd.assert().failure().stderr(predicate::str::contains(
+            "Expected a number of rows greater than 0",
+        ));
+        assert!(!file_path.exists());
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_convert_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join("pems-1.csv");
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("csv")
+            .arg("--columns")
+            .arg("timeperiod,flow1")
+            .arg("--limit")
+            .arg("10");
+        cmd.assert().success();
+
+        let contents = std::fs::read_to_string(&file_path)?;
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "timeperiod,flow1");
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_convert_json_nested_projection() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join("cities.json");
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("json")
+            .arg("--columns")
+            .arg("country.name");
+        cmd.assert().success();
+
+        let contents = std::fs::read_to_string(&file_path)?;
+        let first: serde_json::Value =
+            serde_json::from_str(contents.lines().next().unwrap())?;
+        assert_eq!(first, serde_json::json!({"country": {"name": "France"}}));
+        assert_eq!(contents.lines().count(), 3);
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_convert_gzip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join("pems-1.json.gz");
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("json")
+            .arg("--compression")
+            .arg("gzip");
+        cmd.assert().success();
+
+        // the output starts with the gzip magic bytes
+        let contents = std::fs::read(&file_path)?;
+        assert_eq!(&contents[..2], &[0x1f, 0x8b]);
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_convert_arrow() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join("pems-1.arrow");
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("arrow");
+        cmd.assert().success();
+
+        let contents = std::fs::read(&file_path)?;
+        assert_eq!(&contents[..6], b"ARROW1");
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_convert_output_exists() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output")
+            .arg(PEMS_2_PARQUET_PATH)
+            .arg("--format")
+            .arg("csv");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("FileExists"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_convert_arrow_compression() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        let dir = tempdir()?;
+        let file_path = dir.path().join("pems-1.arrow");
+        let file_name = file_path.to_str().unwrap();
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("arrow")
+            .arg("--compression")
+            .arg("zstd");
+        cmd.assert().failure().stderr(predicate::str::contains(
+            "Compression is only supported by the csv and json formats",
+        ));
+        assert!(!file_path.exists());
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_import_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let input_path = dir.path().join("input.csv");
+        std::fs::write(
+            &input_path,
+            "id;name;score\n1;'a;b';1.5\n2;NA;2\n3;c\n4;d;4.25\n",
+        )?;
+        let file_path = dir.path().join("imported.parquet");
+        let file_name = file_path.to_str().unwrap();
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("import")
+            .arg("--input")
+            .arg(input_path.to_str().unwrap())
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("csv")
+            .arg("--delimiter")
+            .arg(";")
+            .arg("--quote")
+            .arg("'")
+            .arg("--null")
+            .arg("NA");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Rows written: 3"))
+            .stdout(predicate::str::contains("Lines rejected: 1"))
+            .stderr(predicate::str::contains("Rejected line 4"));
+
+        let mut cat_cmd = Command::cargo_bin("pqrs")?;
+        cat_cmd.arg("cat").arg(file_name).arg("--json");
+        cat_cmd.assert().success().stdout(predicate::str::similar(
+            r#"{"id":1,"name":"a;b","score":1.5}
+{"id":2,"name":null,"score":2.0}
+{"id":4,"name":"d","score":4.25}
+"#,
+        ));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_import_json() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let input_path = dir.path().join("input.json");
+        std::fs::write(
+            &input_path,
+            r#"{"continent":"Europe","country":{"name":"France","population":67}}
+not json
+{"continent":"Asia","country":{"name":"Japan","population":125}}
+"#,
+        )?;
+        let file_path = dir.path().join("imported.parquet");
+        let file_name = file_path.to_str().unwrap();
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("import")
+            .arg("--input")
+            .arg(input_path.to_str().unwrap())
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("json");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Rows written: 2"))
+            .stdout(predicate::str::contains("Lines rejected: 1"));
+
+        let mut cat_cmd = Command::cargo_bin("pqrs")?;
+        cat_cmd.arg("cat").arg(file_name).arg("--json");
+        cat_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with(
+                r#"{"continent":"Europe","country":{"name":"France","population":67}}"#,
+            ));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_import_explicit_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let input_path = dir.path().join("input.csv");
+        std::fs::write(&input_path, "1,one\nx,two\n3,\n")?;
+        let schema_path = dir.path().join("schema.txt");
+        std::fs::write(
+            &schema_path,
+            "message schema { REQUIRED INT32 id; OPTIONAL BYTE_ARRAY name (UTF8); }",
+        )?;
+        let file_path = dir.path().join("imported.parquet");
+        let file_name = file_path.to_str().unwrap();
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("import")
+            .arg("--input")
+            .arg(input_path.to_str().unwrap())
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("csv")
+            .arg("--no-header")
+            .arg("--schema")
+            .arg(schema_path.to_str().unwrap());
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Rows written: 2"))
+            .stdout(predicate::str::contains("Lines rejected: 1"));
+
+        let mut schema_cmd = Command::cargo_bin("pqrs")?;
+        schema_cmd.arg("schema").arg(file_name);
+        schema_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("REQUIRED INT32 id;"));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_avro_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let avro_path = dir.path().join("cities.avro");
+        let avro_name = avro_path.to_str().unwrap();
+        let file_path = dir.path().join("cities.parquet");
+        let file_name = file_path.to_str().unwrap();
+
+        let mut convert_cmd = Command::cargo_bin("pqrs")?;
+        convert_cmd
+            .arg("convert")
+            .arg("--input")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--output")
+            .arg(avro_name)
+            .arg("--format")
+            .arg("avro");
+        convert_cmd.assert().success();
+
+        // avro object container files start with a magic number
+        let contents = std::fs::read(&avro_path)?;
+        assert_eq!(&contents[..4], b"Obj\x01");
+
+        let mut import_cmd = Command::cargo_bin("pqrs")?;
+        import_cmd
+            .arg("import")
+            .arg("--input")
+            .arg(avro_name)
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("avro");
+        import_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rows written: 3"));
+
+        let mut cat_cmd = Command::cargo_bin("pqrs")?;
+        cat_cmd.arg("cat").arg(file_name).arg("--json");
+        cat_cmd
+            .assert()
+            .success()
+            .stdout(predicate::str::similar(CAT_JSON_OUTPUT));
+
+        // the avro schema is kept in the metadata and used to export the file again
+        let mut export_cmd = Command::cargo_bin("pqrs")?;
+        export_cmd
+            .arg("--debug")
+            .arg("convert")
+            .arg("--input")
+            .arg(file_name)
+            .arg("--output")
+            .arg(dir.path().join("exported.avro").to_str().unwrap())
+            .arg("--format")
+            .arg("avro");
+        export_cmd
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "Using the avro schema from the metadata",
+            ));
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_query() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("query")
+            .arg("SELECT COUNT(*) AS total FROM t")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH)
+            .arg("--format")
+            .arg("csv");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::similar("total\n5573\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_query_group_by() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("query")
+            .arg("SELECT flow4 IS NULL AS missing, COUNT(*) AS total FROM t GROUP BY flow4 IS NULL")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--json");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(r#""missing":true"#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_query_nested_columns() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("query")
+            .arg("SELECT * FROM t LIMIT 1")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--json");
+        cmd.assert().success().stdout(predicate::str::contains(
+            r#""country":{"name":"France","city":["Paris","Nice","Marseilles","Cannes"]}"#,
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_query_unknown_table() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("query")
+            .arg("SELECT * FROM pems")
+            .arg(PEMS_1_PARQUET_PATH);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("Unknown table pems"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_query_schema_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("query")
+            .arg("SELECT COUNT(*) FROM t")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("SchemaMismatch"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_rowcount() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("rowcount").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("3 rows"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_rowcount_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("--output-format")
+            .arg("json")
+            .arg("rowcount")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH);
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(document["files"][0]["path"], PEMS_1_PARQUET_PATH);
+        assert_eq!(document["files"][0]["rows"], 2693);
+        assert_eq!(document["files"][1]["rows"], 2880);
+        assert_eq!(document["total"]["rows"], 2693 + 2880);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_sample() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("sample")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--records")
+            .arg("1");
+        cmd.assert().success().stdout(
+            predicate::str::contains(SAMPLE_PARTIAL_OUTPUT_2)
+                .and(predicate::str::starts_with(SAMPLE_PARTIAL_OUTPUT_1)),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_sample_seed() -> Result<(), Box<dyn std::error::Error>> {
+        let sample = || -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            let mut cmd = Command::cargo_bin("pqrs")?;
+            cmd.arg("sample")
+                .arg(PEMS_1_PARQUET_PATH)
+                .arg("--records")
+                .arg("5")
+                .arg("--seed")
+                .arg("42");
+            Ok(cmd.assert().success().get_output().stdout.clone())
+        };
+
+        // the same seed always produces the same sample
+        let first = sample()?;
+        assert_eq!(first, sample()?);
+        assert_eq!(String::from_utf8(first)?.lines().count(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_sample_fraction() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("sample")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--fraction")
+            .arg("1")
+            .arg("--preserve-order");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(CAT_OUTPUT));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_sample_rowgroup_cluster() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("sample")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--records")
+            .arg("2")
+            .arg("--method")
+            .arg("rowgroup-cluster");
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(String::from_utf8(output)?.lines().count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_sample_skips_row_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("--debug")
+            .arg("sample")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--fraction")
+            .arg("0");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::contains("Read 0 out of 1 row groups"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_sample_rowgroup_cluster_picks_whole_groups(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // nine records written in row groups of three: ids 1-3, 4-6 and 7-9
+        let dir = tempdir()?;
+        let input_path = dir.path().join("input.csv");
+        let records: Vec<String> = (1..=9).map(|id| id.to_string()).collect();
+        std::fs::write(&input_path, format!("id\n{}\n", records.join("\n")))?;
+        let file_path = dir.path().join("row-groups.parquet");
+        let file_name = file_path.to_str().unwrap();
+        let mut import_cmd = Command::cargo_bin("pqrs")?;
+        import_cmd
+            .arg("import")
+            .arg("--input")
+            .arg(input_path.to_str().unwrap())
+            .arg("--output")
+            .arg(file_name)
+            .arg("--format")
+            .arg("csv")
+            .arg("--max-row-group-size")
+            .arg("3");
+        import_cmd.assert().success();
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("--debug")
+            .arg("sample")
+            .arg(file_name)
+            .arg("--records")
+            .arg("5")
+            .arg("--method")
+            .arg("rowgroup-cluster")
+            .arg("--seed")
+            .arg("42")
+            .arg("--preserve-order")
+            .arg("--json");
+        let assert = cmd
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Read 2 out of 3 row groups"));
+
+        let output = String::from_utf8(assert.get_output().stdout.clone())?;
+        let ids: Vec<usize> = output
+            .lines()
+            .map(|line| {
+                line.trim_start_matches("{\"id\":")
+                    .trim_end_matches('}')
+                    .parse()
+            })
+            .collect::<Result<_, _>>()?;
+        assert_eq!(ids.len(), 5);
+
+        // the first chosen row group is returned whole, the second one up to the limit
+        let groups: Vec<usize> = ids.iter().map(|id| (id - 1) / 3).collect();
+        assert!(groups[..3].iter().all(|&g| g == groups[0]));
+        assert!(groups[3..].iter().all(|&g| g == groups[3]));
+        assert!(groups[0] < groups[3]);
+        assert_eq!(
+            ids[..3],
+            [groups[0] * 3 + 1, groups[0] * 3 + 2, groups[0] * 3 + 3]
+        );
+        assert_eq!(ids[3..], [groups[3] * 3 + 1, groups[3] * 3 + 2]);
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains(SCHEMA_OUTPUT));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(document["files"][0]["rows"], 3);
+        assert_eq!(document["files"][0]["fields"][0]["name"], "continent");
+        assert_eq!(
+            document["files"][0]["fields"][0]["type"],
+            "BYTE_ARRAY (UTF8)"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_as_spark() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--as")
+            .arg("spark");
+        cmd.assert().success().stdout(predicate::str::contains(
+            "CREATE TABLE `cities` (\n  `continent` STRING,\n  \
+             `country` STRUCT<`name`: STRING, `city`: ARRAY<STRING>>\n) USING PARQUET;",
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_as_bigquery() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--as")
+            .arg("bigquery");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let fields: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(fields[0]["name"], "continent");
+        assert_eq!(fields[0]["type"], "STRING");
+        assert_eq!(fields[1]["type"], "RECORD");
+        assert_eq!(fields[1]["fields"][1]["name"], "city");
+        assert_eq!(fields[1]["fields"][1]["mode"], "REPEATED");
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_as_postgres_warns() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--as")
+            .arg("postgres");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("\"country\" jsonb"))
+            .stderr(predicate::str::contains(
+                "The column country of type STRUCT cannot be mapped to Postgres",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_check_consistent() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema")
+            .arg("--check-consistent")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("2 file(s), majority"));
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema")
+            .arg("--check-consistent")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(CITIES_PARQUET_PATH)
+            .arg(PEMS_2_PARQUET_PATH)
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "2 distinct schemas found across 3 files",
+            ))
+            .get_output()
+            .stdout
+            .clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let schemas = &document["schemas"];
+        assert_eq!(schemas[0]["majority"], true);
+        assert_eq!(schemas[0]["file_count"], 2);
+        assert_eq!(schemas[1]["files"][0], CITIES_PARQUET_PATH);
+        assert_eq!(schemas[1]["differences"][0]["change"], "removed");
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_diff_identical() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema-diff")
+            .arg(CITIES_PARQUET_PATH)
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--compatibility")
+            .arg("full");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("The schemas are identical"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_schema_diff_breaking() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let import = |name: &str,
+                      message_type: &str,
+                      row: &str|
+         -> Result<String, Box<dyn std::error::Error>> {
+            let input_path = dir.path().join(format!("{}.csv", name));
+            std::fs::write(&input_path, row)?;
+            let schema_path = dir.path().join(format!("{}.txt", name));
+            std::fs::write(&schema_path, message_type)?;
+            let file_path = dir.path().join(format!("{}.parquet", name));
+            let mut cmd = Command::cargo_bin("pqrs")?;
+            cmd.arg("import")
+                .arg("--input")
+                .arg(input_path.to_str().unwrap())
+                .arg("--output")
+                .arg(file_path.to_str().unwrap())
+                .arg("--format")
+                .arg("csv")
+                .arg("--no-header")
+                .arg("--schema")
+                .arg(schema_path.to_str().unwrap());
+            cmd.assert().success();
+            Ok(file_path.to_str().unwrap().to_string())
+        };
+        let old = import(
+            "old",
+            "message schema { REQUIRED INT32 id; OPTIONAL BYTE_ARRAY name (UTF8); }",
+            "1,one\n",
+        )?;
+        let new = import(
+            "new",
+            "message schema { REQUIRED INT64 id; REQUIRED BYTE_ARRAY name (UTF8); \
+             OPTIONAL INT32 age; }",
+            "1,one,30\n",
+        )?;
+
+        // the old files may have null names, which the new readers do not expect
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema-diff").arg(&old).arg(&new);
+        cmd.assert()
+            .failure()
+            .stdout(predicate::str::contains("repetition changed"))
+            .stdout(predicate::str::contains("OPTIONAL INT32"))
+            .stderr(predicate::str::contains("SchemaMismatch"));
+
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("schema-diff")
+            .arg(&old)
+            .arg(&new)
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().failure().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let changes = &document["changes"];
+        assert_eq!(changes[0]["change"], "type changed");
+        assert_eq!(changes[0]["path"], "id");
+        assert_eq!(changes[0]["breaking"], false);
+        assert_eq!(changes[1]["change"], "repetition changed");
+        assert_eq!(changes[1]["breaking"], true);
+        assert_eq!(changes[2]["change"], "added");
+        assert_eq!(changes[2]["new"], "OPTIONAL INT32");
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_size_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("size")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--output-format")
+            .arg("csv");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::starts_with(
+                "path,rows,compressed_bytes,uncompressed_bytes,fields\n",
+            ))
+            .stdout(predicate::str::contains(format!(
+                "{},2693,13067,63085,",
+                PEMS_1_PARQUET_PATH
+            )))
+            .stdout(predicate::str::contains("total,5386,26134,126170,\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_size_by_column() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("size").arg(PEMS_1_PARQUET_PATH).arg("--by-column");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Column"))
+            .stdout(predicate::str::contains("timeperiod"))
+            .stdout(predicate::str::contains("footer"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_size_by_row_group_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("size")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--by-row-group")
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let parts = document["parts"].as_array().unwrap();
+        let size = |part: &serde_json::Value| part["compressed_bytes"].as_i64().unwrap();
+
+        // the parts are sorted with the biggest first
+        assert!(parts.windows(2).all(|w| size(&w[0]) >= size(&w[1])));
+
+        // the row groups add up to the compressed size, the footer is a part of its own
+        let row_groups: i64 = parts
+            .iter()
+            .filter(|part| part["part"] == "column chunks")
+            .map(size)
+            .sum();
+        assert_eq!(row_groups, 13067);
+        let footers: Vec<_> = parts
+            .iter()
+            .filter(|part| part["part"] == "footer")
+            .collect();
+        assert_eq!(footers.len(), 1);
+        assert!(footers[0]["row_group"].is_null());
+
+        // the total is the sum of the parts, footer included
+        let total: i64 = parts.iter().map(size).sum();
+        assert_eq!(total, 13067 + size(footers[0]));
+        assert_eq!(
+            document["total"]["compressed_bytes"].as_i64().unwrap(),
+            total
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("stats").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("continent"))
+            .stdout(predicate::str::contains("Europe"))
+            .stdout(predicate::str::contains("North America"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_stats_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("stats")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--compute")
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let continent = &document["columns"][0];
+        assert_eq!(continent["column"], "continent");
+        assert_eq!(continent["min"], "Europe");
+        assert_eq!(continent["max"], "North America");
+        assert_eq!(continent["null_count"], 0);
+        assert_eq!(continent["row_groups"], 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_pages() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("pages").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Row group 0, column continent:"))
+            .stdout(predicate::str::contains(
+                "Row group 0, column country.city.bag.array_element:",
+            ))
+            .stdout(predicate::str::contains("DATA_PAGE"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_pages_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("pages")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("continent")
+            .arg("--row-group")
+            .arg("0")
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let pages = document["pages"].as_array().unwrap();
+        assert!(pages.iter().all(|p| p["column"] == "continent"));
+        let values: i64 = pages
+            .iter()
+            .filter(|p| p["page_type"] == "DATA_PAGE")
+            .map(|p| p["num_values"].as_i64().unwrap())
+            .sum();
+        assert_eq!(values, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_pages_unknown_column() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("pages")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--column")
+            .arg("planet");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("UnknownColumn"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_profile() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("profile").arg(CITIES_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Column: continent"))
+            .stdout(predicate::str::contains("Column: country.name"))
+            .stdout(predicate::str::contains("distinct: 2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_profile_invalid_top() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("profile")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--top")
+            .arg("five");
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("must be a number"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_profile_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("profile")
+            .arg(CITIES_PARQUET_PATH)
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let continent = &document["columns"][0];
+        assert_eq!(continent["column"], "continent");
+        assert_eq!(continent["count"], 3);
+        assert_eq!(continent["nulls"], 0);
+        assert_eq!(continent["distinct"], 2);
+        assert_eq!(continent["top"][0]["value"], "Europe");
+        assert_eq!(continent["top"][0]["count"], 2);
+        let country = &document["columns"][1];
+        assert_eq!(country["column"], "country.name");
+        assert_eq!(country["length"]["max"], 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_profile_approximate() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("profile")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--approximate")
+            .arg("--output-format")
+            .arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let document: serde_json::Value = serde_json::from_slice(&output)?;
+        let first = &document["columns"][0];
+        assert_eq!(first["count"], 2693);
+        assert_eq!(first["approximate"], true);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_uncompressed_size() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("size").arg(PEMS_1_PARQUET_PATH);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Uncompressed Size: 63085"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_uncompressed_size_pretty() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("size").arg(PEMS_1_PARQUET_PATH).arg("--pretty");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Uncompressed Size: 61.606 KiB"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_uncompressed_size_si() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("pqrs")?;
+        cmd.arg("size")
+            .arg(PEMS_1_PARQUET_PATH)
+            .arg("--pretty")
+            .arg("--si");
         cmd.assert()
             .success()
-            .stdout(predicate::str::contains("Uncompressed Size: 61 KiB"));
+            .stdout(predicate::str::contains("Uncompressed Size: 63.085 kB"));
 
         Ok(())
     }
@@ -175,7 +1928,7 @@ mod integration {
             .arg("--pretty");
         cmd.assert()
             .success()
-            .stdout(predicate::str::contains("Compressed Size: 12 KiB"));
+            .stdout(predicate::str::contains("Compressed Size: 12.761 KiB"));
 
         Ok(())
     }